@@ -0,0 +1,195 @@
+use crate::registry::Part;
+use crate::solver::Answer;
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// expected answers, read from a small subset of TOML :
+/// `[dayN]` tables holding `part1`/`part2` integers, "strings" or """multi-line strings"""
+#[derive(Debug, Default, PartialEq)]
+pub struct Answers {
+    expected: HashMap<(usize, Part), String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Check {
+    Pass,
+    Fail {
+        expected: String,
+    },
+    /// nothing to compare with
+    Missing,
+}
+
+// multi-line answers are compared without trailing spaces or surrounding blank lines
+fn normalize(answer: &str) -> String {
+    answer
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches('\n')
+        .to_string()
+}
+
+impl Answers {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = crate::input::read_file(path)?;
+        Self::parse(&content).with_context(|| format!("invalid answers in {}", path.display()))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut expected = HashMap::new();
+        let mut day: Option<usize> = None;
+        let mut lines = content.lines().enumerate();
+
+        while let Some((i, line)) = lines.next() {
+            let line = line.trim();
+            let at = || format!("line {}", i + 1);
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(table) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let number = table
+                    .trim()
+                    .strip_prefix("day")
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(|| anyhow!("'{}' is not a [dayN] table", line))
+                    .with_context(at)?;
+                day = Some(number);
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow!("expecting 'part1 = ...' in '{}'", line))
+                .with_context(at)?;
+            let day = day
+                .ok_or_else(|| anyhow!("'{}' is outside of a [dayN] table", key.trim()))
+                .with_context(at)?;
+            let part = match key.trim() {
+                "part1" => Part::One,
+                "part2" => Part::Two,
+                unknown => Err(anyhow!("unknown key '{}'", unknown)).with_context(at)?,
+            };
+
+            let value = value.trim();
+            let answer = if let Some(first) = value.strip_prefix("\"\"\"") {
+                let mut text = vec![first];
+                loop {
+                    let (_, l) = lines
+                        .next()
+                        .ok_or_else(|| anyhow!("unterminated multi-line string"))
+                        .with_context(at)?;
+                    if let Some(last) = l.strip_suffix("\"\"\"") {
+                        text.push(last);
+                        break;
+                    }
+                    text.push(l);
+                }
+                text.join("\n")
+            } else if let Some(text) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+                text.to_string()
+            } else if value.parse::<usize>().is_ok() {
+                value.to_string()
+            } else {
+                return Err(anyhow!("'{}' is neither a number nor a string", value))
+                    .with_context(at);
+            };
+
+            if expected.insert((day, part), normalize(&answer)).is_some() {
+                return Err(anyhow!("day {} part {} is given twice", day, part)).with_context(at);
+            }
+        }
+
+        Ok(Self { expected })
+    }
+
+    pub fn expected(&self, day: usize, part: Part) -> Option<&str> {
+        self.expected.get(&(day, part)).map(String::as_str)
+    }
+
+    pub fn check(&self, day: usize, part: Part, answer: &Answer) -> Check {
+        Check::new(self.expected(day, part), answer)
+    }
+}
+
+impl Check {
+    pub fn new(expected: Option<&str>, answer: &Answer) -> Self {
+        match expected.map(normalize) {
+            None => Check::Missing,
+            Some(expected) if normalize(&answer.to_string()) == expected => Check::Pass,
+            Some(expected) => Check::Fail { expected },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_can_be_parsed() {
+        let answers = Answers::parse(
+            r#"
+# comment
+[day1]
+part1 = 7
+part2 = "five"
+
+[day13]
+part2 = """
+Paper(4,1)
+#  #
+ ##
+"""
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(Some("7"), answers.expected(1, Part::One));
+        assert_eq!(Some("five"), answers.expected(1, Part::Two));
+        assert_eq!(
+            Some("Paper(4,1)\n#  #\n ##"),
+            answers.expected(13, Part::Two)
+        );
+        assert_eq!(None, answers.expected(13, Part::One));
+
+        assert_eq!(Check::Pass, answers.check(1, Part::One, &Answer::Number(7)));
+        assert_eq!(
+            Check::Pass,
+            answers.check(
+                13,
+                Part::Two,
+                &Answer::Text("Paper(4,1)\n#  #\n ## \n".to_string())
+            )
+        );
+        assert_eq!(
+            Check::Fail {
+                expected: "7".to_string()
+            },
+            answers.check(1, Part::One, &Answer::Number(8))
+        );
+        assert_eq!(
+            Check::Missing,
+            answers.check(2, Part::One, &Answer::Number(8))
+        );
+    }
+
+    #[test]
+    fn invalid_answers_are_rejected() {
+        assert!(Answers::parse("part1 = 3").is_err());
+        assert!(Answers::parse("[day1]\npart3 = 3").is_err());
+        assert!(Answers::parse("[day1]\npart1 = three").is_err());
+        assert!(Answers::parse("[day1]\npart1 = 3\npart1 = 4").is_err());
+        assert!(Answers::parse("[day1]\npart1 = \"\"\"\nunterminated").is_err());
+        assert!(Answers::parse("[days]").is_err());
+    }
+
+    #[test]
+    fn bundled_answers_are_valid() {
+        let answers = Answers::load(&crate::input::default_resources_dir().join("answers.toml"))
+            .expect("bundled answers");
+        assert!(answers.expected(24, Part::Two).is_some());
+    }
+}
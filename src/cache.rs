@@ -0,0 +1,191 @@
+use crate::registry::{Day, Part};
+use crate::solver::Answer;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const EXTENSION: &str = "answer";
+
+/// aoc2021 in the user cache directory, or .answer_cache in the current one without it
+pub fn default_cache_dir() -> PathBuf {
+    let var = |name| std::env::var_os(name).filter(|v| !v.is_empty());
+    var("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| var("HOME").map(|home| Path::new(&home).join(".cache")))
+        .or_else(|| var("LOCALAPPDATA").map(PathBuf::from))
+        .map_or_else(|| PathBuf::from(".answer_cache"), |dir| dir.join("aoc2021"))
+}
+
+/// FNV-1a, which unlike the std hasher gives the same hash with every compiler
+pub fn input_hash(input: &str) -> u64 {
+    input.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// answer as it was printed when it was solved
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedAnswer {
+    pub answer: Answer,
+    pub description: String,
+}
+
+impl CachedAnswer {
+    // kind, number of answer lines, answer lines, then the description
+    fn encode(&self) -> String {
+        let (kind, value) = match &self.answer {
+            Answer::Number(n) => ("number", n.to_string()),
+            Answer::Text(t) => ("text", t.clone()),
+            Answer::None => ("none", String::new()),
+        };
+        format!(
+            "{}\n{}\n{}\n{}",
+            kind,
+            value.split('\n').count(),
+            value,
+            self.description
+        )
+    }
+
+    fn decode(content: &str) -> Option<Self> {
+        let mut lines = content.split('\n');
+        let kind = lines.next()?;
+        let count: usize = lines.next()?.parse().ok()?;
+        let value = lines.by_ref().take(count).collect::<Vec<_>>().join("\n");
+        let answer = match kind {
+            "number" => Answer::Number(value.parse().ok()?),
+            "text" => Answer::Text(value),
+            "none" => Answer::None,
+            _ => return None,
+        };
+        Some(Self {
+            answer,
+            description: lines.collect::<Vec<_>>().join("\n"),
+        })
+    }
+}
+
+/// answers already solved, one file for each day, part, input and solver version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnswerCache {
+    dir: PathBuf,
+}
+
+impl Default for AnswerCache {
+    fn default() -> Self {
+        Self::new(default_cache_dir())
+    }
+}
+
+impl AnswerCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, day: &Day, part: Part, input: &str) -> PathBuf {
+        self.dir.join(format!(
+            "day{}_part{}_{:016x}_v{}.{}",
+            day.number,
+            part,
+            input_hash(input),
+            day.version,
+            EXTENSION
+        ))
+    }
+
+    /// none when missing or unreadable, the part is then solved again
+    pub fn get(&self, day: &Day, part: Part, input: &str) -> Option<CachedAnswer> {
+        let content = fs::read_to_string(self.path(day, part, input)).ok()?;
+        CachedAnswer::decode(&content)
+    }
+
+    pub fn store(&self, day: &Day, part: Part, input: &str, cached: &CachedAnswer) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("could not create {}", self.dir.display()))?;
+        let path = self.path(day, part, input);
+        fs::write(&path, cached.encode())
+            .with_context(|| format!("could not write {}", path.display()))
+    }
+
+    /// number of answers removed, other files of the directory are left alone
+    pub fn clear(&self) -> Result<usize> {
+        if !self.dir.exists() {
+            return Ok(0);
+        }
+        let mut removed = 0;
+        let entries = fs::read_dir(&self.dir)
+            .with_context(|| format!("could not read {}", self.dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == EXTENSION) {
+                fs::remove_file(&path)
+                    .with_context(|| format!("could not remove {}", path.display()))?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::get_day;
+
+    fn temp_cache(name: &str) -> AnswerCache {
+        let dir = std::env::temp_dir().join(format!("aoc2021_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        AnswerCache::new(dir)
+    }
+
+    #[test]
+    fn answers_survive_encoding() {
+        for answer in [
+            Answer::Number(42),
+            Answer::Text("#.#\n.#.\n".to_string()),
+            Answer::Text(String::new()),
+            Answer::None,
+        ] {
+            let cached = CachedAnswer {
+                answer,
+                description: "part 1 : some\nlines".to_string(),
+            };
+            assert_eq!(Some(cached.clone()), CachedAnswer::decode(&cached.encode()));
+        }
+        assert_eq!(None, CachedAnswer::decode("number\n1\nx\n"));
+        assert_eq!(None, CachedAnswer::decode(""));
+    }
+
+    #[test]
+    fn answers_are_keyed_by_input() {
+        let cache = temp_cache("keyed");
+        let day = get_day(1).unwrap();
+        let cached = CachedAnswer {
+            answer: Answer::Number(7),
+            description: "part 1 : 7".to_string(),
+        };
+        assert_eq!(None, cache.get(day, Part::One, "1\n2\n"));
+
+        cache.store(day, Part::One, "1\n2\n", &cached).unwrap();
+        assert_eq!(Some(cached), cache.get(day, Part::One, "1\n2\n"));
+        assert_eq!(None, cache.get(day, Part::Two, "1\n2\n"));
+        assert_eq!(None, cache.get(day, Part::One, "1\n3\n"));
+        assert_eq!(None, cache.get(get_day(2).unwrap(), Part::One, "1\n2\n"));
+
+        fs::write(cache.dir().join("notes.txt"), "kept").unwrap();
+        assert_eq!(1, cache.clear().unwrap());
+        assert_eq!(None, cache.get(day, Part::One, "1\n2\n"));
+        assert!(cache.dir().join("notes.txt").exists());
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn hashes_depend_on_every_byte() {
+        assert_eq!(0xcbf2_9ce4_8422_2325, input_hash(""));
+        assert_ne!(input_hash("12"), input_hash("21"));
+    }
+}
@@ -0,0 +1,660 @@
+use anyhow::{anyhow, Context, Result};
+use aoc2021::cache::AnswerCache;
+use aoc2021::export::ImageFormat;
+use aoc2021::input::InputSource;
+use aoc2021::output::OutputFormat;
+use aoc2021::registry::{all_days, get_day, Day, Part};
+use aoc2021::timing::ReportFormat;
+use aoc2021::trace::TraceFilter;
+use itertools::Itertools;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub const USAGE: &str = "\
+usage: aoc2021 [--all] [--day <days>] [--part <1|2>]
+               [--input <file> | --resources <dir> | --example <n>]
+               [--check] [--format <text|json|tsv>] [--timings <file>] [--parallel]
+               [--trace <filter>] [--no-cache]
+       aoc2021 --animate --day <day> [--delay <ms>] [--steps <range>] [--frames <dir>]
+       aoc2021 --export <file> --day <day> [--scale <n>]
+       aoc2021 --clear-cache
+       aoc2021 bench [--day <days>] [--part <1|2>] [--runs <n>] [--warmup <n>]
+       aoc2021 generate --day <day> [--size <n>] [--seed <n>]
+
+  -a, --all               run every day (default)
+  -d, --day <days>        run the given days : 19, 10-15 or 1,3,20-25
+  -p, --part <1|2>        run only the given part of each selected day
+  -i, --input <file>      read the puzzle input from this file ('-' for stdin), single day only
+  -r, --resources <dir>   read each day's puzzle input from this directory, instead of the bundled ones
+  -e, --example <n>       solve the n-th worked example of the puzzle text, single day only
+  -c, --check             compare fresh answers with the resources answers.toml, fail on any mismatch
+  -f, --format <format>   print the answers as text (default), json records or day,part,answer,ms tsv
+  -t, --timings <file>    write parse and solve durations to a .json or .csv report
+  -j, --parallel          run the days across a thread pool, still printed in calendar order
+  -T, --trace <filter>    print solver internals to stderr : debug, day24=trace or info,day19=debug
+      --no-cache          solve every part again, instead of reusing the answers cached by earlier runs
+      --clear-cache       remove every cached answer, without solving anything
+  -h, --help              print this help
+
+bench measures each part, and its alternative strategies, over several runs
+  -n, --runs <n>          number of measured runs (default 10)
+  -w, --warmup <n>        number of runs discarded before measuring (default 1)
+
+--animate draws each step of a single day evolving a state (6, 11, 20 and 25)
+      --delay <ms>        pause between two frames (default 100)
+      --steps <range>     draw only these steps : 10, 10-20 or 10-
+      --frames <dir>      write each frame, without colors, to a text file of this directory instead
+
+--export writes the spatial result of a single day (2, 5, 9, 13, 15, 20 and 25) to a .ppm, .png or .svg file
+      --scale <n>         size of the square drawn for each cell (default 4)
+
+generate prints a random puzzle input for a single day, the same for a given seed
+  -s, --size <n>          how big the input is, in the day's own unit (default 10)
+  -x, --seed <n>          seed of the random draws (default 1)
+
+built with --features count-allocations, the timings also give the allocations and peak heap
+of each day and part, left out with --parallel";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bench {
+    pub runs: usize,
+    pub warmup: usize,
+}
+
+impl Default for Bench {
+    fn default() -> Self {
+        Self {
+            runs: 10,
+            warmup: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Generate {
+    pub size: usize,
+    pub seed: u64,
+}
+
+impl Default for Generate {
+    fn default() -> Self {
+        Self { size: 10, seed: 1 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Animate {
+    pub delay: Duration,
+    /// first and last steps drawn, both included
+    pub steps: (usize, usize),
+    /// frames are written to this directory instead of being drawn
+    pub frames: Option<PathBuf>,
+}
+
+impl Default for Animate {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(100),
+            steps: (0, usize::MAX),
+            frames: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Export {
+    pub path: PathBuf,
+    pub format: ImageFormat,
+    pub scale: usize,
+}
+
+pub struct Options {
+    pub days: Vec<&'static Day>,
+    pub parts: Vec<Part>,
+    pub input: InputSource,
+    pub check: bool,
+    pub format: OutputFormat,
+    pub timings: Option<(PathBuf, ReportFormat)>,
+    pub parallel: bool,
+    pub trace: Option<TraceFilter>,
+    /// none when answers must be solved again
+    pub cache: Option<AnswerCache>,
+    pub clear_cache: bool,
+    pub bench: Option<Bench>,
+    pub generate: Option<Generate>,
+    pub animate: Option<Animate>,
+    pub export: Option<Export>,
+    pub help: bool,
+}
+
+// parse '19', '10-15' or '1,3,20-25' into day numbers
+fn parse_days(spec: &str) -> Result<Vec<usize>> {
+    let mut days = vec![];
+    for chunk in spec.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+        if let Some((first, last)) = chunk.split('-').collect_tuple() {
+            let first: usize = first
+                .trim()
+                .parse()
+                .with_context(|| format!("invalid day range '{}'", chunk))?;
+            let last: usize = last
+                .trim()
+                .parse()
+                .with_context(|| format!("invalid day range '{}'", chunk))?;
+            if first > last {
+                return Err(anyhow!("day range '{}' is reversed", chunk));
+            }
+            days.extend(first..=last);
+        } else {
+            days.push(
+                chunk
+                    .parse()
+                    .with_context(|| format!("invalid day '{}'", chunk))?,
+            );
+        }
+    }
+    if days.is_empty() {
+        return Err(anyhow!("no day selected by '{}'", spec));
+    }
+    Ok(days)
+}
+
+// parse '10', '10-20' or '10-' into the first and last steps
+fn parse_steps(spec: &str) -> Result<(usize, usize)> {
+    let step = |s: &str| {
+        s.trim()
+            .parse::<usize>()
+            .with_context(|| format!("invalid steps '{}'", spec))
+    };
+    match spec.split_once('-') {
+        Some((first, "")) => Ok((step(first)?, usize::MAX)),
+        Some((first, last)) => {
+            let (first, last) = (step(first)?, step(last)?);
+            if first > last {
+                return Err(anyhow!("steps '{}' are reversed", spec));
+            }
+            Ok((first, last))
+        }
+        None => step(spec).map(|s| (s, s)),
+    }
+}
+
+impl Options {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut args = args.into_iter().peekable();
+        let mut bench = None;
+        let mut generate = None;
+        match args.peek().map(String::as_str) {
+            Some("bench") => bench = Some(Bench::default()),
+            Some("generate") => generate = Some(Generate::default()),
+            _ => {}
+        }
+        if bench.is_some() || generate.is_some() {
+            args.next();
+        }
+
+        let mut day_numbers: Vec<usize> = vec![];
+        let mut all = false;
+        let mut parts: Vec<Part> = vec![];
+        let mut input: Option<InputSource> = None;
+        let mut check = false;
+        let mut format = OutputFormat::default();
+        let mut timings = None;
+        let mut parallel = false;
+        let mut trace = None;
+        let mut no_cache = false;
+        let mut clear_cache = false;
+        let mut animate = false;
+        let mut animation = Animate::default();
+        let mut animation_options = vec![];
+        let mut export = None;
+        let mut scale = None;
+        let mut help = false;
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| anyhow!("missing value after {}", name))
+            };
+            match arg.as_str() {
+                "-a" | "--all" => all = true,
+                "-d" | "--day" => day_numbers.append(&mut parse_days(&value(&arg)?)?),
+                "-p" | "--part" => parts.push(Part::parse(&value(&arg)?)?),
+                "-i" | "--input" => input = Some(InputSource::parse(&value(&arg)?)),
+                "-r" | "--resources" => {
+                    input = Some(InputSource::Resources(PathBuf::from(value(&arg)?)))
+                }
+                "-e" | "--example" => {
+                    let n: usize = value(&arg)?
+                        .parse()
+                        .with_context(|| format!("invalid example number after {}", arg))?;
+                    if n == 0 {
+                        return Err(anyhow!("examples are numbered from 1"));
+                    }
+                    input = Some(InputSource::Example(n));
+                }
+                "-c" | "--check" => check = true,
+                "-f" | "--format" => format = OutputFormat::parse(&value(&arg)?)?,
+                "-t" | "--timings" => {
+                    let path = PathBuf::from(value(&arg)?);
+                    timings = Some((path.clone(), ReportFormat::from_path(&path)?));
+                }
+                "-j" | "--parallel" => parallel = true,
+                "-T" | "--trace" => {
+                    let filter = TraceFilter::parse(&value(&arg)?)?;
+                    if let Some(target) = filter
+                        .targets()
+                        .find(|t| !all_days().iter().any(|d| format!("day{}", d.number) == *t))
+                    {
+                        return Err(anyhow!(
+                            "cannot trace '{}', targets are days such as day24",
+                            target
+                        ));
+                    }
+                    trace = Some(filter);
+                }
+                "-n" | "--runs" | "-w" | "--warmup" => {
+                    let count: usize = value(&arg)?
+                        .parse()
+                        .with_context(|| format!("invalid count after {}", arg))?;
+                    let bench = bench
+                        .as_mut()
+                        .ok_or_else(|| anyhow!("{} is only available with bench", arg))?;
+                    if arg == "-n" || arg == "--runs" {
+                        bench.runs = count;
+                    } else {
+                        bench.warmup = count;
+                    }
+                }
+                "-s" | "--size" | "-x" | "--seed" => {
+                    let count: u64 = value(&arg)?
+                        .parse()
+                        .with_context(|| format!("invalid number after {}", arg))?;
+                    let generate = generate
+                        .as_mut()
+                        .ok_or_else(|| anyhow!("{} is only available with generate", arg))?;
+                    if arg == "-s" || arg == "--size" {
+                        generate.size = count as usize;
+                    } else {
+                        generate.seed = count;
+                    }
+                }
+                "--animate" => animate = true,
+                "--delay" => {
+                    let ms: u64 = value(&arg)?
+                        .parse()
+                        .with_context(|| format!("invalid delay after {}", arg))?;
+                    animation.delay = Duration::from_millis(ms);
+                    animation_options.push(arg);
+                }
+                "--steps" => {
+                    animation.steps = parse_steps(&value(&arg)?)?;
+                    animation_options.push(arg);
+                }
+                "--frames" => {
+                    animation.frames = Some(PathBuf::from(value(&arg)?));
+                    animation_options.push(arg);
+                }
+                "--export" => {
+                    let path = PathBuf::from(value(&arg)?);
+                    export = Some((path.clone(), ImageFormat::from_path(&path)?));
+                }
+                "--scale" => {
+                    let n: usize = value(&arg)?
+                        .parse()
+                        .with_context(|| format!("invalid scale after {}", arg))?;
+                    if n == 0 {
+                        return Err(anyhow!("the scale must be at least 1"));
+                    }
+                    scale = Some(n);
+                }
+                "--no-cache" => no_cache = true,
+                "--clear-cache" => clear_cache = true,
+                "-h" | "--help" => help = true,
+                unknown => return Err(anyhow!("unknown argument '{}'\n{}", unknown, USAGE)),
+            }
+        }
+
+        if generate.is_some()
+            && (all || day_numbers.len() != 1 || !parts.is_empty() || input.is_some())
+        {
+            return Err(anyhow!(
+                "generate needs a single --day, without --part or --input"
+            ));
+        }
+        if generate.is_some() && (check || parallel || timings.is_some()) {
+            return Err(anyhow!(
+                "generate cannot be combined with --check, --timings or --parallel"
+            ));
+        }
+
+        if let Some(option) = animation_options.first().filter(|_| !animate) {
+            return Err(anyhow!("{} is only available with --animate", option));
+        }
+        if animate && (all || day_numbers.len() != 1 || !parts.is_empty()) {
+            return Err(anyhow!("--animate needs a single --day, without --part"));
+        }
+        if animate
+            && (check
+                || parallel
+                || timings.is_some()
+                || format != OutputFormat::Text
+                || bench.is_some()
+                || generate.is_some())
+        {
+            return Err(anyhow!(
+                "--animate only draws the steps of a day, it cannot be combined with other modes"
+            ));
+        }
+        let animate = animate.then_some(animation);
+
+        if scale.is_some() && export.is_none() {
+            return Err(anyhow!("--scale is only available with --export"));
+        }
+        if export.is_some() && (all || day_numbers.len() != 1 || !parts.is_empty()) {
+            return Err(anyhow!("--export needs a single --day, without --part"));
+        }
+        if export.is_some()
+            && (check
+                || parallel
+                || timings.is_some()
+                || format != OutputFormat::Text
+                || bench.is_some()
+                || generate.is_some()
+                || animate.is_some())
+        {
+            return Err(anyhow!(
+                "--export only writes the picture of a day, it cannot be combined with other modes"
+            ));
+        }
+        let export = export.map(|(path, format)| Export {
+            path,
+            format,
+            scale: scale.unwrap_or(4),
+        });
+
+        let days = if all || day_numbers.is_empty() {
+            all_days().iter().collect()
+        } else {
+            day_numbers
+                .into_iter()
+                .sorted()
+                .dedup()
+                .map(get_day)
+                .collect::<Result<Vec<_>>>()?
+        };
+
+        let parts = if parts.is_empty() {
+            Part::all().to_vec()
+        } else {
+            parts.into_iter().sorted().dedup().collect()
+        };
+
+        let input = input.unwrap_or_default();
+        if days.len() > 1 && !matches!(input, InputSource::Resources(_)) {
+            return Err(anyhow!(
+                "--input and --example can only be used with a single --day, use --resources for several days"
+            ));
+        }
+
+        if check && input.answers_file().is_none() {
+            return Err(anyhow!(
+                "--check compares with the answers of the resources inputs, it cannot be used with --input or --example"
+            ));
+        }
+
+        if bench.is_some() && (check || parallel || timings.is_some()) {
+            return Err(anyhow!(
+                "bench cannot be combined with --check, --timings or --parallel"
+            ));
+        }
+        if format != OutputFormat::Text && (check || bench.is_some() || generate.is_some()) {
+            return Err(anyhow!("--format only applies to the answers of a run"));
+        }
+        if bench.map(|b| b.runs) == Some(0) {
+            return Err(anyhow!("bench needs at least one run"));
+        }
+        if generate.map(|g| g.size) == Some(0) {
+            return Err(anyhow!("generate needs a size of at least 1"));
+        }
+
+        // traces only show up when the solvers actually run, and a check solves everything
+        // again, as answers cached by an older build could hide a regression
+        let cache = (!no_cache && !check && trace.is_none()).then(AnswerCache::default);
+
+        Ok(Self {
+            days,
+            parts,
+            input,
+            check,
+            format,
+            timings,
+            parallel,
+            trace,
+            cache,
+            clear_cache,
+            bench,
+            generate,
+            animate,
+            export,
+            help,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Options> {
+        Options::parse(args.split_whitespace().map(String::from))
+    }
+
+    fn day_numbers(options: &Options) -> Vec<usize> {
+        options.days.iter().map(|d| d.number).collect()
+    }
+
+    #[test]
+    fn days_can_be_selected() {
+        assert_eq!(25, parse("").unwrap().days.len());
+        assert_eq!(25, parse("--all").unwrap().days.len());
+        assert_eq!(vec![19], day_numbers(&parse("--day 19").unwrap()));
+        assert_eq!(
+            vec![10, 11, 12, 13, 14, 15],
+            day_numbers(&parse("--day 10-15").unwrap())
+        );
+        assert_eq!(
+            vec![1, 3, 24, 25],
+            day_numbers(&parse("-d 24-25,3 -d 1,3").unwrap())
+        );
+    }
+
+    #[test]
+    fn parts_can_be_selected() {
+        assert_eq!(vec![Part::One, Part::Two], parse("").unwrap().parts);
+        assert_eq!(vec![Part::Two], parse("--day 19 --part 2").unwrap().parts);
+    }
+
+    #[test]
+    fn bad_selections_are_rejected() {
+        assert!(parse("--day 26").is_err());
+        assert!(parse("--day 0-3").is_err());
+        assert!(parse("--day 15-10").is_err());
+        assert!(parse("--day").is_err());
+        assert!(parse("--day x").is_err());
+        assert!(parse("--part 3").is_err());
+        assert!(parse("--bogus").is_err());
+    }
+
+    #[test]
+    fn input_can_be_selected() {
+        assert_eq!(InputSource::default(), parse("--day 3").unwrap().input);
+        assert_eq!(
+            InputSource::File(PathBuf::from("my_input.txt")),
+            parse("--day 3 --input my_input.txt").unwrap().input
+        );
+        assert_eq!(InputSource::Stdin, parse("-d 3 -i -").unwrap().input);
+        assert_eq!(
+            InputSource::Resources(PathBuf::from("inputs")),
+            parse("--resources inputs").unwrap().input
+        );
+        assert!(parse("--day 3-4 --input my_input.txt").is_err());
+        assert!(parse("--input my_input.txt").is_err());
+
+        assert_eq!(
+            InputSource::Example(2),
+            parse("--day 22 --example 2").unwrap().input
+        );
+        assert!(parse("--example 1").is_err());
+        assert!(parse("-d 22 -e 0").is_err());
+        assert!(parse("-d 22 -e 1 --check").is_err());
+    }
+
+    #[test]
+    fn output_format_can_be_selected() {
+        assert_eq!(OutputFormat::Text, parse("").unwrap().format);
+        assert_eq!(OutputFormat::Json, parse("--format json").unwrap().format);
+        assert_eq!(OutputFormat::Tsv, parse("-d 3 -f tsv -j").unwrap().format);
+        assert!(parse("--format yaml").is_err());
+        assert!(parse("--check --format json").is_err());
+        assert!(parse("bench -f tsv").is_err());
+    }
+
+    #[test]
+    fn timings_report_can_be_requested() {
+        assert_eq!(None, parse("").unwrap().timings);
+        assert_eq!(
+            Some((PathBuf::from("t.csv"), ReportFormat::Csv)),
+            parse("--timings t.csv").unwrap().timings
+        );
+        assert!(parse("--timings t.txt").is_err());
+    }
+
+    #[test]
+    fn bench_can_be_configured() {
+        assert_eq!(None, parse("--day 24").unwrap().bench);
+        assert_eq!(Some(Bench::default()), parse("bench").unwrap().bench);
+        let options = parse("bench --day 24 --runs 5 -w 0").unwrap();
+        assert_eq!(Some(Bench { runs: 5, warmup: 0 }), options.bench);
+        assert_eq!(1, options.days.len());
+
+        assert!(parse("--day 24 --runs 5").is_err());
+        assert!(parse("bench --runs 0").is_err());
+        assert!(parse("bench --runs x").is_err());
+        assert!(parse("bench --parallel").is_err());
+    }
+
+    #[test]
+    fn generate_needs_a_single_day() {
+        assert_eq!(None, parse("--day 12").unwrap().generate);
+        assert_eq!(
+            Some(Generate::default()),
+            parse("generate --day 12").unwrap().generate
+        );
+        let options = parse("generate -d 19 --size 40 --seed 7").unwrap();
+        assert_eq!(Some(Generate { size: 40, seed: 7 }), options.generate);
+        assert_eq!(vec![19], day_numbers(&options));
+
+        assert!(parse("generate").is_err());
+        assert!(parse("generate --day 1-2").is_err());
+        assert!(parse("generate --day 1 --part 1").is_err());
+        assert!(parse("generate --day 1 --check").is_err());
+        assert!(parse("generate --day 1 --seed x").is_err());
+        assert!(parse("generate --day 1 --size 0").is_err());
+        assert!(parse("--day 1 --seed 3").is_err());
+    }
+
+    #[test]
+    fn trace_targets_are_days() {
+        assert!(parse("").unwrap().trace.is_none());
+        let filter = parse("--day 24 --trace day24=debug").unwrap().trace;
+        assert_eq!(Some(TraceFilter::parse("day24=debug").unwrap()), filter);
+        assert!(parse("-T debug,day19=trace").is_ok());
+
+        assert!(parse("--trace day26=debug").is_err());
+        assert!(parse("--trace alu=debug").is_err());
+        assert!(parse("--trace day24=loud").is_err());
+    }
+
+    #[test]
+    fn cache_can_be_bypassed() {
+        assert_eq!(Some(AnswerCache::default()), parse("").unwrap().cache);
+        assert_eq!(None, parse("--no-cache").unwrap().cache);
+        assert_eq!(None, parse("-d 24 -T day24=debug").unwrap().cache);
+        assert_eq!(None, parse("--check").unwrap().cache);
+        assert!(parse("--clear-cache").unwrap().clear_cache);
+        assert!(!parse("").unwrap().clear_cache);
+    }
+
+    #[test]
+    fn animation_can_be_configured() {
+        assert_eq!(None, parse("--day 11").unwrap().animate);
+        assert_eq!(
+            Some(Animate::default()),
+            parse("--animate --day 11").unwrap().animate
+        );
+        let options = parse("-d 25 --steps 10-20 --animate --delay 40 --frames out").unwrap();
+        assert_eq!(
+            Some(Animate {
+                delay: Duration::from_millis(40),
+                steps: (10, 20),
+                frames: Some(PathBuf::from("out")),
+            }),
+            options.animate
+        );
+        assert_eq!(
+            Some((5, usize::MAX)),
+            parse("-d 6 --animate --steps 5-")
+                .unwrap()
+                .animate
+                .map(|a| a.steps)
+        );
+        assert_eq!(
+            Some((7, 7)),
+            parse("-d 6 --animate --steps 7")
+                .unwrap()
+                .animate
+                .map(|a| a.steps)
+        );
+
+        assert!(parse("--animate").is_err());
+        assert!(parse("--animate -d 6-7").is_err());
+        assert!(parse("--animate -d 6 -p 1").is_err());
+        assert!(parse("--animate -d 6 --check").is_err());
+        assert!(parse("-d 6 --delay 10").is_err());
+        assert!(parse("--animate -d 6 --steps 9-3").is_err());
+        assert!(parse("--animate -d 6 --steps x").is_err());
+    }
+
+    #[test]
+    fn export_needs_a_known_format() {
+        assert_eq!(None, parse("--day 9").unwrap().export);
+        assert_eq!(
+            Some(Export {
+                path: PathBuf::from("basins.svg"),
+                format: ImageFormat::Svg,
+                scale: 10,
+            }),
+            parse("--export basins.svg -d 9 --scale 10").unwrap().export
+        );
+        assert_eq!(
+            Some(4),
+            parse("-d 13 --export paper.png")
+                .unwrap()
+                .export
+                .map(|e| e.scale)
+        );
+
+        assert!(parse("-d 9 --export basins.gif").is_err());
+        assert!(parse("--export basins.png").is_err());
+        assert!(parse("-d 9 --export basins.png --scale 0").is_err());
+        assert!(parse("-d 9 --scale 2").is_err());
+        assert!(parse("-d 9 --export basins.png --animate").is_err());
+    }
+
+    #[test]
+    fn check_needs_the_resources() {
+        assert!(parse("--check").unwrap().check);
+        assert!(!parse("").unwrap().check);
+        assert!(parse("-d 3 -i my_input.txt --check").is_err());
+    }
+}
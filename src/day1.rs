@@ -1,45 +1,130 @@
-use itertools::Itertools;
+use crate::parse::{parse_lines, ParseError};
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver};
+use anyhow::{anyhow, Result};
+use std::io::BufRead;
 
-fn parse_depths(report: &'static str) -> impl Iterator<Item = usize> {
-    report
-        .lines()
-        .map(|str_depth| str_depth.parse::<usize>())
-        .filter(|r| r.is_ok())
-        .map(|r| r.unwrap())
+fn parse_depth(str_depth: &str) -> Result<usize> {
+    Ok(str_depth.trim().parse()?)
 }
 
-fn count_depth_incrs(report: &'static str) -> usize {
-    parse_depths(report)
-        .tuple_windows()
-        .filter(|(d1, d2)| d2 > d1)
-        .count()
+fn parse_depths(report: &str) -> Result<Vec<usize>> {
+    parse_lines(report, parse_depth)
 }
 
-fn count_summed_depth_incrs(report: &'static str) -> usize {
-    parse_depths(report)
-        .tuple_windows()
-        .map(|(d1, d2, d3)| d1 + d2 + d3)
-        .tuple_windows()
-        .filter(|(sum_d1, sum_d2)| sum_d2 > sum_d1)
-        .count()
+/// how the sums of a sliding window of depths evolve from one window to the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WindowStats {
+    pub window: usize,
+    pub increases: usize,
+    pub decreases: usize,
+    pub plateaus: usize,
+    /// most consecutive windows whose sums keep increasing
+    pub longest_increasing_run: usize,
 }
 
-pub fn print_depth_incrs() {
-    let report = include_str!("../resources/day1_sonar_depths.txt");
-    println!("number of depth increases : {}", count_depth_incrs(report));
-    println!(
-        "number of summed depth increases : {}",
-        count_summed_depth_incrs(report)
-    );
+/// analyses the depths as they come, only holding the last depths of the largest window
+#[derive(Debug, Clone)]
+pub struct SonarAnalyser {
+    // ring of the last depths, recent[count % len] being the oldest one
+    recent: Vec<usize>,
+    count: usize,
+    stats: Vec<WindowStats>,
+    runs: Vec<usize>,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl SonarAnalyser {
+    pub fn new(windows: &[usize]) -> Result<Self> {
+        if windows.is_empty() || windows.contains(&0) {
+            return Err(anyhow!(
+                "window sizes must be at least 1, got {:?}",
+                windows
+            ));
+        }
+        Ok(Self {
+            recent: vec![0; *windows.iter().max().unwrap()],
+            count: 0,
+            stats: windows
+                .iter()
+                .map(|&window| WindowStats {
+                    window,
+                    ..WindowStats::default()
+                })
+                .collect(),
+            runs: vec![0; windows.len()],
+        })
+    }
 
-    #[test]
-    fn aoc_example_works() {
-        let report = "199
+    // two consecutive sums only differ by the depth entering the window and the one leaving it
+    pub fn push(&mut self, depth: usize) {
+        let len = self.recent.len();
+        for (stats, run) in self.stats.iter_mut().zip(&mut self.runs) {
+            if self.count < stats.window {
+                continue;
+            }
+            let leaving = self.recent[(self.count - stats.window) % len];
+            if depth > leaving {
+                stats.increases += 1;
+                *run += 1;
+            } else {
+                if depth < leaving {
+                    stats.decreases += 1;
+                } else {
+                    stats.plateaus += 1;
+                }
+                *run = 1;
+            }
+            stats.longest_increasing_run = stats.longest_increasing_run.max(*run);
+        }
+        self.recent[self.count % len] = depth;
+        self.count += 1;
+        // the first full window starts a run
+        for (stats, run) in self.stats.iter_mut().zip(&mut self.runs) {
+            if self.count == stats.window {
+                *run = 1;
+                stats.longest_increasing_run = 1;
+            }
+        }
+    }
+
+    /// one for each window size, in the order they were given
+    pub fn stats(&self) -> &[WindowStats] {
+        &self.stats
+    }
+
+    /// depths read line by line, blank lines being skipped and the first unparsable one reported
+    pub fn analyse(mut reader: impl BufRead, windows: &[usize]) -> Result<Vec<WindowStats>> {
+        let mut analyser = Self::new(windows)?;
+        let mut line = String::new();
+        let mut index = 0;
+        while reader.read_line(&mut line)? > 0 {
+            index += 1;
+            if !line.trim().is_empty() {
+                let depth = parse_depth(&line).map_err(|e| {
+                    ParseError::new(index, format!("'{}' : {:#}", line.trim_end(), e))
+                })?;
+                analyser.push(depth);
+            }
+            line.clear();
+        }
+        Ok(analyser.stats)
+    }
+}
+
+fn count_window_incrs(depths: &[usize], window: usize) -> usize {
+    let mut analyser = SonarAnalyser::new(&[window]).expect("window is not empty");
+    depths.iter().for_each(|depth| analyser.push(*depth));
+    analyser.stats()[0].increases
+}
+
+pub struct SonarReport {
+    depths: Vec<usize>,
+}
+
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "sonar sweep",
+    input: "199
 200
 208
 210
@@ -48,9 +133,111 @@ mod tests {
 240
 269
 260
-263
-";
-        assert_eq!(7, count_depth_incrs(report));
-        assert_eq!(5, count_summed_depth_incrs(report));
+263",
+    part1: Some("7"),
+    part2: Some("5"),
+}];
+
+impl Solver for SonarReport {
+    fn parse(report: &str) -> Result<Self> {
+        Ok(Self {
+            depths: parse_depths(report)?,
+        })
+    }
+
+    fn part1(&self) -> Answer {
+        count_window_incrs(&self.depths, 1).into()
+    }
+
+    fn part2(&self) -> Answer {
+        count_window_incrs(&self.depths, 3).into()
+    }
+
+    // depths mostly go down, the sea floor being uneven
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut depth = rng.between(100, 200);
+        (0..size)
+            .map(|_| {
+                depth = (depth + rng.between(-10, 30)).max(0);
+                format!("{}\n", depth)
+            })
+            .collect()
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("number of depth increases : {}", answer),
+            Part::Two => format!("number of summed depth increases : {}", answer),
+        }
+    }
+}
+
+pub fn print_depth_incrs(report: &str, part: Part) {
+    print_answer::<SonarReport>(report, part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aoc_example_works() {
+        let report = EXAMPLES[0].input;
+        let depths = parse_depths(report).unwrap();
+        assert_eq!(7, count_window_incrs(&depths, 1));
+        assert_eq!(5, count_window_incrs(&depths, 3));
+
+        let sonar = SonarReport::parse(report).unwrap();
+        assert_eq!(Answer::Number(7), sonar.part1());
+        assert_eq!(Answer::Number(5), sonar.part2());
+
+        let err = SonarReport::parse("199\n2O0\n").err().unwrap();
+        assert_eq!(
+            "line 2 : '2O0' : invalid digit found in string",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn depths_can_be_streamed() {
+        let report = format!("{}\n\n263\n", EXAMPLES[0].input);
+        let stats = SonarAnalyser::analyse(report.as_bytes(), &[1, 3, 20]).unwrap();
+        assert_eq!(
+            WindowStats {
+                window: 1,
+                increases: 7,
+                decreases: 2,
+                plateaus: 1,
+                longest_increasing_run: 4,
+            },
+            stats[0]
+        );
+        assert_eq!(
+            WindowStats {
+                window: 3,
+                increases: 5,
+                decreases: 2,
+                plateaus: 1,
+                longest_increasing_run: 5,
+            },
+            stats[1]
+        );
+        assert_eq!(
+            WindowStats {
+                window: 20,
+                ..WindowStats::default()
+            },
+            stats[2]
+        );
+
+        let err = SonarAnalyser::analyse("199\n\n2O0\n".as_bytes(), &[1])
+            .err()
+            .unwrap();
+        assert_eq!(
+            "line 3 : '2O0' : invalid digit found in string",
+            err.to_string()
+        );
+        assert!(SonarAnalyser::new(&[1, 0]).is_err());
+        assert!(SonarAnalyser::new(&[]).is_err());
     }
 }
@@ -24,22 +24,28 @@ fn get_closing_score_by_start_char(start: char) -> usize {
 }
 
 #[derive(Debug)]
-enum ParsedSequence {
-    Complete(&'static str),
+enum ParsedSequence<'a> {
+    Complete(&'a str),
     Incomplete { start: char, score: usize },
 }
 
+use crate::parse::ParseError;
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver};
+use crate::trace::{debug, trace};
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use ParsedSequence::{Complete, Incomplete};
-type SyntaxResult = Result<ParsedSequence, char>;
+type SyntaxResult<'a> = Result<ParsedSequence<'a>, char>;
 
-fn get_valid_subsequence(seq: &'static str) -> SyntaxResult {
+fn get_valid_subsequence(seq: &str) -> SyntaxResult<'_> {
     if seq.is_empty() {
         return Ok(Complete(""));
     }
     let start = seq.chars().next().unwrap();
     if seq.len() == 1 {
-        // eprintln!("sequence {} is incomplete", seq);
+        trace!("sequence {} is incomplete", seq);
         return Ok(Incomplete { start, score: 0 });
     }
     let mut read_l = 1;
@@ -73,12 +79,12 @@ fn get_valid_subsequence(seq: &'static str) -> SyntaxResult {
                 }
                 // ore there is a pb
                 let bad_closing_char = seq.chars().nth(read_l).unwrap();
-                // eprintln!(
-                //     "{} {} {} mismatch",
-                //     start,
-                //     &seq[..read_l + 1],
-                //     bad_closing_char
-                // );
+                trace!(
+                    "{} {} {} mismatch",
+                    start,
+                    &seq[..read_l + 1],
+                    bad_closing_char
+                );
                 return Err(bad_closing_char);
             }
             Ok(Incomplete { start, score: 0 })
@@ -90,14 +96,14 @@ fn get_valid_subsequence(seq: &'static str) -> SyntaxResult {
     }
 }
 
-fn check_line(line: &'static str) -> SyntaxResult {
+fn check_line(line: &str) -> SyntaxResult<'_> {
     let mut read_chars = 0;
     while read_chars < line.len() {
         match get_valid_subsequence(&line[read_chars..])? {
             Incomplete { start, score } => {
                 let score = score * 5 + get_closing_score_by_start_char(start);
                 let result = Incomplete { start: '§', score };
-                // eprintln!("{} Incomplete : {}", line, score);
+                debug!("{} incomplete : {}", line, score);
                 return Ok(result);
             }
             Complete(sub) => {
@@ -108,7 +114,7 @@ fn check_line(line: &'static str) -> SyntaxResult {
     Ok(Complete(line))
 }
 
-fn illegal_score(input: &'static str) -> usize {
+fn illegal_score(input: &str) -> usize {
     input
         .lines()
         .filter_map(|line| check_line(line).err())
@@ -122,7 +128,8 @@ fn illegal_score(input: &'static str) -> usize {
         .sum()
 }
 
-fn middle_completion_score(input: &'static str) -> usize {
+/// none when no line is incomplete
+fn middle_completion_score(input: &str) -> Option<usize> {
     let incomplete_scores: Vec<_> = input
         .lines()
         .filter_map(|line| check_line(line).ok())
@@ -132,27 +139,17 @@ fn middle_completion_score(input: &'static str) -> usize {
         })
         .sorted()
         .collect();
-    let middle = (incomplete_scores.len() - 1) / 2;
-    incomplete_scores[middle]
+    let middle = incomplete_scores.len().checked_sub(1)? / 2;
+    Some(incomplete_scores[middle])
 }
 
-pub fn print_syntax_check() {
-    let input = include_str!("../resources/day10_navigation_syntax.txt");
-
-    println!("illegal score : {}", illegal_score(input));
-    println!(
-        "middle incomplete score : {}",
-        middle_completion_score(input)
-    );
+pub struct NavigationSubsystem {
+    input: String,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn aoc_example_works() {
-        let input = "[({(<(())[]>[[{[]{<()<>>
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "navigation subsystem",
+    input: "[({(<(())[]>[[{[]{<()<>>
 [(()[<>])]({[<{<<[]>>(
 {([(<{}[<>[]}>{[]{[(<()>
 (((({<>}<{<{<>}{[]{[]{}
@@ -161,9 +158,117 @@ mod tests {
 {<[[]]>}<{[{[{[]{()[[[]
 [<(<(<(<{}))><([]([]()
 <{([([[(<>()){}]>(<<{{
-<{([{{}}[<[[[<>{}]]]>[]]
-";
+<{([{{}}[<[[[<>{}]]]>[]]",
+    part1: Some("26397"),
+    part2: Some("288957"),
+}];
+
+impl Solver for NavigationSubsystem {
+    fn parse(input: &str) -> Result<Self> {
+        for (i, line) in input.lines().enumerate() {
+            if let Some((j, c)) = line
+                .chars()
+                .enumerate()
+                .find(|(_, c)| !"()[]{}<>".contains(*c))
+            {
+                return Err(ParseError::at(
+                    i + 1,
+                    j + 1,
+                    format!("'{}' is not a chunk delimiter", c),
+                )
+                .into());
+            }
+        }
+        if input.trim().is_empty() {
+            return Err(anyhow!("no lines"));
+        }
+        Ok(Self {
+            input: input.to_string(),
+        })
+    }
+
+    fn part1(&self) -> Answer {
+        illegal_score(&self.input).into()
+    }
+
+    fn part2(&self) -> Answer {
+        middle_completion_score(&self.input).map_or(Answer::None, Answer::from)
+    }
+
+    // an odd number of incomplete lines, so that there is a middle score, the others
+    // being corrupted ; chunks are at most 12 deep to keep completion scores small
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        const CHUNKS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+        let incomplete_count = (size / 2) | 1;
+        let mut incomplete: Vec<bool> = (0..size.max(1)).map(|i| i < incomplete_count).collect();
+        rng.shuffle(&mut incomplete);
+
+        incomplete
+            .into_iter()
+            .map(|incomplete| {
+                let mut line = String::new();
+                let mut opened: Vec<usize> = vec![];
+                for _ in 0..rng.between(10, 60) {
+                    if opened.is_empty() || (opened.len() < 12 && rng.one_in(2)) {
+                        let chunk = rng.below(4);
+                        opened.push(chunk);
+                        line.push(CHUNKS[chunk].0);
+                    } else {
+                        line.push(CHUNKS[opened.pop().unwrap()].1);
+                    }
+                }
+                if opened.is_empty() {
+                    line.push('(');
+                    opened.push(0);
+                }
+                if !incomplete {
+                    let expected = *opened.last().unwrap();
+                    let wrong = (expected + rng.between(1, 3) as usize) % 4;
+                    line.push(CHUNKS[wrong].1);
+                    for _ in 0..rng.below(10) {
+                        let (open, close) = *rng.pick(&CHUNKS);
+                        line.push(if rng.one_in(2) { open } else { close });
+                    }
+                }
+                line + "\n"
+            })
+            .collect()
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("illegal score : {}", answer),
+            Part::Two => format!("middle incomplete score : {}", answer),
+        }
+    }
+}
+
+pub fn print_syntax_check(input: &str, part: Part) {
+    print_answer::<NavigationSubsystem>(input, part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aoc_example_works() {
+        let input = EXAMPLES[0].input;
         assert_eq!(26397, illegal_score(input));
-        assert_eq!(288957, middle_completion_score(input))
+        assert_eq!(Some(288957), middle_completion_score(input))
+    }
+
+    #[test]
+    fn degenerate_inputs_are_handled() {
+        assert_eq!(
+            "no lines",
+            NavigationSubsystem::parse("\n\n")
+                .err()
+                .unwrap()
+                .to_string()
+        );
+        let corrupted = NavigationSubsystem::parse("(]\n()\n").unwrap();
+        assert_eq!(Answer::Number(57), corrupted.part1());
+        assert_eq!(Answer::None, corrupted.part2());
     }
 }
@@ -1,84 +1,156 @@
-use super::day9::*;
+use crate::grid::{Grid, Point};
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Frames, Solver};
+use anyhow::Result;
+use colored::Colorize;
 use std::collections::HashSet;
+use std::fmt::Write;
 
-fn parse_energy_levels(input: &str) -> Vec<Vec<usize>> {
-    input
-        .lines()
-        .map(|l| {
-            l.split("")
-                .filter_map(|c| c.parse::<usize>().ok())
-                .collect()
-        })
-        .collect()
+fn parse_energy_levels(input: &str) -> Result<Grid<usize>> {
+    Grid::parse_digits(input)
 }
 
-fn count_number_of_flashes_for_step(energies: &mut [Vec<usize>]) -> (usize, bool) {
-    let dim = (energies.len(), energies.get(0).unwrap().len());
-    let mut flashed: HashSet<Point> = HashSet::with_capacity(dim.0 * dim.1);
+fn count_number_of_flashes_for_step(energies: &mut Grid<usize>) -> (usize, bool) {
+    let mut flashed: HashSet<Point> = HashSet::with_capacity(energies.height() * energies.width());
 
-    energies
-        .iter_mut()
-        .flat_map(|l| l.iter_mut())
-        .for_each(|e| *e += 1);
+    energies.values_mut().for_each(|e| *e += 1);
 
     loop {
         let next_flashes: Vec<Point> = energies
             .iter()
-            .enumerate()
-            .flat_map(move |(x, l)| l.iter().enumerate().map(move |(y, e)| (x, y, *e)))
-            .filter(|(_, _, e)| *e > 9)
-            .map(|(x, y, _)| (x, y))
+            .filter(|(_, e)| **e > 9)
+            .map(|(p, _)| p)
             .filter(|p| !flashed.contains(p))
             .collect();
 
         if next_flashes.is_empty() {
             break;
         } else {
-            for (x, y) in &next_flashes {
-                flashed.insert((*x, *y));
+            for p in &next_flashes {
+                flashed.insert(*p);
             }
-            for (x, y) in next_flashes
-                .into_iter()
-                .flat_map(|p| get_neighbours_pos_diag(&p, &dim).into_iter())
-            {
-                energies[x][y] += 1;
+            for p in next_flashes {
+                let surrounding: Vec<_> = energies.surrounding(p).collect();
+                for n in surrounding {
+                    energies[n] += 1;
+                }
             }
         }
     }
-    for (x, y) in &flashed {
-        energies[*x][*y] = 0;
+    for p in &flashed {
+        energies[*p] = 0;
     }
     let flashes_count = flashed.len();
-    let all_flashed = flashes_count == dim.0 * dim.1;
+    let all_flashed = flashes_count == energies.height() * energies.width();
     (flashes_count, all_flashed)
 }
-fn sum_flashes(input: &str, steps: usize) -> usize {
-    let mut energies = parse_energy_levels(input);
+fn sum_flashes(energies: &Grid<usize>, steps: usize) -> usize {
+    let mut energies = energies.clone();
     let mut flashes_count = 0;
     for _ in 0..steps {
         flashes_count += count_number_of_flashes_for_step(&mut energies).0;
     }
     flashes_count
 }
-fn get_first_all_flashed_step(input: &str) -> usize {
+fn get_first_all_flashed_step(energies: &Grid<usize>) -> usize {
     let mut first = 1;
-    let mut energies = parse_energy_levels(input);
+    let mut energies = energies.clone();
     while !count_number_of_flashes_for_step(&mut energies).1 {
         first += 1;
     }
     first
 }
 
-pub fn display_octopuses_flash_count() {
-    let input = include_str!("../resources/day11_octopuses_energy.txt");
-    println!(
-        "number of flashes after 100 steps {}",
-        sum_flashes(input, 100)
-    );
-    println!(
-        "first step during which all octopuses flash {}",
-        get_first_all_flashed_step(input)
-    );
+/// octopuses which just flashed are back to 0, and highlighted
+fn draw_energies(energies: &Grid<usize>, step: usize, flashes: usize) -> String {
+    let mut frame = format!("step {} : {} flashes\n", step, flashes);
+    for row in energies.rows() {
+        for energy in row {
+            if *energy == 0 {
+                write!(frame, "{}", "0".bold().yellow()).unwrap();
+            } else {
+                write!(frame, "{}", energy.to_string().dimmed()).unwrap();
+            }
+        }
+        frame.push('\n');
+    }
+    frame
+}
+
+pub struct Octopuses {
+    energies: Grid<usize>,
+}
+
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "larger",
+    input: "5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526",
+    part1: Some("1656"),
+    part2: Some("195"),
+}];
+
+impl Solver for Octopuses {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            energies: parse_energy_levels(input)?,
+        })
+    }
+
+    fn part1(&self) -> Answer {
+        sum_flashes(&self.energies, 100).into()
+    }
+
+    fn part2(&self) -> Answer {
+        get_first_all_flashed_step(&self.energies).into()
+    }
+
+    // a size x size cavern, drawn again until its octopuses flash all together
+    // within 1000 steps, which gets rare beyond a size of 15
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        loop {
+            let energies = Grid::from_fn(size.max(1), size.max(1), |_| rng.below(10));
+            let mut steps = energies.clone();
+            if (0..1000).any(|_| count_number_of_flashes_for_step(&mut steps).1) {
+                return energies.to_string();
+            }
+        }
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("number of flashes after 100 steps {}", answer),
+            Part::Two => format!("first step during which all octopuses flash {}", answer),
+        }
+    }
+
+    // until the step during which they all flash
+    fn frames(&self) -> Option<Frames<'_>> {
+        let mut energies = self.energies.clone();
+        let first = draw_energies(&energies, 0, 0);
+        let mut all_flashed = false;
+        let steps = (1..).map_while(move |step| {
+            if all_flashed {
+                return None;
+            }
+            let (flashes, all) = count_number_of_flashes_for_step(&mut energies);
+            all_flashed = all;
+            Some(draw_energies(&energies, step, flashes))
+        });
+        Some(Box::new(std::iter::once(first).chain(steps)))
+    }
+}
+
+pub fn display_octopuses_flash_count(input: &str, part: Part) {
+    print_answer::<Octopuses>(input, part)
 }
 
 #[cfg(test)]
@@ -92,23 +164,25 @@ mod tests {
 19191
 19991
 11111";
-        assert_eq!(9, sum_flashes(input, 1));
+        assert_eq!(9, sum_flashes(&parse_energy_levels(input).unwrap(), 1));
     }
     #[test]
     fn aoc_example_works() {
-        let input = "5483143223
-2745854711
-5264556173
-6141336146
-6357385478
-4167524645
-2176841721
-6882881134
-4846848554
-5283751526";
-        assert_eq!(35, sum_flashes(input, 2));
-        assert_eq!(1656, sum_flashes(input, 100));
+        let input = EXAMPLES[0].input;
+        let energies = parse_energy_levels(input).unwrap();
+        assert_eq!(35, sum_flashes(&energies, 2));
+        assert_eq!(1656, sum_flashes(&energies, 100));
 
-        assert_eq!(195, get_first_all_flashed_step(input));
+        assert_eq!(195, get_first_all_flashed_step(&energies));
+    }
+
+    #[test]
+    fn frames_stop_once_all_flash() {
+        colored::control::set_override(false);
+        let octopuses = Octopuses::parse(EXAMPLES[0].input).unwrap();
+        let frames: Vec<_> = octopuses.frames().unwrap().collect();
+        assert_eq!(196, frames.len());
+        assert!(frames[0].starts_with("step 0 : 0 flashes\n5483143223\n"));
+        assert!(frames[195].starts_with("step 195 : 100 flashes\n0000000000\n"));
     }
 }
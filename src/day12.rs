@@ -1,30 +1,36 @@
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-enum Cave {
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Cave {
     Start,
     End,
-    Small(&'static str),
-    Large(&'static str),
+    Small(String),
+    Large(String),
 }
 
 use std::iter::once;
 use std::ops::Index;
 
+use crate::parse::parse_lines;
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::search::count_paths;
+use crate::solver::{print_answer, Answer, Example, Solver};
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use Cave::*;
 impl Cave {
-    pub fn parse(name: &'static str) -> Result<Cave, String> {
+    pub fn parse(name: &str) -> Result<Cave> {
         match name.trim() {
             "start" => Ok(Start),
             "end" => Ok(End),
-            "" => Err("empty value !".to_string()),
-            lower if lower.chars().all(|c| c.is_lowercase()) => Ok(Small(lower)),
-            upper if upper.chars().all(|c| c.is_uppercase()) => Ok(Large(upper)),
-            unkown => Err(format!("'{}' is not a valid Cave name", unkown)),
+            "" => Err(anyhow!("empty value !")),
+            lower if lower.chars().all(|c| c.is_lowercase()) => Ok(Small(lower.to_string())),
+            upper if upper.chars().all(|c| c.is_uppercase()) => Ok(Large(upper.to_string())),
+            unkown => Err(anyhow!("'{}' is not a valid Cave name", unkown)),
         }
     }
 }
 
-struct CavesMap {
+pub struct CavesMap {
     caves: Vec<Cave>,
 
     /// associate 2 caves by their indexes, the lower first
@@ -42,49 +48,58 @@ impl<'m> Index<usize> for &'m CavesMap {
     }
 }
 
-#[derive(Clone)]
+/// path being explored, small caves being identified by their bit ; the caves past
+/// the first 64 ones are in the next words, which are not allocated for smaller maps
+#[derive(Clone, PartialEq, Eq, Hash)]
 struct Path {
-    small_caves_index: Vec<usize>,
+    small_caves_visited: u64,
+    more_small_caves_visited: Vec<u64>,
     current_index: usize,
 
-    // this is really specific, but avcoids itering over small_caves_index
+    // this is really specific, but avoids checking every visited cave
     small_cave_visited_twice: bool,
 }
 
 impl Path {
-    pub fn new() -> Self {
-        Self {
-            small_caves_index: vec![],
-            current_index: 0,
-            small_cave_visited_twice: false,
+    fn has_visited(&self, cave_index: usize) -> bool {
+        let word = match cave_index / 64 {
+            0 => self.small_caves_visited,
+            i => self.more_small_caves_visited[i - 1],
+        };
+        word & (1 << (cave_index % 64)) != 0
+    }
+
+    fn visiting(&self, cave_index: usize) -> Self {
+        let mut path = self.clone();
+        match cave_index / 64 {
+            0 => path.small_caves_visited |= 1 << cave_index,
+            i => path.more_small_caves_visited[i - 1] |= 1 << (cave_index % 64),
         }
+        path
     }
 }
 
 impl CavesMap {
-    fn parse_connection(line: &'static str) -> Result<(Cave, Cave), String> {
-        if let Some((cave1, cave2)) = line
-            .split('-')
-            .filter_map(|name| Cave::parse(name).ok())
-            .collect_tuple()
-        {
-            Ok((cave1, cave2))
+    // a path could go back and forth between two large caves forever
+    fn parse_connection(line: &str) -> Result<(Cave, Cave)> {
+        if let Some((cave1, cave2)) = line.split('-').collect_tuple() {
+            match (Cave::parse(cave1)?, Cave::parse(cave2)?) {
+                (Large(_), Large(_)) => Err(anyhow!("large caves cannot be connected together")),
+                caves => Ok(caves),
+            }
         } else {
-            Err(format!("cannot parse connection '{}'", line))
+            Err(anyhow!("expecting 2 caves separated by '-'"))
         }
     }
 
-    pub fn parse(input: &'static str) -> Self {
+    pub fn parse(input: &str) -> Result<Self> {
         let (mut start, mut end) = (None, None);
 
-        let connections: Vec<_> = input
-            .lines()
-            .filter_map(|l| Self::parse_connection(l).ok())
-            .collect();
+        let connections: Vec<_> = parse_lines(input, Self::parse_connection)?;
 
         let caves: Vec<_> = connections
             .iter()
-            .flat_map(|(cave1, cave2)| once(*cave1).chain(once(*cave2)))
+            .flat_map(|(cave1, cave2)| once(cave1.clone()).chain(once(cave2.clone())))
             .unique()
             .collect();
 
@@ -118,15 +133,15 @@ impl CavesMap {
             .map(|(cave1, cave2)| (get_index(cave1), get_index(cave2)))
             .collect();
 
-        let start_index = start.unwrap();
-        let end_index = end.unwrap();
+        let start_index = start.ok_or_else(|| anyhow!("no connection from start"))?;
+        let end_index = end.ok_or_else(|| anyhow!("no connection to end"))?;
 
-        Self {
+        Ok(Self {
             caves,
             connections_by_index,
             start_index,
             end_index,
-        }
+        })
     }
 
     fn get_connected_indexes(&self, cave_index: usize) -> Vec<usize> {
@@ -142,123 +157,65 @@ impl CavesMap {
             .collect()
     }
 
-    fn count_pathes(&self, small_cave_selector: impl Fn(&Path, usize) -> bool) -> usize {
-        // let's try to count without building actual path
-        let mut pathes = vec![Path {
+    fn count_pathes(&self, small_cave_selector: impl Fn(&Path, usize) -> bool) -> Option<usize> {
+        // pathes reaching the same cave with the same small caves visited
+        // have the same endings, which are counted once ; only large caves
+        // connected together could lead a path back to where it was
+        let start = Path {
+            small_caves_visited: 0,
+            more_small_caves_visited: vec![0; self.caves.len().div_ceil(64) - 1],
             current_index: self.start_index,
-            ..Path::new()
-        }];
-        let mut pathes_count = 0;
-        loop {
-            let new_pathes: Vec<_> = pathes
-                .into_iter()
-                .flat_map(|p| {
-                    // collecting here avoids keeping filter too long
-                    let path_options: Vec<_> = self
-                        .get_connected_indexes(p.current_index)
-                        .into_iter()
-                        .filter(|i| {
-                            if let Small(_c) = self[*i] {
-                                small_cave_selector(&p, *i)
-                            } else {
-                                true
-                            }
-                        })
-                        .collect();
-
-                    path_options
-                        .into_iter()
-                        .filter_map(move |o_idx| match self[o_idx] {
-                            End => Some(Path {
-                                current_index: self.end_index,
-                                ..p.clone()
-                            }),
-                            // TODO : copy only when modified
-                            Large(_) => Some(Path {
-                                current_index: o_idx,
-                                ..p.clone()
-                            }),
-                            Small(_) => {
-                                let mut small_caves_index = p.small_caves_index.clone();
-                                small_caves_index.push(o_idx);
-                                Some(Path {
-                                    small_caves_index,
-                                    current_index: o_idx,
-                                    small_cave_visited_twice: p.small_cave_visited_twice
-                                        || p.small_caves_index.contains(&o_idx),
-                                })
-                            }
-                            _ => None,
-                        })
-                })
-                .collect();
-
-            let new_pathes_len = new_pathes.len();
-            pathes = new_pathes
-                .into_iter()
-                .filter(|p| !matches!(self[p.current_index], End))
-                .collect();
-
-            // filtered out pathes are new finished ones
-            pathes_count += new_pathes_len - pathes.len();
-
-            if pathes.is_empty() {
-                break;
-            }
-        }
-        pathes_count
+            small_cave_visited_twice: false,
+        };
+        count_paths(
+            start,
+            |p| {
+                self.get_connected_indexes(p.current_index)
+                    .into_iter()
+                    .filter_map(|o_idx| match self[o_idx] {
+                        End | Large(_) => Some(Path {
+                            current_index: o_idx,
+                            ..p.clone()
+                        }),
+                        Small(_) if small_cave_selector(p, o_idx) => Some(Path {
+                            current_index: o_idx,
+                            small_cave_visited_twice: p.small_cave_visited_twice
+                                || p.has_visited(o_idx),
+                            ..p.visiting(o_idx)
+                        }),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+            },
+            |p| p.current_index == self.end_index,
+        )
     }
 }
 
-fn count_pathes(input: &'static str) -> usize {
-    let map = CavesMap::parse(input);
-
-    map.count_pathes(|p: &Path, i| !p.small_caves_index.contains(&i))
-}
-
-fn count_pathes_twice_visited(input: &'static str) -> usize {
-    let map = CavesMap::parse(input);
-
-    map.count_pathes(|p: &Path, i| !p.small_caves_index.contains(&i) || !p.small_cave_visited_twice)
+fn count_pathes(map: &CavesMap) -> Option<usize> {
+    map.count_pathes(|p: &Path, i| !p.has_visited(i))
 }
 
-pub fn display_pathes() {
-    let input = include_str!("../resources/day12_connections.txt");
-    println!("number of pathes : {}", count_pathes(input));
-    println!(
-        "number of path while visiting twice small places : {}",
-        count_pathes_twice_visited(input)
-    );
+fn count_pathes_twice_visited(map: &CavesMap) -> Option<usize> {
+    map.count_pathes(|p: &Path, i| !p.has_visited(i) || !p.small_cave_visited_twice)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn cave_can_be_parsed() {
-        assert_eq!(Ok(Start), Cave::parse("start"));
-        assert_eq!(Ok(End), Cave::parse("end"));
-        assert_eq!(Ok(Small("smallcave")), Cave::parse("smallcave"));
-        assert_eq!(Ok(Large("BIGCAVE")), Cave::parse("BIGCAVE"));
-        assert!(Cave::parse("MediumCave").is_err());
-        assert!(Cave::parse("not_a_cave").is_err());
-        assert!(Cave::parse("   ").is_err());
-    }
-
-    #[test]
-    fn check_aoc_example() {
-        let simple_input = "start-A
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        name: "small",
+        input: "start-A
 start-b
 A-c
 A-b
 b-d
 A-end
-b-end";
-        assert_eq!(10, count_pathes(simple_input));
-        assert_eq!(36, count_pathes_twice_visited(simple_input));
-
-        let larger_input = "dc-end
+b-end",
+        part1: Some("10"),
+        part2: Some("36"),
+    },
+    Example {
+        name: "slightly larger",
+        input: "dc-end
 HN-start
 start-kj
 dc-start
@@ -267,10 +224,13 @@ LN-dc
 HN-end
 kj-sa
 kj-HN
-kj-dc";
-        assert_eq!(19, count_pathes(larger_input));
-
-        let largest_input = "fs-end
+kj-dc",
+        part1: Some("19"),
+        part2: Some("103"),
+    },
+    Example {
+        name: "even larger",
+        input: "fs-end
 he-DX
 fs-he
 start-DX
@@ -287,7 +247,162 @@ start-pj
 he-WI
 zg-he
 pj-fs
-start-RW";
-        assert_eq!(226, count_pathes(largest_input));
+start-RW",
+        part1: Some("226"),
+        part2: Some("3509"),
+    },
+];
+
+impl Solver for CavesMap {
+    fn parse(input: &str) -> Result<Self> {
+        CavesMap::parse(input)
+    }
+
+    fn part1(&self) -> Answer {
+        count_pathes(self).map_or(Answer::None, Answer::from)
+    }
+
+    fn part2(&self) -> Answer {
+        count_pathes_twice_visited(self).map_or(Answer::None, Answer::from)
+    }
+
+    // size caves besides start and end, large ones are never connected together
+    // so that pathes cannot loop forever
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut names: Vec<String> = vec![];
+        let mut caves = vec![Start, End];
+        while caves.len() < size.max(1) + 2 {
+            let large = rng.one_in(3);
+            let name: String = (0..2)
+                .map(|_| (b'a' + rng.below(26) as u8) as char)
+                .collect();
+            if !names.contains(&name) {
+                caves.push(if large {
+                    Large(name.to_uppercase())
+                } else {
+                    Small(name.clone())
+                });
+                names.push(name);
+            }
+        }
+        let name = |cave: &Cave| match cave {
+            Start => "start".to_string(),
+            End => "end".to_string(),
+            Small(name) | Large(name) => name.clone(),
+        };
+
+        // each cave is linked to an earlier one, then a few more links are added
+        let mut order: Vec<usize> = (2..caves.len()).collect();
+        rng.shuffle(&mut order);
+        order.insert(0, 0);
+        order.push(1);
+        let mut links: Vec<(usize, usize)> = vec![];
+        for i in 1..order.len() {
+            let candidates: Vec<usize> = order[..i]
+                .iter()
+                .copied()
+                .filter(|c| !matches!((&caves[*c], &caves[order[i]]), (Large(_), Large(_))))
+                .filter(|c| i == 1 || (*c, order[i]) != (0, 1))
+                .collect();
+            // start comes first and is never large, there is always a candidate ;
+            // end only goes straight to start when there is no other cave
+            links.push((*rng.pick(&candidates), order[i]));
+        }
+        for _ in 0..size / 2 {
+            let (a, b) = (rng.below(caves.len()), rng.below(caves.len()));
+            let both_large = matches!((&caves[a], &caves[b]), (Large(_), Large(_)));
+            if a != b && !both_large && !links.contains(&(a, b)) && !links.contains(&(b, a)) {
+                links.push((a, b));
+            }
+        }
+        links
+            .iter()
+            .map(|(a, b)| format!("{}-{}\n", name(&caves[*a]), name(&caves[*b])))
+            .collect()
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("number of pathes : {}", answer),
+            Part::Two => format!(
+                "number of path while visiting twice small places : {}",
+                answer
+            ),
+        }
+    }
+}
+
+pub fn display_pathes(input: &str, part: Part) {
+    print_answer::<CavesMap>(input, part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cave_can_be_parsed() {
+        assert_eq!(Start, Cave::parse("start").unwrap());
+        assert_eq!(End, Cave::parse("end").unwrap());
+        assert_eq!(
+            Small("smallcave".to_string()),
+            Cave::parse("smallcave").unwrap()
+        );
+        assert_eq!(
+            Large("BIGCAVE".to_string()),
+            Cave::parse("BIGCAVE").unwrap()
+        );
+        assert!(Cave::parse("MediumCave").is_err());
+        assert!(Cave::parse("not_a_cave").is_err());
+        assert!(Cave::parse("   ").is_err());
+    }
+
+    #[test]
+    fn check_aoc_example() {
+        let simple_input = EXAMPLES[0].input;
+        let simple_map = CavesMap::parse(simple_input).unwrap();
+        assert_eq!(Some(10), count_pathes(&simple_map));
+        assert_eq!(Some(36), count_pathes_twice_visited(&simple_map));
+
+        let larger_input = EXAMPLES[1].input;
+        assert_eq!(
+            Some(19),
+            count_pathes(&CavesMap::parse(larger_input).unwrap())
+        );
+
+        let largest_input = EXAMPLES[2].input;
+        assert_eq!(
+            Some(226),
+            count_pathes(&CavesMap::parse(largest_input).unwrap())
+        );
+    }
+
+    #[test]
+    fn caves_are_not_limited_to_64() {
+        let input = CavesMap::generate(&mut Rng::new(1), 100);
+        let map = CavesMap::parse(&input).unwrap();
+        assert_eq!(102, map.caves.len());
+
+        // a chain of 71 small caves, the last ones beyond the first word
+        let names: Vec<String> = (0..71)
+            .map(|i| format!("{}{}", (b'a' + i / 26) as char, (b'a' + i % 26) as char))
+            .collect();
+        let mut input = format!("start-{}\n", names[0]);
+        for pair in names.windows(2) {
+            input.push_str(&format!("{}-{}\n", pair[0], pair[1]));
+        }
+        input.push_str(&format!("{}-end\n{}-end\n", names[70], names[65]));
+        let map = CavesMap::parse(&input).unwrap();
+        assert_eq!(Some(2), count_pathes(&map));
+    }
+
+    #[test]
+    fn large_caves_cannot_be_connected_together() {
+        let err = CavesMap::parse("start-A\nA-B\nB-end\n").err().unwrap();
+        assert_eq!(
+            "line 2 : 'A-B' : large caves cannot be connected together",
+            err.to_string()
+        );
+        assert!(CavesMap::parse("start-A\nA-A\nA-end\n").is_err());
     }
 }
@@ -1,9 +1,16 @@
+use crate::export::Color;
+use crate::grid::Grid;
+use crate::parse::parse_lines;
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver};
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use std::fmt::{Display, Formatter};
 
 type Points = (usize, usize);
 
+#[derive(Clone)]
 enum Fold {
     X(usize),
     Y(usize),
@@ -24,7 +31,8 @@ impl Fold {
         }
     }
 }
-struct Paper {
+#[derive(Clone)]
+pub struct Paper {
     points: Vec<Points>,
     folds: Vec<Fold>,
 }
@@ -33,39 +41,34 @@ impl Display for Paper {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let max_x = *self.points.iter().map(|(x, _)| x).max().unwrap_or(&0);
         let max_y = *self.points.iter().map(|(_, y)| y).max().unwrap_or(&0);
-        let mut display = vec![vec![' '; max_x + 1]; max_y + 1];
+        let mut display = Grid::new(max_y + 1, max_x + 1, ' ');
 
         f.write_fmt(format_args!("Paper({},{})\n", max_x, max_y))?;
 
         for (x, y) in &self.points {
-            display[*y][*x] = '#';
+            display[(*y, *x)] = '#';
         }
-        for line in &display {
-            f.write_fmt(format_args!("{}\n", line.iter().join("")))?;
-        }
-        Ok(())
+        display.fmt(f)
     }
 }
 
 impl Paper {
-    pub fn parse(input: &str) -> Self {
-        let lines = input.lines();
-        let points: Vec<Points> = lines
-            .clone()
-            .filter(|l| l.contains(','))
-            .filter_map(|l| {
-                l.split(',')
-                    .filter_map(|sub| sub.parse::<usize>().ok())
-                    .collect_tuple()
-            })
-            .collect();
-
-        let folds = lines
-            .clone()
-            .filter_map(|l| l.strip_prefix("fold along "))
-            .filter_map(|l| Fold::new(l).ok())
-            .collect();
-        Self { points, folds }
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut points: Vec<Points> = vec![];
+        let mut folds = vec![];
+        parse_lines(input, |l| {
+            if let Some(fold) = l.strip_prefix("fold along ") {
+                folds.push(Fold::new(fold)?);
+            } else if let Some((x, y)) = l.split(',').collect_tuple() {
+                points.push((x.trim().parse()?, y.trim().parse()?));
+            } else {
+                return Err(anyhow!(
+                    "expecting a 'x,y' dot or a 'fold along' instruction"
+                ));
+            }
+            Ok(())
+        })?;
+        Ok(Self { points, folds })
     }
 
     pub fn fold(&mut self, folds_count: usize) {
@@ -95,32 +98,15 @@ impl Paper {
     }
 }
 
-fn count_dots_after_folding(input: &str, folds_count: usize) -> usize {
-    let mut paper = Paper::parse(input);
+fn count_dots_after_folding(paper: &Paper, folds_count: usize) -> usize {
+    let mut paper = paper.clone();
     paper.fold(folds_count);
     paper.count_points()
 }
 
-pub fn print_origami_details() {
-    let input = include_str!("../resources/day13_transparent_paper.txt");
-
-    println!(
-        "number of dots after 1 fold {}",
-        count_dots_after_folding(input, 1)
-    );
-
-    let mut paper = Paper::parse(input);
-    paper.fold(paper.folds.len());
-    println!("{}", paper);
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn aoc_examples_work() {
-        let input = "6,10
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "transparent paper",
+    input: "6,10
 0,14
 9,10
 0,3
@@ -140,7 +126,108 @@ mod tests {
 9,0
 
 fold along y=7
-fold along x=5";
-        assert_eq!(17, count_dots_after_folding(input, 1))
+fold along x=5",
+    part1: Some("17"),
+    part2: Some("Paper(4,4)\n#####\n#   #\n#   #\n#   #\n#####"),
+}];
+
+impl Solver for Paper {
+    fn parse(input: &str) -> Result<Self> {
+        Paper::parse(input)
+    }
+
+    fn part1(&self) -> Answer {
+        count_dots_after_folding(self, 1).into()
+    }
+
+    fn part2(&self) -> Answer {
+        let mut paper = self.clone();
+        paper.fold(paper.folds.len());
+        paper.to_string().into()
+    }
+
+    // a 40x6 message unfolded a few times, size setting how many
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let (mut width, mut height) = (40, 6);
+        let mut points: Vec<Points> = (0..width)
+            .flat_map(|x| (0..height).map(move |y| (x, y)))
+            .filter(|_| rng.one_in(3))
+            .collect();
+
+        // each unfolding mirrors some dots on the other side of the fold line
+        let mut folds = vec![];
+        for i in 0..size.clamp(1, 12) {
+            let fold = if i % 2 == 0 { Y(height) } else { X(width) };
+            let mut unfolded = vec![];
+            for (x, y) in points {
+                let mirrored = match fold {
+                    X(f) => (2 * f - x, y),
+                    Y(f) => (x, 2 * f - y),
+                };
+                match rng.below(3) {
+                    0 => unfolded.push((x, y)),
+                    1 => unfolded.push(mirrored),
+                    _ => unfolded.extend([(x, y), mirrored]),
+                }
+            }
+            points = unfolded;
+            match fold {
+                X(f) => width = 2 * f + 1,
+                Y(f) => height = 2 * f + 1,
+            }
+            folds.push(fold);
+        }
+
+        rng.shuffle(&mut points);
+        let mut input: String = points
+            .iter()
+            .map(|(x, y)| format!("{},{}\n", x, y))
+            .collect();
+        input.push('\n');
+        for fold in folds.iter().rev() {
+            input += &match fold {
+                X(f) => format!("fold along x={}\n", f),
+                Y(f) => format!("fold along y={}\n", f),
+            };
+        }
+        input
+    }
+
+    // ink dots on the folded paper, with a margin around the code
+    fn picture(&self) -> Option<Grid<Color>> {
+        let mut paper = self.clone();
+        paper.fold(paper.folds.len());
+        let max_x = paper.points.iter().map(|(x, _)| *x).max().unwrap_or(0);
+        let max_y = paper.points.iter().map(|(_, y)| *y).max().unwrap_or(0);
+        let mut picture = Grid::new(max_y + 3, max_x + 3, [250, 245, 230]);
+        for (x, y) in &paper.points {
+            picture[(y + 1, x + 1)] = [20, 30, 90];
+        }
+        Some(picture)
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("number of dots after 1 fold {}", answer),
+            Part::Two => format!("{}", answer),
+        }
+    }
+}
+
+pub fn print_origami_details(input: &str, part: Part) {
+    print_answer::<Paper>(input, part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aoc_examples_work() {
+        let input = EXAMPLES[0].input;
+        assert_eq!(
+            17,
+            count_dots_after_folding(&Paper::parse(input).unwrap(), 1)
+        )
     }
 }
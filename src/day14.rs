@@ -1,6 +1,13 @@
+use crate::parse::parse_numbered_lines;
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver, Variant};
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
-struct Polymer {
+#[derive(Clone)]
+pub struct Polymer {
+    template: Vec<u8>,
     // store the number of _sequence for x+256 y
     pair_counts: Vec<usize>,
     chemistry: Vec<Option<u8>>,
@@ -13,10 +20,16 @@ impl Polymer {
         (c1 as usize) + 256 * (c2 as usize)
     }
 
-    pub fn parse(input: &str) -> Self {
-        let mut lines = input.lines();
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut lines = input.lines().enumerate();
 
-        let bytes: Vec<_> = lines.next().unwrap().bytes().collect();
+        let bytes: Vec<_> = lines
+            .next()
+            .map(|(_, l)| l.trim().bytes().collect())
+            .unwrap_or_default();
+        if bytes.is_empty() {
+            return Err(anyhow!("missing polymer template"));
+        }
         let mut pair_counts = vec![0usize; 256 * 256];
         for (c1, c2) in bytes.iter().tuple_windows() {
             pair_counts[Self::get_index(*c1, *c2)] += 1;
@@ -25,21 +38,26 @@ impl Polymer {
         let last_component = bytes[bytes.len() - 1];
 
         let mut chemistry = vec![None; 256 * 256];
-        for ((c1, c2), new) in lines.filter_map(|l| {
-            // let's try without collect_tuple
-            let mut vals = l.split(" -> ");
-            let mut pair = vals.next()?.bytes();
-            let new = vals.next()?.bytes().next()?;
-            Some(((pair.next()?, pair.next()?), new))
-        }) {
+        let rules = parse_numbered_lines(lines, |l| {
+            if let Some((&[c1, c2], &[new])) = l
+                .split_once(" -> ")
+                .map(|(pair, new)| (pair.trim().as_bytes(), new.trim().as_bytes()))
+            {
+                Ok(((c1, c2), new))
+            } else {
+                Err(anyhow!("expecting a 'AB -> C' insertion rule"))
+            }
+        })?;
+        for ((c1, c2), new) in rules {
             chemistry[Self::get_index(c1, c2)] = Some(new);
         }
 
-        Self {
+        Ok(Self {
+            template: bytes,
             pair_counts,
             chemistry,
             last_component,
-        }
+        })
     }
 
     pub fn grow(&mut self, steps: usize) {
@@ -84,27 +102,31 @@ impl Polymer {
     }
 }
 
-pub fn display_polymer() {
-    let input = include_str!("../resources/day14_chemistry.txt");
+fn get_quantities_spread_after(polymer: &Polymer, steps: usize) -> usize {
+    let mut polymer = polymer.clone();
+    polymer.grow(steps);
+    let elt_counts = polymer.decompose_and_sort_quantities();
+    elt_counts[0] - elt_counts[elt_counts.len() - 1]
+}
 
-    let mut polymer = Polymer::parse(input);
-    for i in 1..5 {
-        polymer.grow(10);
-        let elt_counts = polymer.decompose_and_sort_quantities();
-        println!(
-            "Difference between most common an least common element after {} steps : {}",
-            10 * i,
-            elt_counts[0] - elt_counts[elt_counts.len() - 1]
-        );
+/// reference for get_quantities_spread_after, inserting every element in the polymer itself
+fn grow_every_element(polymer: &Polymer, steps: usize) -> usize {
+    let mut elements = polymer.template.clone();
+    for _ in 0..steps {
+        let mut grown = vec![elements[0]];
+        for (c1, c2) in elements.iter().tuple_windows() {
+            grown.extend(polymer.chemistry[Polymer::get_index(*c1, *c2)]);
+            grown.push(*c2);
+        }
+        elements = grown;
     }
+    let counts = elements.into_iter().counts();
+    counts.values().max().unwrap() - counts.values().min().unwrap()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    #[test]
-    fn aoc_example_works() {
-        let input = "NNCB
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "polymer template",
+    input: "NNCB
 
 CH -> B
 HH -> N
@@ -121,9 +143,71 @@ BN -> B
 BB -> N
 BC -> B
 CC -> N
-CN -> C";
+CN -> C",
+    part1: Some("1588"),
+    part2: Some("2188189693529"),
+}];
+
+impl Solver for Polymer {
+    fn parse(input: &str) -> Result<Self> {
+        Polymer::parse(input)
+    }
+
+    fn part1(&self) -> Answer {
+        get_quantities_spread_after(self, 10).into()
+    }
+
+    fn part2(&self) -> Answer {
+        get_quantities_spread_after(self, 40).into()
+    }
+
+    // a template of size elements, with a rule for every pair of the 10 elements
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let elements: Vec<char> = "BCFHKNOPSV".chars().collect();
+        let template: String = (0..size.max(2)).map(|_| *rng.pick(&elements)).collect();
+        let mut input = template + "\n\n";
+        for a in &elements {
+            for b in &elements {
+                input += &format!("{}{} -> {}\n", a, b, rng.pick(&elements));
+            }
+        }
+        input
+    }
+
+    // the polymer doubles at each step, 40 of them are out of reach
+    fn variants() -> Vec<Variant<Self>> {
+        vec![Variant {
+            name: "every element",
+            part: Part::One,
+            solve: |p| grow_every_element(p, 10).into(),
+        }]
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        let steps = match part {
+            Part::One => 10,
+            Part::Two => 40,
+        };
+        format!(
+            "Difference between most common an least common element after {} steps : {}",
+            steps, answer
+        )
+    }
+}
+
+pub fn display_polymer(input: &str, part: Part) {
+    print_answer::<Polymer>(input, part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn aoc_example_works() {
+        let input = EXAMPLES[0].input;
 
-        let mut polymer = Polymer::parse(input);
+        let mut polymer = Polymer::parse(input).unwrap();
+        assert_eq!(1588, grow_every_element(&polymer, 10));
         let elt_counts = polymer.decompose_and_sort_quantities();
         assert_eq!(1, elt_counts[0] - elt_counts[elt_counts.len() - 1]);
         polymer.grow(10);
@@ -1,78 +1,118 @@
-use crate::day9::{get_neighbours_pos_horz_vert, Point};
-use itertools::Itertools;
-
-fn get_lowest_risk(input: &str, map_factor: usize) -> usize {
-    let base_risks: Vec<Vec<usize>> = input
-        .lines()
-        .map(|l| {
-            l.split("")
-                .filter_map(|c| c.parse::<usize>().ok())
-                .collect()
+use crate::export::{blend, Color, WHITE};
+use crate::grid::{Grid, Point};
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::search::{astar, dijkstra, SearchPath};
+use crate::solver::{print_answer, Answer, Example, Solver, Variant};
+use anyhow::Result;
+
+fn parse_risks(input: &str) -> Result<Grid<usize>> {
+    Grid::parse_digits(input)
+}
+
+fn tile_risks(base_risks: &Grid<usize>, map_factor: usize) -> Grid<usize> {
+    base_risks.tiled(map_factor, |risk, (tile_row, tile_col)| {
+        (risk - 1 + tile_row + tile_col) % 9 + 1
+    })
+}
+
+fn get_lowest_risk_path(risks: &Grid<usize>) -> SearchPath<Point> {
+    let exit = (risks.height() - 1, risks.width() - 1);
+    dijkstra(
+        (0, 0),
+        |p| risks.neighbours(*p).map(|n| (n, risks[n])),
+        |p| *p == exit,
+    )
+    .expect("the exit can always be reached")
+}
+
+fn get_lowest_risk(base_risks: &Grid<usize>, map_factor: usize) -> usize {
+    get_lowest_risk_path(&tile_risks(base_risks, map_factor)).cost
+}
+
+// each step costs at least 1, the manhattan distance never overestimates the risk
+fn get_lowest_risk_a_star(base_risks: &Grid<usize>, map_factor: usize) -> usize {
+    let risks = tile_risks(base_risks, map_factor);
+    let exit = (risks.height() - 1, risks.width() - 1);
+    astar(
+        (0, 0),
+        |p| risks.neighbours(*p).map(|n| (n, risks[n])),
+        |p: &Point| exit.0 - p.0 + exit.1 - p.1,
+        |p| *p == exit,
+    )
+    .expect("the exit can always be reached")
+    .cost
+}
+
+pub struct RiskMap {
+    risks: Grid<usize>,
+}
+
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "risk levels",
+    input: "1163751742
+1381373672
+2136511328
+3694931569
+7463417111
+1319128137
+1359912421
+3125421639
+1293138521
+2311944581",
+    part1: Some("40"),
+    part2: Some("315"),
+}];
+
+impl Solver for RiskMap {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            risks: parse_risks(input)?,
         })
-        .collect();
-
-    let dim = (base_risks.len(), base_risks[0].len());
-    let mut risks: Vec<Vec<usize>> = vec![vec![0; dim.0 * map_factor]; dim.1 * map_factor];
-    for mx in 0..map_factor {
-        for my in 0..map_factor {
-            for i in 0..dim.0 {
-                for j in 0..dim.1 {
-                    risks[i + mx * dim.0][j + my * dim.1] =
-                        (base_risks[i][j] - 1 + mx + my) % 9 + 1;
-                }
-            }
-        }
     }
-    let dim = (dim.0 * map_factor, dim.1 * map_factor);
-
-    let mut best_risks: Vec<Vec<Option<usize>>> = vec![vec![None; dim.0]; dim.1];
-
-    best_risks[0][0] = Some(0);
-
-    let mut current_points: Vec<_> = vec![(0, 0)];
-    loop {
-        let new_risked_points: Vec<(Point, usize)> = current_points
-            .into_iter()
-            .map(|p| (p, best_risks[p.0][p.1].unwrap()))
-            .flat_map(|(cur, current_risk)| {
-                get_neighbours_pos_horz_vert(&cur, &dim)
-                    .into_iter()
-                    .map(|p| (p, current_risk + risks[p.0][p.1]))
-                    .collect_vec()
-                    .into_iter()
-            })
-            .collect();
-
-        for (p, r) in &new_risked_points {
-            if best_risks[p.0][p.1].unwrap_or(usize::MAX) > *r {
-                best_risks[p.0][p.1] = Some(*r);
-            }
-        }
-        current_points = new_risked_points
-            .into_iter()
-            .filter_map(|(p, r)| match r {
-                r if r == best_risks[p.0][p.1].unwrap() => Some(p),
-                _ => None,
-            })
-            .unique()
-            .collect();
-
-        if current_points.is_empty() {
-            break;
+
+    fn part1(&self) -> Answer {
+        get_lowest_risk(&self.risks, 1).into()
+    }
+
+    fn part2(&self) -> Answer {
+        get_lowest_risk(&self.risks, 5).into()
+    }
+
+    // a size x size cave
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        Grid::from_fn(size, size, |_| rng.below(9) + 1).to_string()
+    }
+
+    fn variants() -> Vec<Variant<Self>> {
+        vec![Variant {
+            name: "a star",
+            part: Part::Two,
+            solve: |m| get_lowest_risk_a_star(&m.risks, 5).into(),
+        }]
+    }
+
+    // the riskier, the darker, the path of the first part being red
+    fn picture(&self) -> Option<Grid<Color>> {
+        let mut picture = self
+            .risks
+            .map(|risk| blend(WHITE, [30, 40, 60], (*risk - 1) as f64 / 8.0));
+        for p in get_lowest_risk_path(&self.risks).states {
+            picture[p] = [220, 30, 30];
         }
+        Some(picture)
     }
 
-    best_risks[dim.0 - 1][dim.1 - 1].unwrap()
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("lowest_risk {}", answer),
+            Part::Two => format!("lowest_risk for {} expansion : {}", 5, answer),
+        }
+    }
 }
 
-pub fn display_safest_path() {
-    let input = include_str!("../resources/day15_risks.txt");
-    println!("lowest_risk {}", get_lowest_risk(input, 1));
-    println!(
-        "lowest_risk for {} expansion : {}",
-        5,
-        get_lowest_risk(input, 5)
-    );
+pub fn display_safest_path(input: &str, part: Part) {
+    print_answer::<RiskMap>(input, part)
 }
 
 #[cfg(test)]
@@ -81,18 +121,11 @@ mod tests {
 
     #[test]
     fn aoc_example_works() {
-        let input = "1163751742
-1381373672
-2136511328
-3694931569
-7463417111
-1319128137
-1359912421
-3125421639
-1293138521
-2311944581";
-        assert_eq!(40, get_lowest_risk(input, 1));
+        let input = EXAMPLES[0].input;
+        let risks = parse_risks(input).unwrap();
+        assert_eq!(40, get_lowest_risk(&risks, 1));
 
-        assert_eq!(315, get_lowest_risk(input, 5));
+        assert_eq!(315, get_lowest_risk(&risks, 5));
+        assert_eq!(315, get_lowest_risk_a_star(&risks, 5));
     }
 }
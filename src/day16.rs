@@ -1,45 +1,31 @@
 use crate::day16::Operator::{Maximum, Minimum, Product, Sum, Unknown, EQ, GT, LT};
+use crate::parse::ParseError;
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver};
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
-fn parse_hexa(input: &str) -> Vec<char> {
-    input
-        .chars()
-        .flat_map(|c| match c {
-            '0' => "0000".chars(),
-            '1' => "0001".chars(),
-            '2' => "0010".chars(),
-            '3' => "0011".chars(),
-            '4' => "0100".chars(),
-            '5' => "0101".chars(),
-            '6' => "0110".chars(),
-            '7' => "0111".chars(),
-            '8' => "1000".chars(),
-            '9' => "1001".chars(),
-            'A' => "1010".chars(),
-            'B' => "1011".chars(),
-            'C' => "1100".chars(),
-            'D' => "1101".chars(),
-            'E' => "1110".chars(),
-            'F' => "1111".chars(),
-            _ => "".chars(),
-        })
-        .collect()
-}
-fn parse_bit(c: char) -> Option<usize> {
-    match c {
-        '0' => Some(0),
-        '1' => Some(1),
-        o => {
-            eprintln!("cannot parse '{}", o);
-            None
-        }
+fn parse_hexa(input: &str) -> Result<Vec<char>> {
+    let mut bits = vec![];
+    for (i, c) in input.trim().chars().enumerate() {
+        let quartet = c.to_digit(16).ok_or_else(|| {
+            ParseError::at(1, i + 1, format!("'{}' is not an hexadecimal digit", c))
+        })?;
+        bits.extend(format!("{:04b}", quartet).chars());
     }
+    Ok(bits)
 }
-fn bits_to_num(bits: &mut dyn Iterator<Item = char>, bit_count: usize) -> usize {
-    bits.take(bit_count)
-        .enumerate()
-        .filter_map(|(i, c)| parse_bit(c).map(|v| v << (bit_count - 1 - i)))
-        .sum()
+
+fn bits_to_num(bits: &mut dyn Iterator<Item = char>, bit_count: usize) -> Result<usize> {
+    let mut value = 0;
+    for _ in 0..bit_count {
+        let bit = bits
+            .next()
+            .ok_or_else(|| anyhow!("the transmission ends in the middle of a packet"))?;
+        value = (value << 1) + if bit == '1' { 1 } else { 0 };
+    }
+    Ok(value)
 }
 
 #[derive(Debug, PartialEq)]
@@ -81,64 +67,78 @@ enum PacketContent {
     Operation(Operation),
 }
 impl PacketContent {
-    fn parse_literal(bits: &mut dyn Iterator<Item = char>) -> usize {
+    fn parse_literal(bits: &mut dyn Iterator<Item = char>) -> Result<usize> {
         let mut value: usize = 0;
         loop {
-            let last_quartet = bits.next().unwrap() == '0';
-            let val = bits_to_num(bits, 4);
+            let last_quartet = bits_to_num(bits, 1)? == 0;
+            let val = bits_to_num(bits, 4)?;
             value = (value << 4) + val;
             if last_quartet {
                 break;
             }
         }
-        value
+        Ok(value)
     }
-    fn parse(bits: &mut dyn Iterator<Item = char>) -> Self {
-        let typeid = bits_to_num(bits, 3);
-        match typeid {
-            4 => PacketContent::Literal(PacketContent::parse_literal(bits)),
-            id => PacketContent::Operation(PacketContent::parse_operation(id, bits)),
-        }
+    fn parse(bits: &mut dyn Iterator<Item = char>) -> Result<Self> {
+        let typeid = bits_to_num(bits, 3)?;
+        Ok(match typeid {
+            4 => PacketContent::Literal(PacketContent::parse_literal(bits)?),
+            id => PacketContent::Operation(PacketContent::parse_operation(id, bits)?),
+        })
     }
-    fn parse_operation(typeid: usize, bits: &mut dyn Iterator<Item = char>) -> Operation {
+    fn parse_operation(typeid: usize, bits: &mut dyn Iterator<Item = char>) -> Result<Operation> {
         let op = Operator::new(typeid);
-        let length_type = bits.next().unwrap();
-        match length_type {
-            '0' => {
-                let subpackets_length = bits_to_num(bits, 15);
+        let length_type = bits_to_num(bits, 1)?;
+        let operands = match length_type {
+            0 => {
+                let subpackets_length = bits_to_num(bits, 15)?;
                 // TODO : see if bits are consumed as well
                 let mut subpackets_bits = bits.take(subpackets_length).peekable();
                 let mut operands = vec![];
                 while subpackets_bits.peek().is_some() {
-                    operands.push(Packet::parse(&mut subpackets_bits));
+                    operands.push(Packet::parse(&mut subpackets_bits)?);
                 }
-                Operation { op, operands }
+                operands
             }
             _ => {
-                let operands_count = bits_to_num(bits, 11);
-                let operands = (0..operands_count).map(|_| Packet::parse(bits)).collect();
-                Operation { op, operands }
+                let operands_count = bits_to_num(bits, 11)?;
+                (0..operands_count)
+                    .map(|_| Packet::parse(bits))
+                    .collect::<Result<_>>()?
             }
+        };
+
+        let expected = match op {
+            GT | LT | EQ => operands.len() == 2,
+            _ => !operands.is_empty(),
+        };
+        if !expected {
+            return Err(anyhow!(
+                "{:?} operator cannot apply to {} operands",
+                op,
+                operands.len()
+            ));
         }
+        Ok(Operation { op, operands })
     }
 }
 
 #[derive(Debug, PartialEq)]
-struct Packet {
+pub struct Packet {
     version: usize,
     content: PacketContent,
 }
 
 impl Packet {
-    fn from_str(input: &str) -> Self {
-        Self::parse(&mut parse_hexa(input).into_iter())
+    pub fn from_hexa(input: &str) -> Result<Self> {
+        Self::parse(&mut parse_hexa(input)?.into_iter())
     }
-    fn parse(bits: &mut dyn Iterator<Item = char>) -> Self {
-        let version = bits_to_num(bits, 3);
-        let content = PacketContent::parse(bits);
-        Self { version, content }
+    fn parse(bits: &mut dyn Iterator<Item = char>) -> Result<Self> {
+        let version = bits_to_num(bits, 3)?;
+        let content = PacketContent::parse(bits)?;
+        Ok(Self { version, content })
     }
-    fn sum_version(&self) -> usize {
+    pub fn sum_version(&self) -> usize {
         match &self.content {
             PacketContent::Literal(_) => self.version,
             PacketContent::Operation(op) => {
@@ -151,7 +151,7 @@ impl Packet {
         }
     }
 
-    fn value(&self) -> usize {
+    pub fn value(&self) -> usize {
         match &self.content {
             PacketContent::Literal(v) => *v,
             PacketContent::Operation(op) => match op.op {
@@ -206,13 +206,172 @@ impl Packet {
     }
 }
 
-pub fn print_bits() {
-    let input = include_str!("../resources/day16_bits.txt");
-    println!(
-        "summed BITS versions : {}",
-        Packet::from_str(input).sum_version()
+fn push_bits(bits: &mut String, value: usize, bit_count: usize) {
+    bits.extend(
+        (0..bit_count)
+            .rev()
+            .map(|i| if value >> i & 1 == 1 { '1' } else { '0' }),
     );
-    println!("BITS value : {}", Packet::from_str(input).value());
+}
+
+/// a random packet nesting at most budget operations, products only apply to
+/// small literals so that values cannot overflow
+fn random_packet_bits(rng: &mut Rng, depth: usize, budget: &mut usize, literal: bool) -> String {
+    let mut bits = String::new();
+    push_bits(&mut bits, rng.below(8), 3);
+
+    if literal || depth >= 6 || *budget == 0 || rng.one_in(4) {
+        let value = if literal {
+            rng.below(16)
+        } else {
+            let magnitude = rng.below(20);
+            rng.below(1 << magnitude)
+        };
+        push_bits(&mut bits, 4, 3);
+        let groups = (usize::BITS - value.leading_zeros()).div_ceil(4).max(1) as usize;
+        for group in (0..groups).rev() {
+            bits.push(if group == 0 { '0' } else { '1' });
+            push_bits(&mut bits, value >> (4 * group) & 0xF, 4);
+        }
+        return bits;
+    }
+
+    *budget -= 1;
+    let typeid = *rng.pick(&[0, 1, 2, 3, 5, 6, 7]);
+    let operands_count = match typeid {
+        5..=7 => 2,
+        1 => rng.between(1, 3) as usize,
+        _ => rng.between(1, 5) as usize,
+    };
+    let operands: String = (0..operands_count)
+        .map(|_| random_packet_bits(rng, depth + 1, budget, typeid == 1))
+        .collect();
+    push_bits(&mut bits, typeid, 3);
+    if rng.one_in(2) && operands.len() < 1 << 15 {
+        bits.push('0');
+        push_bits(&mut bits, operands.len(), 15);
+    } else {
+        bits.push('1');
+        push_bits(&mut bits, operands_count, 11);
+    }
+    bits + &operands
+}
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        name: "sum of versions 1",
+        input: "8A004A801A8002F478",
+        part1: Some("16"),
+        part2: None,
+    },
+    Example {
+        name: "sum of versions 2",
+        input: "620080001611562C8802118E34",
+        part1: Some("12"),
+        part2: None,
+    },
+    Example {
+        name: "sum of versions 3",
+        input: "C0015000016115A2E0802F182340",
+        part1: Some("23"),
+        part2: None,
+    },
+    Example {
+        name: "sum of versions 4",
+        input: "A0016C880162017C3686B18A3D4780",
+        part1: Some("31"),
+        part2: None,
+    },
+    Example {
+        name: "sum",
+        input: "C200B40A82",
+        part1: None,
+        part2: Some("3"),
+    },
+    Example {
+        name: "product",
+        input: "04005AC33890",
+        part1: None,
+        part2: Some("54"),
+    },
+    Example {
+        name: "minimum",
+        input: "880086C3E88112",
+        part1: None,
+        part2: Some("7"),
+    },
+    Example {
+        name: "maximum",
+        input: "CE00C43D881120",
+        part1: None,
+        part2: Some("9"),
+    },
+    Example {
+        name: "less than",
+        input: "D8005AC2A8F0",
+        part1: None,
+        part2: Some("1"),
+    },
+    Example {
+        name: "greater than",
+        input: "F600BC2D8F",
+        part1: None,
+        part2: Some("0"),
+    },
+    Example {
+        name: "equal to",
+        input: "9C005AC2F8F0",
+        part1: None,
+        part2: Some("0"),
+    },
+    Example {
+        name: "sum equal to product",
+        input: "9C0141080250320F1802104A08",
+        part1: None,
+        part2: Some("1"),
+    },
+];
+
+impl Solver for Packet {
+    fn parse(input: &str) -> Result<Self> {
+        Packet::from_hexa(input)
+    }
+
+    fn part1(&self) -> Answer {
+        self.sum_version().into()
+    }
+
+    fn part2(&self) -> Answer {
+        self.value().into()
+    }
+
+    // a transmission of about size operator packets
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut bits = random_packet_bits(rng, 0, &mut size.clone(), false);
+        while !bits.len().is_multiple_of(4) {
+            bits.push('0');
+        }
+        let hexa: String = bits
+            .as_bytes()
+            .chunks(4)
+            .map(|quartet| {
+                let value = quartet.iter().fold(0, |acc, b| acc * 2 + (b - b'0') as u32);
+                char::from_digit(value, 16).unwrap().to_ascii_uppercase()
+            })
+            .collect();
+        hexa + "\n"
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("summed BITS versions : {}", answer),
+            Part::Two => format!("BITS value : {}", answer),
+        }
+    }
+}
+
+pub fn print_bits(input: &str, part: Part) {
+    print_answer::<Packet>(input, part)
 }
 
 #[cfg(test)]
@@ -222,16 +381,16 @@ mod tests {
     #[test]
     fn aoc_examples_work() {
         let input = "D2FE28";
-        let mut bits = parse_hexa(input).into_iter();
+        let mut bits = parse_hexa(input).unwrap().into_iter();
         assert_eq!(
             Packet {
                 version: 6,
                 content: PacketContent::Literal(2021)
             },
-            Packet::parse(&mut bits)
+            Packet::parse(&mut bits).unwrap()
         );
 
-        let op = Packet::parse(&mut parse_hexa("38006F45291200").into_iter());
+        let op = Packet::parse(&mut parse_hexa("38006F45291200").unwrap().into_iter()).unwrap();
         assert_eq!(
             Packet {
                 version: 1,
@@ -252,7 +411,7 @@ mod tests {
             op
         );
 
-        let op = Packet::parse(&mut parse_hexa("EE00D40C823060").into_iter());
+        let op = Packet::parse(&mut parse_hexa("EE00D40C823060").unwrap().into_iter()).unwrap();
         assert_eq!(
             Packet {
                 version: 7,
@@ -277,27 +436,46 @@ mod tests {
             op
         );
 
-        assert_eq!(16, Packet::from_str("8A004A801A8002F478").sum_version());
+        assert_eq!(
+            16,
+            Packet::from_hexa("8A004A801A8002F478")
+                .unwrap()
+                .sum_version()
+        );
         assert_eq!(
             12,
-            Packet::from_str("620080001611562C8802118E34").sum_version()
+            Packet::from_hexa("620080001611562C8802118E34")
+                .unwrap()
+                .sum_version()
         );
         assert_eq!(
             23,
-            Packet::from_str("C0015000016115A2E0802F182340").sum_version()
+            Packet::from_hexa("C0015000016115A2E0802F182340")
+                .unwrap()
+                .sum_version()
         );
         assert_eq!(
             31,
-            Packet::from_str("A0016C880162017C3686B18A3D4780").sum_version()
+            Packet::from_hexa("A0016C880162017C3686B18A3D4780")
+                .unwrap()
+                .sum_version()
+        );
+
+        assert_eq!(3, Packet::from_hexa("C200B40A82").unwrap().value());
+        assert_eq!(54, Packet::from_hexa("04005AC33890").unwrap().value());
+        assert_eq!(7, Packet::from_hexa("880086C3E88112").unwrap().value());
+        assert_eq!(9, Packet::from_hexa("CE00C43D881120").unwrap().value());
+        assert_eq!(1, Packet::from_hexa("D8005AC2A8F0").unwrap().value());
+        assert_eq!(0, Packet::from_hexa("F600BC2D8F").unwrap().value());
+        assert_eq!(0, Packet::from_hexa("9C005AC2F8F0").unwrap().value());
+        assert_eq!(
+            1,
+            Packet::from_hexa("9C0141080250320F1802104A08")
+                .unwrap()
+                .value()
         );
 
-        assert_eq!(3, Packet::from_str("C200B40A82").value());
-        assert_eq!(54, Packet::from_str("04005AC33890").value());
-        assert_eq!(7, Packet::from_str("880086C3E88112").value());
-        assert_eq!(9, Packet::from_str("CE00C43D881120").value());
-        assert_eq!(1, Packet::from_str("D8005AC2A8F0").value());
-        assert_eq!(0, Packet::from_str("F600BC2D8F").value());
-        assert_eq!(0, Packet::from_str("9C005AC2F8F0").value());
-        assert_eq!(1, Packet::from_str("9C0141080250320F1802104A08").value());
+        assert!(Packet::from_hexa("9C01410802").is_err());
+        assert!(Packet::from_hexa("9C0141080250320F18021G4A08").is_err());
     }
 }
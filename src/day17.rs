@@ -1,9 +1,13 @@
-use anyhow::{anyhow, Error};
+use crate::parse::ParseError;
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver, Variant};
+use anyhow::{anyhow, Error, Result};
 use itertools::Itertools;
 use std::cmp::{max, min};
 use std::str::FromStr;
 
-struct TargetArea {
+pub struct TargetArea {
     x: (isize, isize),
     y: (isize, isize),
 }
@@ -102,26 +106,110 @@ fn find_highest_position(target: &TargetArea) -> Option<isize> {
     highest
 }
 
-pub fn display_trajectory() {
-    let input = include_str!("../resources/day17_targetarea.txt");
-    let area = input.parse().unwrap();
-    println!(
-        "highest y position reachable : {}",
-        find_highest_position(&area).unwrap()
-    );
-    println!(
-        "number of valid trajectories : {}",
-        count_valid_trajectories(&area)
-    );
+/// reference for the searches above : every velocity that does not overshoot the target
+/// at its first step is launched, the highest point of those hitting it being kept
+fn launch_every_probe(target: &TargetArea) -> Vec<isize> {
+    let reach = max(target.y.0.abs(), target.y.1.abs());
+    let mut highest_points = vec![];
+    for init_vx in min(0, target.x.0)..=max(0, target.x.1) {
+        for init_vy in -reach..=reach {
+            let (mut x, mut y, mut vx, mut vy, mut highest) = (0, 0, init_vx, init_vy, 0);
+            while y >= target.y.0 || vy > 0 {
+                x += vx;
+                y += vy;
+                highest = max(highest, y);
+                vx -= vx.signum();
+                vy -= 1;
+                if (target.x.0..=target.x.1).contains(&x) && (target.y.0..=target.y.1).contains(&y)
+                {
+                    highest_points.push(highest);
+                    break;
+                }
+            }
+        }
+    }
+    highest_points
+}
+
+// no probe may reach the target, and a target above the launcher can be reached on the way down
+fn highest_answer(highest: Option<isize>) -> Answer {
+    match highest.map(usize::try_from) {
+        Some(Ok(highest)) => highest.into(),
+        Some(Err(_)) => Answer::Text(highest.unwrap().to_string()),
+        None => Answer::None,
+    }
+}
+
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "target area",
+    input: "target area: x=20..30, y=-10..-5",
+    part1: Some("45"),
+    part2: Some("112"),
+}];
+
+impl Solver for TargetArea {
+    fn parse(input: &str) -> Result<Self> {
+        input
+            .parse()
+            .map_err(|e| ParseError::new(1, format!("{:#}", e)).into())
+    }
+
+    fn part1(&self) -> Answer {
+        highest_answer(find_highest_position(self))
+    }
+
+    fn part2(&self) -> Answer {
+        count_valid_trajectories(self).into()
+    }
+
+    // a target ahead and below the probe, further away as size grows
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let size = size.max(1) as isize;
+        let x_min = rng.between(10, 10 + size);
+        let x_max = x_min + rng.between(5, 5 + size / 2);
+        let y_max = -rng.between(5, 5 + size / 4);
+        let y_min = y_max - rng.between(5, 5 + size / 4);
+        format!(
+            "target area: x={}..{}, y={}..{}\n",
+            x_min, x_max, y_min, y_max
+        )
+    }
+
+    fn variants() -> Vec<Variant<Self>> {
+        vec![
+            Variant {
+                name: "every probe",
+                part: Part::One,
+                solve: |t| highest_answer(launch_every_probe(t).into_iter().max()),
+            },
+            Variant {
+                name: "every probe count",
+                part: Part::Two,
+                solve: |t| launch_every_probe(t).len().into(),
+            },
+        ]
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("highest y position reachable : {}", answer),
+            Part::Two => format!("number of valid trajectories : {}", answer),
+        }
+    }
+}
+
+pub fn display_trajectory(input: &str, part: Part) {
+    print_answer::<TargetArea>(input, part)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::solver::Solution;
 
     #[test]
     fn aoc_example_works() {
-        let input = "target area: x=20..30, y=-10..-5";
+        let input = EXAMPLES[0].input;
 
         let t_area: TargetArea = input.parse().unwrap();
 
@@ -137,5 +225,18 @@ mod tests {
         assert_eq!(Some(45), find_highest_position(&t_area));
 
         assert_eq!(112, count_valid_trajectories(&t_area));
+
+        let highest_points = launch_every_probe(&t_area);
+        assert_eq!(Some(&45), highest_points.iter().max());
+        assert_eq!(112, highest_points.len());
+    }
+
+    #[test]
+    fn unreachable_targets_have_no_highest_point() {
+        let t_area: TargetArea = "target area: x=30..20, y=-10..-5".parse().unwrap();
+
+        assert_eq!(Answer::None, t_area.part1());
+        assert_eq!(Answer::Number(0), t_area.part2());
+        assert_eq!(Answer::None, t_area.solve_variant("every probe").unwrap());
     }
 }
@@ -1,5 +1,10 @@
 use crate::day18::FishNumberPart::{Complex, Simple};
-use anyhow::{anyhow, Error};
+use crate::parse::parse_lines;
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver};
+use crate::trace::debug;
+use anyhow::{anyhow, Error, Result};
 use std::fmt::{Display, Formatter};
 use std::iter::Sum;
 use std::ops::Deref;
@@ -57,7 +62,7 @@ impl FishNumberPart {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-struct FishNumber {
+pub struct FishNumber {
     left: Box<FishNumberPart>,
     right: Box<FishNumberPart>,
 }
@@ -118,15 +123,19 @@ impl FishNumber {
         result
     }
 
-    fn magnitude(&self) -> usize {
+    pub fn magnitude(&self) -> usize {
         3 * self.left.magnitude() + 2 * self.right.magnitude()
     }
 
-    fn try_read(input: &str) -> Result<Self, Error> {
+    pub fn try_read(input: &str) -> Result<Self, Error> {
         let mut parts = input
             .trim()
             .split_inclusive(|c| ['[', ',', ']'].contains(&c));
         if let Complex(p) = FishNumberPart::try_read(&mut parts)? {
+            // closing brackets are skipped while reading, anything else is left over
+            if let Some(extra) = parts.find(|p| *p != "]") {
+                return Err(anyhow!("unexpected '{}' after the FishNumber", extra));
+            }
             Ok(p)
         } else {
             Err(anyhow!("'{}' is not a valid Complex FishNumber", input))
@@ -176,7 +185,7 @@ impl FishNumber {
             let mut previous_d: usize = reduce[0].1;
             let len = reduce.len();
 
-            // eprintln!("reducing {}", Self::from_vect(&reduce));
+            debug!("reducing {}", Self::from_vect(&reduce));
             // searching for xplosion
             for i in 1..len {
                 let current = reduce[i];
@@ -247,21 +256,88 @@ fn get_max_magnitude_from_addition(numbers: &[FishNumber]) -> usize {
     max_mag
 }
 
-pub fn display_additions() {
-    let input = include_str!("../resources/day18_fish_numbers.txt");
-    let numbers: Vec<_> = input
-        .lines()
-        .filter_map(|l| FishNumber::try_read(l).ok())
-        .collect();
-
-    let max_magnitude = get_max_magnitude_from_addition(&numbers);
-    let sum = numbers.into_iter().sum::<FishNumber>();
-
-    println!("FishNumber sum magnitude: {}", sum.magnitude());
-    println!(
-        "FishNumber single addition max magnitude: {}",
-        max_magnitude
-    );
+pub struct Homework {
+    numbers: Vec<FishNumber>,
+}
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        name: "larger sum",
+        input: "[[[0,[4,5]],[0,0]],[[[4,5],[2,6]],[9,5]]]
+[7,[[[3,7],[4,3]],[[6,3],[8,8]]]]
+[[2,[[0,8],[3,4]]],[[[6,7],1],[7,[1,6]]]]
+[[[[2,4],7],[6,[0,5]]],[[[6,8],[2,8]],[[2,1],[4,5]]]]
+[7,[5,[[3,8],[1,4]]]]
+[[2,[2,2]],[8,[8,1]]]
+[2,9]
+[1,[[[9,3],9],[[9,0],[0,7]]]]
+[[[5,[7,4]],7],1]
+[[[[4,2],2],6],[8,7]]",
+        part1: Some("3488"),
+        part2: None,
+    },
+    Example {
+        name: "homework",
+        input: "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
+[[[5,[2,8]],4],[5,[[9,9],0]]]
+[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
+[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
+[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]
+[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]
+[[[[5,4],[7,7]],8],[[8,3],8]]
+[[9,3],[[9,9],[6,[4,9]]]]
+[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
+[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]",
+        part1: Some("4140"),
+        part2: Some("3993"),
+    },
+];
+
+impl Solver for Homework {
+    fn parse(input: &str) -> Result<Self> {
+        let numbers = parse_lines(input, FishNumber::try_read)?;
+        if numbers.is_empty() {
+            return Err(anyhow!("no FishNumber to add"));
+        }
+        Ok(Self { numbers })
+    }
+
+    fn part1(&self) -> Answer {
+        let sum = self.numbers.iter().cloned().sum::<FishNumber>();
+        sum.magnitude().into()
+    }
+
+    fn part2(&self) -> Answer {
+        get_max_magnitude_from_addition(&self.numbers).into()
+    }
+
+    // size reduced numbers, pairs nesting at most 4 deep
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        fn pair(rng: &mut Rng, depth: usize) -> String {
+            let element = |rng: &mut Rng| {
+                if depth < 4 && rng.one_in(2) {
+                    pair(rng, depth + 1)
+                } else {
+                    rng.below(10).to_string()
+                }
+            };
+            let left = element(rng);
+            let right = element(rng);
+            format!("[{},{}]", left, right)
+        }
+        (0..size.max(1)).map(|_| pair(rng, 1) + "\n").collect()
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("FishNumber sum magnitude: {}", answer),
+            Part::Two => format!("FishNumber single addition max magnitude: {}", answer),
+        }
+    }
+}
+
+pub fn display_additions(input: &str, part: Part) {
+    print_answer::<Homework>(input, part)
 }
 
 #[cfg(test)]
@@ -364,16 +440,7 @@ mod tests {
             );
         }
 
-        let input = "[[[0,[4,5]],[0,0]],[[[4,5],[2,6]],[9,5]]]
-[7,[[[3,7],[4,3]],[[6,3],[8,8]]]]
-[[2,[[0,8],[3,4]]],[[[6,7],1],[7,[1,6]]]]
-[[[[2,4],7],[6,[0,5]]],[[[6,8],[2,8]],[[2,1],[4,5]]]]
-[7,[5,[[3,8],[1,4]]]]
-[[2,[2,2]],[8,[8,1]]]
-[2,9]
-[1,[[[9,3],9],[[9,0],[0,7]]]]
-[[[5,[7,4]],7],1]
-[[[[4,2],2],6],[8,7]]";
+        let input = EXAMPLES[0].input;
 
         let sum = input
             .lines()
@@ -403,16 +470,7 @@ mod tests {
             assert_eq!(*m, FishNumber::try_read(fnb).unwrap().magnitude());
         }
 
-        let input = "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
-[[[5,[2,8]],4],[5,[[9,9],0]]]
-[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
-[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
-[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]
-[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]
-[[[[5,4],[7,7]],8],[[8,3],8]]
-[[9,3],[[9,9],[6,[4,9]]]]
-[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
-[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]";
+        let input = EXAMPLES[1].input;
         let numbers: Vec<_> = input
             .lines()
             .filter_map(|l| FishNumber::try_read(l).ok())
@@ -8,12 +8,12 @@ impl FromStr for ThreeDPoint {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some((x, y, z)) = s
-            .split(',')
-            .filter_map(|v| v.parse::<isize>().ok())
-            .collect_tuple()
-        {
-            return Ok(Self { x, y, z });
+        if let Some((x, y, z)) = s.trim().split(',').collect_tuple() {
+            return Ok(Self {
+                x: x.parse()?,
+                y: y.parse()?,
+                z: z.parse()?,
+            });
         }
         Err(anyhow!("could not read 3D point from '{}'", s))
     }
@@ -66,8 +66,14 @@ enum Direction {
     RZ,
 }
 
-use anyhow::{anyhow, Error};
+use crate::parse::{parse_numbered_lines, ParseError};
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver};
+use crate::trace::{debug, trace};
+use anyhow::{anyhow, Error, Result};
 use itertools::Itertools;
+use std::cell::OnceCell;
 use std::collections::HashSet;
 use std::str::FromStr;
 use Direction::{RX, RY, RZ, X, Y, Z};
@@ -197,32 +203,34 @@ impl Scanner {
     }
 }
 
-fn read_scanners(input: &str) -> Vec<Scanner> {
-    let lines = input.lines();
-    let delimiters = lines.clone().enumerate().filter_map(|(i, l)| {
-        if l.starts_with("--- scanner ") {
-            Some(i)
-        } else {
-            None
-        }
-    });
-
-    delimiters
-        .map(|i| {
-            let mut data = lines.clone();
-            // this will  fast forward data to the right position
-            let _scanner = data.nth(i).unwrap();
-            Scanner {
-                points: data
-                    .take_while(|l| !l.starts_with("--- scanner "))
-                    .filter_map(|l| l.parse::<ThreeDPoint>().ok())
-                    .collect(),
-            }
+fn read_scanners(input: &str) -> Result<Vec<Scanner>> {
+    let lines: Vec<(usize, &str)> = input.lines().enumerate().collect();
+    if let Some((i, l)) = lines
+        .iter()
+        .find(|(_, l)| !l.trim().is_empty())
+        .filter(|(_, l)| !l.starts_with("--- scanner "))
+    {
+        return Err(ParseError::new(i + 1, format!("'{}' : expected a scanner header", l)).into());
+    }
+    if lines.iter().all(|(_, l)| l.trim().is_empty()) {
+        return Err(anyhow!("no scanner"));
+    }
+
+    lines
+        .split(|(_, l)| l.starts_with("--- scanner "))
+        .skip(1)
+        .map(|data| {
+            Ok(Scanner {
+                points: parse_numbered_lines(data.iter().copied(), |l| l.parse::<ThreeDPoint>())?,
+            })
         })
         .collect()
 }
 
-fn tune_scanners(scanners: &[Scanner]) -> (Vec<Scanner>, Vec<ThreeDPoint>) {
+// scanners placed in the first scanner referential, and their coordinates
+type Tuning = (Vec<Scanner>, Vec<ThreeDPoint>);
+
+fn tune_scanners(scanners: &[Scanner]) -> Result<Tuning> {
     let mut converted_idx = vec![0];
     let mut converted: Vec<Scanner> = vec![Scanner {
         points: scanners[0].points.clone(),
@@ -234,7 +242,7 @@ fn tune_scanners(scanners: &[Scanner]) -> (Vec<Scanner>, Vec<ThreeDPoint>) {
         let checked_against = converted.len();
         for (j, scanner) in scanners.iter().enumerate() {
             if !converted_idx.contains(&j) {
-                // eprint!(".");
+                trace!("matching scanner {}", j);
                 if let Some((sd, trans)) = converted.iter().enumerate().find_map(|(u, conv)| {
                     if u >= already_checked_against {
                         conv.get_matching_scandir(scanner)
@@ -242,7 +250,7 @@ fn tune_scanners(scanners: &[Scanner]) -> (Vec<Scanner>, Vec<ThreeDPoint>) {
                         None
                     }
                 }) {
-                    // eprintln!("#{:?}", sd);
+                    debug!("scanner {} found at {:?}, facing {:?}", j, trans, sd);
                     converted_idx.push(j);
                     scanners_coordinates.push(ThreeDPoint { x: 0, y: 0, z: 0 } + trans);
                     converted.push(Scanner {
@@ -258,14 +266,14 @@ fn tune_scanners(scanners: &[Scanner]) -> (Vec<Scanner>, Vec<ThreeDPoint>) {
         already_checked_against = checked_against;
     }
     if converted.len() < scanners.len() {
-        panic!(
-            "could not place {:?} scanners relative to others",
+        return Err(anyhow!(
+            "could not place scanners {:?} relative to others",
             (0..scanners.len())
                 .filter(|i| !converted_idx.contains(i))
                 .collect_vec()
-        );
+        ));
     }
-    (converted, scanners_coordinates)
+    Ok((converted, scanners_coordinates))
 }
 
 fn max_manhattan_distance(scan_coords: &[ThreeDPoint]) -> usize {
@@ -280,27 +288,36 @@ fn max_manhattan_distance(scan_coords: &[ThreeDPoint]) -> usize {
         .unwrap() as usize
 }
 
-pub fn display_breacons_and_scanners() {
-    let input = include_str!("../resources/day19_scanners.txt");
-    let scanners = read_scanners(input);
+pub struct BeaconMap {
+    scanners: Vec<Scanner>,
+    // both parts rely on the (costly) scanners tuning, done once by the first of them,
+    // or why some scanners could not be placed
+    tuned: OnceCell<Result<Tuning, String>>,
+}
 
-    let (scanners, coordinates) = tune_scanners(&scanners);
+impl BeaconMap {
+    fn tuned(&self) -> Result<&Tuning> {
+        self.tuned
+            .get_or_init(|| tune_scanners(&self.scanners).map_err(|e| e.to_string()))
+            .as_ref()
+            .map_err(|e| anyhow!("{}", e))
+    }
 
-    let beacons: HashSet<_> = scanners.iter().flat_map(|s| s.points.iter()).collect();
+    fn count_beacons(&self) -> Result<usize> {
+        let (scanners, _) = self.tuned()?;
+        let beacons: HashSet<_> = scanners.iter().flat_map(|s| s.points.iter()).collect();
+        Ok(beacons.len())
+    }
 
-    println!("there are {} different beacons", beacons.len());
-    println!(
-        "max manhattan distance between scanners is {}",
-        max_manhattan_distance(&coordinates)
-    );
+    fn farthest_scanners(&self) -> Result<usize> {
+        let (_, coordinates) = self.tuned()?;
+        Ok(max_manhattan_distance(coordinates))
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    fn get_input() -> &'static str {
-        "--- scanner 0 ---
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "scanners",
+    input: "--- scanner 0 ---
 404,-588,-901
 528,-643,409
 -838,591,734
@@ -435,14 +452,150 @@ mod tests {
 839,-516,451
 891,-625,532
 -652,-548,-490
-30,-46,-14"
+30,-46,-14",
+    part1: Some("79"),
+    part2: Some("3621"),
+}];
+
+impl Solver for BeaconMap {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            scanners: read_scanners(input)?,
+            tuned: OnceCell::new(),
+        })
+    }
+
+    fn part1(&self) -> Answer {
+        self.count_beacons().map_or(Answer::None, Answer::from)
+    }
+
+    fn part2(&self) -> Answer {
+        self.farthest_scanners().map_or(Answer::None, Answer::from)
     }
 
+    // scanners which cannot be placed are reported, instead of leaving the parts without answer
+    fn try_solve(&self, part: Part) -> Result<Answer> {
+        Ok(match part {
+            Part::One => self.count_beacons()?.into(),
+            Part::Two => self.farthest_scanners()?.into(),
+        })
+    }
+
+    // size scanners, each one sharing at least 12 beacons with an earlier one,
+    // and reporting them in its own random orientation
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        // the 24 orientations, as signed axis permutations keeping the handedness
+        let permutations = [
+            [0, 1, 2],
+            [1, 2, 0],
+            [2, 0, 1],
+            [0, 2, 1],
+            [2, 1, 0],
+            [1, 0, 2],
+        ];
+        let orientations: Vec<([usize; 3], [isize; 3])> = permutations
+            .iter()
+            .enumerate()
+            .flat_map(|(i, axes)| {
+                (0..8).filter_map(move |s| {
+                    let signs = [1 - 2 * (s & 1), 1 - (s & 2), 1 - (s & 4) / 2];
+                    let parity = if i < 3 { 1 } else { -1 };
+                    (parity * signs.iter().product::<isize>() == 1).then_some((*axes, signs))
+                })
+            })
+            .collect();
+
+        let around = |rng: &mut Rng, low: [isize; 3], high: [isize; 3]| ThreeDPoint {
+            x: rng.between(low[0], high[0]),
+            y: rng.between(low[1], high[1]),
+            z: rng.between(low[2], high[2]),
+        };
+        let coordinates = |p: &ThreeDPoint| [p.x, p.y, p.z];
+        let in_range = |scanner: &ThreeDPoint, beacon: &ThreeDPoint| {
+            let (s, b) = (coordinates(scanner), coordinates(beacon));
+            (0..3).all(|i| (b[i] - s[i]).abs() <= 1000)
+        };
+
+        let mut scanners: Vec<ThreeDPoint> = vec![];
+        let mut beacons: Vec<ThreeDPoint> = vec![];
+        for _ in 0..size.max(1) {
+            let mut own = 0;
+            let scanner = if scanners.is_empty() {
+                ThreeDPoint { x: 0, y: 0, z: 0 }
+            } else {
+                let earlier = coordinates(rng.pick(&scanners));
+                let scanner = around(rng, earlier.map(|c| c - 1100), earlier.map(|c| c + 1100));
+                let s = coordinates(&scanner);
+                let low: Vec<_> = (0..3).map(|i| s[i].max(earlier[i]) - 1000).collect();
+                let high: Vec<_> = (0..3).map(|i| s[i].min(earlier[i]) + 1000).collect();
+                while own < 12 {
+                    let beacon = around(rng, [low[0], low[1], low[2]], [high[0], high[1], high[2]]);
+                    if !beacons.contains(&beacon) {
+                        beacons.push(beacon);
+                        own += 1;
+                    }
+                }
+                scanner
+            };
+            let s = coordinates(&scanner);
+            for _ in 0..rng.between(5, 15) {
+                beacons.push(around(rng, s.map(|c| c - 1000), s.map(|c| c + 1000)));
+            }
+            scanners.push(scanner);
+        }
+
+        let mut input = String::new();
+        for (i, scanner) in scanners.iter().enumerate() {
+            let (axes, signs) = *rng.pick(&orientations);
+            let mut seen: Vec<ThreeDPoint> = beacons
+                .iter()
+                .filter(|b| in_range(scanner, b))
+                .copied()
+                .unique()
+                .collect();
+            rng.shuffle(&mut seen);
+            if i > 0 {
+                input.push('\n');
+            }
+            input += &format!("--- scanner {} ---\n", i);
+            for beacon in seen {
+                let relative = coordinates(&beacon)
+                    .iter()
+                    .zip(coordinates(scanner))
+                    .map(|(b, s)| b - s)
+                    .collect::<Vec<_>>();
+                let reported: Vec<_> = (0..3).map(|i| signs[i] * relative[axes[i]]).collect();
+                input += &format!("{},{},{}\n", reported[0], reported[1], reported[2]);
+            }
+        }
+        input
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("there are {} different beacons", answer),
+            Part::Two => format!("max manhattan distance between scanners is {}", answer),
+        }
+    }
+}
+
+pub fn display_breacons_and_scanners(input: &str, part: Part) {
+    print_answer::<BeaconMap>(input, part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn scanners_can_be_decoded() {
-        let scanners = read_scanners(get_input());
+        let scanners = read_scanners(EXAMPLES[0].input).unwrap();
         assert_eq!(5, scanners.len());
-        assert_eq!(26, scanners[4].points.len())
+        assert_eq!(26, scanners[4].points.len());
+
+        let err = read_scanners("--- scanner 0 ---\n1,2,3\n4,5\n").unwrap_err();
+        assert_eq!(3, err.downcast_ref::<ParseError>().unwrap().line);
+        assert_eq!("no scanner", read_scanners("\n").unwrap_err().to_string());
     }
 
     #[test]
@@ -457,14 +610,34 @@ mod tests {
 
     #[test]
     fn scanners_can_be_positioned_maybe() {
-        let scanners = read_scanners(get_input());
+        let scanners = read_scanners(EXAMPLES[0].input).unwrap();
         assert!(scanners[0].get_matching_scandir(&scanners[1]).is_some());
         assert!(scanners[0].get_matching_scandir(&scanners[2]).is_none());
 
-        let (scanners, coords) = tune_scanners(&read_scanners(get_input()));
+        let (scanners, coords) = tune_scanners(&read_scanners(EXAMPLES[0].input).unwrap()).unwrap();
         let beacons: HashSet<_> = scanners.iter().flat_map(|s| s.points.iter()).collect();
 
         assert_eq!(79, beacons.len());
         assert_eq!(3621, max_manhattan_distance(&coords));
     }
+
+    #[test]
+    fn scanners_are_tuned_by_the_parts() {
+        let map = BeaconMap::parse(EXAMPLES[0].input).unwrap();
+        assert!(map.tuned.get().is_none());
+
+        assert_eq!(Answer::Number(3621), map.part2());
+        assert_eq!(Answer::Number(79), map.part1());
+    }
+
+    #[test]
+    fn unrelated_scanners_are_reported() {
+        let input = "--- scanner 0 ---\n1,2,3\n\n--- scanner 1 ---\n4,5,6\n";
+        let map = BeaconMap::parse(input).unwrap();
+        assert_eq!(
+            "could not place scanners [1] relative to others",
+            map.try_solve(Part::One).unwrap_err().to_string()
+        );
+        assert_eq!(Answer::None, map.part2());
+    }
 }
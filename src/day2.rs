@@ -1,19 +1,68 @@
-use crate::day2::Movement::{Down, Forward, Up};
-use itertools::Itertools;
+use crate::day2::Movement::{Back, Down, Forward, Goto, Up};
+use crate::export::{Color, BLACK, WHITE};
+use crate::grid::Grid;
+use crate::parse::ParseError;
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver};
+use anyhow::{anyhow, Result};
+use std::fmt::{self, Display};
+use std::iter::Peekable;
+use std::vec::IntoIter;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-struct Position {
-    horz: usize,
-    depth: usize,
-    aim: usize,
+/// signed, as nothing keeps a plan from going up above the surface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Position {
+    pub horz: i64,
+    pub depth: i64,
+    pub aim: i64,
 }
 
-#[derive(Debug)]
-enum Movement {
-    Forward(usize),
-    Down(usize),
-    Up(usize),
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Movement {
+    Forward(i64),
+    Back(i64),
+    Down(i64),
+    Up(i64),
+    /// absolute horizontal position and depth, the aim being kept
+    Goto(i64, i64),
 }
+
+impl Display for Movement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Forward(val) => write!(f, "forward {}", val),
+            Back(val) => write!(f, "back {}", val),
+            Down(val) => write!(f, "down {}", val),
+            Up(val) => write!(f, "up {}", val),
+            Goto(horz, depth) => write!(f, "goto {} {}", horz, depth),
+        }
+    }
+}
+
+/// a plan is a list of statements, repeat blocks nesting their own
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Statement {
+    Move(Movement),
+    Repeat(usize, Vec<Statement>),
+}
+
+/// plain movements change the depth, aimed ones the aim
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpretation {
+    Plain,
+    Aimed,
+}
+
+impl Display for Interpretation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Interpretation::Plain => "plain",
+            Interpretation::Aimed => "aimed",
+        })
+    }
+}
+
 impl Position {
     pub fn new() -> Position {
         Position {
@@ -22,71 +71,388 @@ impl Position {
             aim: 0,
         }
     }
-    pub fn apply_mvt(&mut self, mvt: Movement) {
-        match mvt {
-            Forward(val) => self.horz += val,
-            Down(val) => self.depth += val,
-            Up(val) => self.depth -= val,
+    // none when a coordinate goes out of range
+    fn moved(&self, mvt: Movement, interpretation: Interpretation) -> Option<Position> {
+        let mut next = *self;
+        match (mvt, interpretation) {
+            (Goto(horz, depth), _) => (next.horz, next.depth) = (horz, depth),
+            (Forward(val), Interpretation::Plain) => next.horz = self.horz.checked_add(val)?,
+            (Back(val), Interpretation::Plain) => next.horz = self.horz.checked_sub(val)?,
+            (Down(val), Interpretation::Plain) => next.depth = self.depth.checked_add(val)?,
+            (Up(val), Interpretation::Plain) => next.depth = self.depth.checked_sub(val)?,
+            (Forward(val), Interpretation::Aimed) => {
+                next.horz = self.horz.checked_add(val)?;
+                next.depth = self.depth.checked_add(self.aim.checked_mul(val)?)?;
+            }
+            (Back(val), Interpretation::Aimed) => {
+                next.horz = self.horz.checked_sub(val)?;
+                next.depth = self.depth.checked_sub(self.aim.checked_mul(val)?)?;
+            }
+            (Down(val), Interpretation::Aimed) => next.aim = self.aim.checked_add(val)?,
+            (Up(val), Interpretation::Aimed) => next.aim = self.aim.checked_sub(val)?,
+        }
+        Some(next)
+    }
+
+    /// fails, leaving the position as it was, when a coordinate would overflow
+    pub fn apply(&mut self, mvt: Movement, interpretation: Interpretation) -> Result<()> {
+        *self = self.moved(mvt, interpretation).ok_or_else(|| {
+            anyhow!(
+                "'{}' takes the {} position {:?} out of range",
+                mvt,
+                interpretation,
+                self
+            )
+        })?;
+        Ok(())
+    }
+
+    pub fn apply_mvt(&mut self, mvt: Movement) -> Result<()> {
+        self.apply(mvt, Interpretation::Plain)
+    }
+
+    pub fn apply_aimed_mvt(&mut self, mvt: Movement) -> Result<()> {
+        self.apply(mvt, Interpretation::Aimed)
+    }
+
+    // negative products are answered as text
+    fn product(&self) -> Answer {
+        let product = self.horz as i128 * self.depth as i128;
+        match usize::try_from(product) {
+            Ok(product) => product.into(),
+            Err(_) => Answer::Text(product.to_string()),
         }
     }
+}
+
+// word of a plan and where it starts, braces being words of their own
+struct Token<'a> {
+    text: &'a str,
+    line: usize,
+    column: usize,
+}
+
+impl Token<'_> {
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::at(self.line, self.column, message)
+    }
+}
 
-    pub fn apply_aimed_mvt(&mut self, mvt: Movement) {
-        match mvt {
-            Forward(val) => {
-                self.horz += val;
-                self.depth += self.aim * val
+// comments run from # to the end of the line
+fn tokenize(plan: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    for (i, line) in plan.lines().enumerate() {
+        let code = line.split('#').next().unwrap_or_default();
+        // offset and column of the word being read
+        let mut word: Option<(usize, usize)> = None;
+        let mut end_word = |word: &mut Option<(usize, usize)>, end: usize| {
+            if let Some((start, column)) = word.take() {
+                tokens.push(Token {
+                    text: &code[start..end],
+                    line: i + 1,
+                    column: column + 1,
+                });
+            }
+        };
+        for (column, (offset, c)) in code.char_indices().enumerate() {
+            if c.is_whitespace() {
+                end_word(&mut word, offset);
+            } else if c == '{' || c == '}' {
+                end_word(&mut word, offset);
+                word = Some((offset, column));
+                end_word(&mut word, offset + 1);
+            } else if word.is_none() {
+                word = Some((offset, column));
             }
-            Down(val) => self.aim += val,
-            Up(val) => self.aim -= val,
         }
+        end_word(&mut word, code.len());
     }
+    tokens
 }
 
-fn parse_movement(line: &str) -> Option<Movement> {
-    let instructions: Option<(&str, &str)> = line.split_whitespace().collect_tuple();
-    instructions
-        .map(|(direct, val)| {
-            val.parse::<usize>()
-                .ok()
-                .map(|val| match direct {
-                    "forward" => Some(Forward(val)),
-                    "down" => Some(Down(val)),
-                    "up" => Some(Up(val)),
-                    _ => None,
-                })
-                .flatten()
-        })
-        .flatten()
+/// most movements a plan may expand to, so that running it or recording its trajectory stays bounded ;
+/// each run of an empty repeat block counts as one movement
+pub const MAX_MOVEMENTS: usize = 1_000_000;
+/// most repeat blocks nested in each other
+pub const MAX_NESTING: usize = 32;
+
+struct PlanParser<'a> {
+    tokens: Peekable<IntoIter<Token<'a>>>,
+    // repeat blocks being parsed
+    nesting: usize,
 }
 
-fn apply_plan(init_pos: &Position, plan: &str) -> Position {
-    let movements: Vec<_> = plan.lines().filter_map(parse_movement).collect();
-    let mut target = *init_pos;
-    for mvt in movements {
-        target.apply_mvt(mvt);
+impl PlanParser<'_> {
+    fn number(&mut self, command: &Token) -> Result<i64, ParseError> {
+        let token = self
+            .tokens
+            .next()
+            .ok_or_else(|| command.error(format!("expecting a number after '{}'", command.text)))?;
+        token
+            .text
+            .parse()
+            .map_err(|_| token.error(format!("'{}' is not a number", token.text)))
     }
-    target
+
+    fn distance(&mut self, command: &Token) -> Result<i64, ParseError> {
+        let distance = self.number(command)?;
+        if distance < 0 {
+            return Err(command.error(format!(
+                "'{} {}' : negative distance",
+                command.text, distance
+            )));
+        }
+        Ok(distance)
+    }
+
+    // statements until the closing brace of the block opened by repeat, or the end of the plan,
+    // with the number of movements they expand to, empty blocks costing one for each run
+    fn statements(
+        &mut self,
+        repeat: Option<&Token>,
+    ) -> Result<(Vec<Statement>, usize), ParseError> {
+        let mut statements = vec![];
+        let mut movements = 0;
+        let too_many = |token: &Token| {
+            token.error(format!(
+                "the plan expands to more than {} movements",
+                MAX_MOVEMENTS
+            ))
+        };
+        while let Some(token) = self.tokens.next() {
+            let movement = match token.text {
+                "}" if repeat.is_some() => return Ok((statements, movements)),
+                "forward" => Forward(self.distance(&token)?),
+                "back" => Back(self.distance(&token)?),
+                "down" => Down(self.distance(&token)?),
+                "up" => Up(self.distance(&token)?),
+                "goto" => Goto(self.number(&token)?, self.number(&token)?),
+                "repeat" => {
+                    let times = self.distance(&token)? as usize;
+                    if self.tokens.next_if(|brace| brace.text == "{").is_none() {
+                        return Err(token.error("expecting '{' after the repeat count"));
+                    }
+                    if self.nesting == MAX_NESTING {
+                        return Err(token.error(format!(
+                            "repeat blocks are nested more than {} deep",
+                            MAX_NESTING
+                        )));
+                    }
+                    self.nesting += 1;
+                    let (block, block_movements) = self.statements(Some(&token))?;
+                    self.nesting -= 1;
+                    movements = block_movements
+                        .max(1)
+                        .checked_mul(times)
+                        .and_then(|m| m.checked_add(movements))
+                        .filter(|m| *m <= MAX_MOVEMENTS)
+                        .ok_or_else(|| too_many(&token))?;
+                    statements.push(Statement::Repeat(times, block));
+                    continue;
+                }
+                unknown => return Err(token.error(format!("unknown command '{}'", unknown))),
+            };
+            movements += 1;
+            if movements > MAX_MOVEMENTS {
+                return Err(too_many(&token));
+            }
+            statements.push(Statement::Move(movement));
+        }
+        match repeat {
+            Some(repeat) => Err(repeat.error("repeat block is never closed")),
+            None => Ok((statements, movements)),
+        }
+    }
+}
+
+/// movements such as 'forward 5', 'back 2' or 'goto 10 4', and 'repeat 3 { ... }' blocks,
+/// # starting a comment ; an error gives the line and column of the faulty word
+pub fn parse_plan(plan: &str) -> Result<Vec<Statement>> {
+    let mut parser = PlanParser {
+        tokens: tokenize(plan).into_iter().peekable(),
+        nesting: 0,
+    };
+    Ok(parser.statements(None)?.0)
+}
+
+// movements of the plan, in execution order
+fn for_each_movement(
+    plan: &[Statement],
+    apply: &mut impl FnMut(Movement) -> Result<()>,
+) -> Result<()> {
+    for statement in plan {
+        match statement {
+            Statement::Move(mvt) => apply(*mvt)?,
+            Statement::Repeat(times, block) => {
+                for _ in 0..*times {
+                    for_each_movement(block, apply)?;
+                }
+            }
+        }
+    }
+    Ok(())
 }
 
-fn apply_aimed_plan(init_pos: &Position, plan: &str) -> Position {
-    let movements: Vec<_> = plan.lines().filter_map(parse_movement).collect();
+fn apply_plan(init_pos: &Position, plan: &[Statement]) -> Result<Position> {
     let mut target = *init_pos;
-    for mvt in movements {
-        target.apply_aimed_mvt(mvt);
+    for_each_movement(plan, &mut |mvt| target.apply_mvt(mvt))?;
+    Ok(target)
+}
+
+fn apply_aimed_plan(init_pos: &Position, plan: &[Statement]) -> Result<Position> {
+    let mut target = *init_pos;
+    for_each_movement(plan, &mut |mvt| target.apply_aimed_mvt(mvt))?;
+    Ok(target)
+}
+
+/// every position the plan goes through, starting with the initial one
+pub fn trajectory(
+    init_pos: &Position,
+    plan: &[Statement],
+    interpretation: Interpretation,
+) -> Result<Vec<Position>> {
+    let mut positions = vec![*init_pos];
+    let mut current = *init_pos;
+    for_each_movement(plan, &mut |mvt| {
+        current.apply(mvt, interpretation)?;
+        positions.push(current);
+        Ok(())
+    })?;
+    Ok(positions)
+}
+
+const PLAIN_COLOR: Color = [60, 140, 255];
+const AIMED_COLOR: Color = [255, 150, 30];
+
+/// both trajectories drawn on the same canvas of at most max_width x max_height cells,
+/// the horizontal position going right and the depth going down
+fn plot_trajectories(
+    plain: &[Position],
+    aimed: &[Position],
+    max_width: usize,
+    max_height: usize,
+) -> Grid<Color> {
+    let all = || plain.iter().chain(aimed);
+    let bounds = |coord: fn(&Position) -> i64| {
+        let min = all().map(coord).min().unwrap_or(0) as f64;
+        let max = all().map(coord).max().unwrap_or(0) as f64;
+        (min, max)
+    };
+    let (horz, depth) = (bounds(|p| p.horz), bounds(|p| p.depth));
+    let width = (horz.1 - horz.0 + 1.0).min(max_width as f64) as usize;
+    let height = (depth.1 - depth.0 + 1.0).min(max_height as f64) as usize;
+    let scaled = |value: f64, (min, max): (f64, f64), cells: usize| {
+        ((value - min) / (max - min).max(1.0) * (cells - 1) as f64).round()
+    };
+
+    let mut picture = Grid::new(height, width, BLACK);
+    for (positions, color) in [(plain, PLAIN_COLOR), (aimed, AIMED_COLOR)] {
+        let cells: Vec<(f64, f64)> = positions
+            .iter()
+            .map(|p| {
+                (
+                    scaled(p.depth as f64, depth, height),
+                    scaled(p.horz as f64, horz, width),
+                )
+            })
+            .collect();
+        // each segment drawn cell by cell
+        for pair in cells.windows(2) {
+            let ((row1, col1), (row2, col2)) = (pair[0], pair[1]);
+            let steps = (row2 - row1).abs().max((col2 - col1).abs()).max(1.0) as usize;
+            for step in 0..=steps {
+                let ratio = step as f64 / steps as f64;
+                let cell = (
+                    (row1 + (row2 - row1) * ratio).round() as usize,
+                    (col1 + (col2 - col1) * ratio).round() as usize,
+                );
+                picture[cell] = match picture[cell] {
+                    BLACK => color,
+                    current if current == color => color,
+                    _ => WHITE,
+                };
+            }
+        }
     }
-    target
+    picture
 }
 
-pub fn print_position() {
-    let plan = include_str!("../resources/day2_movements.txt");
-    let origin = Position::new();
-    let target = apply_plan(&origin, plan);
-    println!("target_position : {:?}", target);
-    println!("product : {:?}", target.horz * target.depth);
+pub struct Course {
+    plan: Vec<Statement>,
+}
 
-    let aimed_target = apply_aimed_plan(&origin, plan);
-    println!("target_position : {:?}", aimed_target);
-    println!("product : {:?}", aimed_target.horz * aimed_target.depth);
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "planned course",
+    input: "forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2",
+    part1: Some("150"),
+    part2: Some("900"),
+}];
+
+impl Solver for Course {
+    // a plan taking the submarine out of range is rejected before being solved
+    fn parse(plan: &str) -> Result<Self> {
+        let plan = parse_plan(plan)?;
+        apply_plan(&Position::new(), &plan)?;
+        apply_aimed_plan(&Position::new(), &plan)?;
+        Ok(Self { plan })
+    }
+
+    fn part1(&self) -> Answer {
+        apply_plan(&Position::new(), &self.plan)
+            .expect("checked by parse")
+            .product()
+    }
+
+    fn part2(&self) -> Answer {
+        apply_aimed_plan(&Position::new(), &self.plan)
+            .expect("checked by parse")
+            .product()
+    }
+
+    // plain trajectory in blue, aimed one in orange, their crossings in white
+    fn picture(&self) -> Option<Grid<Color>> {
+        let plain = trajectory(&Position::new(), &self.plan, Interpretation::Plain).ok()?;
+        let aimed = trajectory(&Position::new(), &self.plan, Interpretation::Aimed).ok()?;
+        Some(plot_trajectories(&plain, &aimed, 400, 200))
+    }
+
+    // the submarine never goes up above the surface
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut depth = 0;
+        (0..size.min(MAX_MOVEMENTS))
+            .map(|_| {
+                let amount = rng.between(1, 9);
+                let direction = match rng.below(3) {
+                    0 => "forward",
+                    1 if depth >= amount => {
+                        depth -= amount;
+                        "up"
+                    }
+                    _ => {
+                        depth += amount;
+                        "down"
+                    }
+                };
+                format!("{} {}\n", direction, amount)
+            })
+            .collect()
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("product : {}", answer),
+            Part::Two => format!("aimed product : {}", answer),
+        }
+    }
+}
+
+pub fn print_position(plan: &str, part: Part) {
+    print_answer::<Course>(plan, part)
 }
 
 #[cfg(test)]
@@ -95,24 +461,223 @@ mod tests {
     #[test]
     fn aoc_examples_work() {
         let init_pos = Position::new();
-        let plan = "forward 5
-down 5
-forward 8
-up 3
-down 8
-forward 2
-        ";
+        let plan = parse_plan(EXAMPLES[0].input).unwrap();
         assert_eq!(
             Position {
                 horz: 15,
                 depth: 10,
                 aim: 0
             },
-            apply_plan(&init_pos, plan)
+            apply_plan(&init_pos, &plan).unwrap()
         );
 
-        let aimed_pos = apply_aimed_plan(&init_pos, plan);
+        let aimed_pos = apply_aimed_plan(&init_pos, &plan).unwrap();
         assert_eq!(15, aimed_pos.horz);
         assert_eq!(60, aimed_pos.depth);
+
+        let err = parse_plan("forward 5\nbackward 2").err().unwrap();
+        assert_eq!(
+            "line 2, column 1 : unknown command 'backward'",
+            err.to_string()
+        );
+        assert!(parse_plan("forward -5").is_err());
+    }
+
+    #[test]
+    fn plans_can_repeat_blocks() {
+        let plan = parse_plan(
+            "# the example, twice
+repeat 2 {
+    forward 5 down 5   # side by side
+    repeat 1 {forward 8}
+    up 3
+    down 8 forward 2
+}
+back 30
+goto 0 1",
+        )
+        .unwrap();
+        assert_eq!(
+            Statement::Repeat(1, vec![Statement::Move(Forward(8))]),
+            match &plan[0] {
+                Statement::Repeat(2, block) => block[2].clone(),
+                other => panic!("unexpected {:?}", other),
+            }
+        );
+        assert_eq!(3, plan.len());
+
+        let plain = trajectory(&Position::new(), &plan, Interpretation::Plain).unwrap();
+        assert_eq!(15, plain.len());
+        assert_eq!((30, 20), (plain[12].horz, plain[12].depth));
+        assert_eq!((0, 20), (plain[13].horz, plain[13].depth));
+
+        let aimed = trajectory(&Position::new(), &plan, Interpretation::Aimed).unwrap();
+        assert_eq!(
+            (30, 270, 20),
+            (aimed[12].horz, aimed[12].depth, aimed[12].aim)
+        );
+        assert_eq!(
+            (0, -330, 20),
+            (aimed[13].horz, aimed[13].depth, aimed[13].aim)
+        );
+        assert_eq!(
+            Position {
+                horz: 0,
+                depth: 1,
+                aim: 20
+            },
+            apply_aimed_plan(&Position::new(), &plan).unwrap()
+        );
+    }
+
+    #[test]
+    fn plan_errors_are_located() {
+        for (plan, message) in [
+            (
+                "repeat 2 {\n  forward 1\n",
+                "line 1, column 1 : repeat block is never closed",
+            ),
+            (
+                "repeat 2 forward 1",
+                "line 1, column 1 : expecting '{' after the repeat count",
+            ),
+            ("forward 1\n}", "line 2, column 1 : unknown command '}'"),
+            (
+                "down 1 # up\n  forward x",
+                "line 2, column 11 : 'x' is not a number",
+            ),
+            (
+                "goto 4",
+                "line 1, column 1 : expecting a number after 'goto'",
+            ),
+            ("up -2", "line 1, column 1 : 'up -2' : negative distance"),
+        ] {
+            assert_eq!(message, parse_plan(plan).err().unwrap().to_string());
+        }
+        assert!(parse_plan("goto -4 -2").is_ok());
+
+        let nested = "repeat 1000 {\n  repeat 1000 { forward 1 }\n}";
+        assert!(parse_plan(nested).is_ok());
+        assert_eq!(
+            "line 4, column 1 : the plan expands to more than 1000000 movements",
+            parse_plan(&format!("{}\nforward 1", nested))
+                .err()
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!(
+            "line 1, column 1 : the plan expands to more than 1000000 movements",
+            parse_plan("repeat 100000 {\n  repeat 100000 { forward 1 }\n}")
+                .err()
+                .unwrap()
+                .to_string()
+        );
+        assert!(parse_plan("repeat 18446744073709551615 { repeat 2 { up 1 } }").is_err());
+        assert!(parse_plan("repeat 1000 { repeat 1000 { } }").is_ok());
+        for (empty, column) in [
+            ("repeat 9223372036854775807 { }", 1),
+            (
+                "repeat 1000000 { repeat 1000000 { repeat 1000000 { } } }",
+                18,
+            ),
+        ] {
+            assert_eq!(
+                format!(
+                    "line 1, column {} : the plan expands to more than 1000000 movements",
+                    column
+                ),
+                parse_plan(empty).err().unwrap().to_string()
+            );
+        }
+
+        let deep = format!(
+            "{}{}",
+            "repeat 1 {".repeat(MAX_NESTING),
+            "}".repeat(MAX_NESTING)
+        );
+        assert!(parse_plan(&deep).is_ok());
+        assert_eq!(
+            "line 1, column 321 : repeat blocks are nested more than 32 deep",
+            parse_plan(&format!("repeat 1 {{{}}}", deep))
+                .err()
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn plans_can_go_above_the_surface() {
+        let course = Course::parse("forward 2\nup 3\nforward 1").unwrap();
+        assert_eq!(Answer::Text("-9".to_string()), course.part1());
+        assert_eq!(Answer::Text("-9".to_string()), course.part2());
+    }
+
+    #[test]
+    fn positions_cannot_overflow() {
+        for (plan, message) in [
+            (
+                "forward 9223372036854775807\nforward 1",
+                "'forward 1' takes the plain position Position { horz: 9223372036854775807, depth: 0, aim: 0 } out of range",
+            ),
+            (
+                "forward 4000000000\ndown 4000000000\nforward 4000000000",
+                "'forward 4000000000' takes the aimed position Position { horz: 4000000000, depth: 0, aim: 4000000000 } out of range",
+            ),
+        ] {
+            assert_eq!(message, Course::parse(plan).err().unwrap().to_string());
+        }
+
+        let course = Course::parse("forward 4000000000\ndown 4000000000").unwrap();
+        assert_eq!(Answer::Number(16_000_000_000_000_000_000), course.part1());
+        let course =
+            Course::parse("forward 9223372036854775807\ndown 9223372036854775807").unwrap();
+        assert_eq!(
+            Answer::Text("85070591730234615847396907784232501249".to_string()),
+            course.part1()
+        );
+    }
+
+    #[test]
+    fn trajectories_can_be_plotted() {
+        let course = Course::parse(EXAMPLES[0].input).unwrap();
+        let picture = course.picture().unwrap();
+        // horz 0 to 15, depth 0 to 60
+        assert_eq!((61, 16), picture.dim());
+        assert_eq!(WHITE, picture[(0, 0)]);
+        assert_eq!(AIMED_COLOR, picture[(60, 15)]);
+        assert_eq!(PLAIN_COLOR, picture[(10, 15)]);
+
+        let wide = Course::parse("forward 100000\ndown 3").unwrap();
+        assert_eq!((4, 400), wide.picture().unwrap().dim());
+    }
+
+    #[test]
+    fn trajectories_can_be_recorded() {
+        let plan = parse_plan(EXAMPLES[0].input).unwrap();
+        for (interpretation, target) in [
+            (
+                Interpretation::Plain,
+                apply_plan(&Position::new(), &plan).unwrap(),
+            ),
+            (
+                Interpretation::Aimed,
+                apply_aimed_plan(&Position::new(), &plan).unwrap(),
+            ),
+        ] {
+            let positions = trajectory(&Position::new(), &plan, interpretation).unwrap();
+            assert_eq!(plan.len() + 1, positions.len());
+            assert_eq!(Position::new(), positions[0]);
+            assert_eq!(target, *positions.last().unwrap());
+        }
+
+        let aimed = trajectory(&Position::new(), &plan, Interpretation::Aimed).unwrap();
+        assert_eq!(
+            Position {
+                horz: 13,
+                depth: 40,
+                aim: 5
+            },
+            aimed[3]
+        );
     }
 }
@@ -1,64 +1,47 @@
-use anyhow::{anyhow, Error};
+use crate::export::{Color, BLACK, WHITE};
+use crate::grid::Grid;
+use crate::parse::ParseError;
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Frames, Solver};
+use anyhow::{anyhow, Error, Result};
+use colored::Colorize;
 use std::fmt::{Display, Formatter, Write};
-use std::str::{FromStr, Lines};
-
-fn str_to_bools(line: &str) -> Vec<bool> {
-    line.chars()
-        .filter_map(|c| match c {
-            '.' => Some(false),
-            '#' => Some(true),
-            _ => None,
-        })
-        .collect()
-}
+use std::str::FromStr;
 
+#[derive(Clone)]
 struct Image {
-    pixels: Vec<Vec<bool>>,
+    pixels: Grid<bool>,
 }
 impl Image {
     fn count_lit_pixels(&self) -> usize {
-        self.pixels
-            .iter()
-            .map(|col| col.iter().filter(|p| **p).count())
-            .sum()
+        self.pixels.values().filter(|p| **p).count()
     }
     fn dim(&self) -> (usize, usize) {
-        match self.pixels.len() {
-            0 => (0, 0),
-            _ => (self.pixels.len(), self.pixels[0].len()),
-        }
+        self.pixels.dim()
     }
-    fn read_from(lines: Lines<'_>) -> Self {
-        let pixels: Vec<Vec<bool>> = lines
-            .filter(|l| !l.trim().is_empty())
-            .map(str_to_bools)
-            .collect();
-        Self { pixels }
+    fn read_from<'a>(lines: impl IntoIterator<Item = (usize, &'a str)>) -> Result<Self> {
+        let pixels = Grid::parse_numbered(lines, |c| match c {
+            '.' => Some(false),
+            '#' => Some(true),
+            _ => None,
+        })?;
+        Ok(Self { pixels })
     }
     fn extract_centered(&self, edim: (usize, usize)) -> Self {
         let dim = self.dim();
         let offset = ((dim.0 - edim.0) / 2, (dim.1 - edim.1) / 2);
 
         Self {
-            pixels: self
-                .pixels
-                .iter()
-                .enumerate()
-                .filter(|(j, _)| *j >= offset.1 && *j < offset.1 + edim.1)
-                .map(|(_, line)| line[offset.0..edim.0 + offset.0].to_vec())
-                .collect(),
+            pixels: Grid::from_fn(edim.0, edim.1, |(row, col)| {
+                self.pixels[(row + offset.0, col + offset.1)]
+            }),
         }
     }
 }
 impl Display for Image {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for l in &self.pixels {
-            for b in l {
-                f.write_char(if *b { '#' } else { '.' })?;
-            }
-            f.write_char('\n')?;
-        }
-        Ok(())
+        self.pixels.map(|b| if *b { '#' } else { '.' }).fmt(f)
     }
 }
 
@@ -78,27 +61,36 @@ impl FromStr for Enhancer {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let data = str_to_bools(s);
+        let data = s
+            .trim()
+            .chars()
+            .enumerate()
+            .map(|(i, c)| match c {
+                '.' => Ok(false),
+                '#' => Ok(true),
+                _ => Err(ParseError::at(1, i + 1, format!("unexpected '{}'", c))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
         if data.len() == 512 {
             return Ok(Self { data });
         }
-        Err(anyhow!("extracted {} bits from '{}'", data.len(), s))
+        Err(ParseError::new(
+            1,
+            format!("{} bits instead of 512 in the enhancer", data.len()),
+        )
+        .into())
     }
 }
 
 impl Enhancer {
+    /// center is given as (column, row), pixels outside of the image are dark
     fn get_3x3_lit_pixels(img: &Image, center: (isize, isize)) -> Vec<bool> {
-        let dim = img.dim();
-        (0..3)
+        (-1..=1)
             .flat_map(|j| {
-                (0..3).map(move |i| {
-                    if (center.1 + j >= 1 && center.1 + j < (dim.1 + 1) as isize)
-                        && (center.0 + i >= 1 && center.0 + i < (dim.1 + 1) as isize)
-                    {
-                        img.pixels[(center.1 + j - 1) as usize][(center.0 + i - 1) as usize]
-                    } else {
-                        false
-                    }
+                (-1..=1).map(move |i| {
+                    img.pixels
+                        .checked((center.1 + j, center.0 + i))
+                        .is_some_and(|p| img.pixels[p])
                 })
             })
             .collect() // could provide a custom Iter instead
@@ -112,27 +104,18 @@ impl Enhancer {
 
     fn enhance(&self, img: &Image) -> Image {
         let dim = img.dim();
+        // In case all black zones turns to white and vice-versa each step
         Image {
-            pixels: (-10..=dim.0 as isize + 9) // In case all black zones turns to white and vice-versa each step
-                .map(|j| {
-                    (-10..=dim.1 as isize + 9)
-                        .map(|i| self.data[Self::get_index_from_3x3_lit_pixels(img, (i, j))])
-                        .collect()
-                })
-                .collect(),
+            pixels: Grid::from_fn(dim.0 + 20, dim.1 + 20, |(row, col)| {
+                let center = (col as isize - 10, row as isize - 10);
+                self.data[Self::get_index_from_3x3_lit_pixels(img, center)]
+            }),
         }
     }
 }
-pub fn display_enhanced_img() {
-    let input = include_str!("../resources/day20_enhancer.txt");
-
-    let mut lines = input.lines();
-
-    let enhancer: Enhancer = lines.next().unwrap().parse().unwrap();
-    let img = Image::read_from(lines);
-
-    let mut new_img = img;
-    for i in 1..=25 {
+fn enhance_in_pairs(enhancer: &Enhancer, img: &Image, double_passes: usize) -> Image {
+    let mut new_img = img.clone();
+    for _ in 0..double_passes {
         // dta[0]=1 and data[9]=0 :
         // * at first step, all 0 surrounded by 0 are set to 1
         // * and go back to 0 the next step (if they are fully surrounded by 1s)
@@ -141,14 +124,136 @@ pub fn display_enhanced_img() {
         new_img = enhancer
             .enhance(&enhancer.enhance(&new_img))
             .extract_centered((dim.0 + 8, dim.1 + 8));
-        println!(
+    }
+    new_img
+}
+
+fn count_lit_pixels_after(enhancer: &Enhancer, img: &Image, double_passes: usize) -> usize {
+    enhance_in_pairs(enhancer, img, double_passes).count_lit_pixels()
+}
+
+/// lit pixels stand out of the dark ones
+fn draw_image(img: &Image, step: usize) -> String {
+    let mut frame = format!("step {} : {} lit pixels\n", step, img.count_lit_pixels());
+    for row in img.pixels.rows() {
+        for lit in row {
+            if *lit {
+                write!(frame, "{}", "#".bold().white()).unwrap();
+            } else {
+                write!(frame, "{}", ".".dimmed()).unwrap();
+            }
+        }
+        frame.push('\n');
+    }
+    frame
+}
+
+pub struct TrenchMap {
+    enhancer: Enhancer,
+    img: Image,
+}
+
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "trench map",
+    input: "..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..##\
+#..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###\
+.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#.\
+.#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#.....\
+.#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#..\
+...####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.....\
+..##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#
+
+#..#.
+#....
+##..#
+..#..
+..###",
+    part1: Some("35"),
+    part2: Some("3351"),
+}];
+
+impl Solver for TrenchMap {
+    fn parse(input: &str) -> Result<Self> {
+        let mut lines = input.lines().enumerate();
+
+        let enhancer: Enhancer = lines
+            .next()
+            .ok_or_else(|| anyhow!("missing enhancer"))?
+            .1
+            .parse()?;
+        let img = Image::read_from(lines)?;
+        Ok(Self { enhancer, img })
+    }
+
+    fn part1(&self) -> Answer {
+        count_lit_pixels_after(&self.enhancer, &self.img, 1).into()
+    }
+
+    fn part2(&self) -> Answer {
+        count_lit_pixels_after(&self.enhancer, &self.img, 25).into()
+    }
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut enhancer: Vec<char> = (0..512)
+            .map(|_| if rng.one_in(2) { '#' } else { '.' })
+            .collect();
+        // an infinity lit at once must go dark again, or the answer would be infinite
+        if enhancer[0] == '#' {
+            enhancer[511] = '.';
+        }
+        let image = Grid::from_fn(size.max(1), size.max(1), |_| {
+            if rng.one_in(2) {
+                '#'
+            } else {
+                '.'
+            }
+        });
+        format!("{}\n\n{}", enhancer.iter().collect::<String>(), image)
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        let enhancements = match part {
+            Part::One => 2,
+            Part::Two => 50,
+        };
+        format!(
             "number of lit pixels after {} enhancements: {}",
-            2 * i,
-            new_img.count_lit_pixels()
-        );
+            enhancements, answer
+        )
+    }
+
+    // the image once enhanced 50 times
+    fn picture(&self) -> Option<Grid<Color>> {
+        let img = enhance_in_pairs(&self.enhancer, &self.img, 25);
+        Some(img.pixels.map(|lit| if *lit { WHITE } else { BLACK }))
+    }
+
+    // the 50 enhancements of the second part, each one growing the image by a pixel on each side ;
+    // the infinity may only be lit after odd ones, so that even ones can be cropped
+    fn frames(&self) -> Option<Frames<'_>> {
+        let mut img = self.img.clone();
+        let first = draw_image(&img, 0);
+        let steps = (1..=25).flat_map(move |double_pass| {
+            let dim = img.dim();
+            let half = self.enhancer.enhance(&img);
+            let odd = draw_image(
+                &half.extract_centered((dim.0 + 2, dim.1 + 2)),
+                2 * double_pass - 1,
+            );
+            img = self
+                .enhancer
+                .enhance(&half)
+                .extract_centered((dim.0 + 4, dim.1 + 4));
+            [odd, draw_image(&img, 2 * double_pass)]
+        });
+        Some(Box::new(std::iter::once(first).chain(steps)))
     }
 }
 
+pub fn display_enhanced_img(input: &str, part: Part) {
+    print_answer::<TrenchMap>(input, part)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -165,7 +270,7 @@ mod tests {
     #[test]
     fn pixels_can_be_extracted() {
         let img = Image {
-            pixels: vec![vec![true; 3], vec![false; 3], vec![true; 3]],
+            pixels: Grid::from_rows(vec![vec![true; 3], vec![false; 3], vec![true; 3]]).unwrap(),
         };
         println!("{}", &img);
 
@@ -190,29 +295,17 @@ mod tests {
 
     #[test]
     fn aoc_example_work() {
-        let input = "..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..##\
-#..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###\
-.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#.\
-.#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#.....\
-.#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#..\
-...####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.....\
-..##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#
-
-#..#.
-#....
-##..#
-..#..
-..###";
-        let mut lines = input.lines();
+        let input = EXAMPLES[0].input;
+        let mut lines = input.lines().enumerate();
         assert_eq!(7, lines.clone().count());
 
-        let enhancer: Enhancer = lines.next().unwrap().parse().unwrap();
-        let img = Image::read_from(lines);
+        let enhancer: Enhancer = lines.next().unwrap().1.parse().unwrap();
+        let img = Image::read_from(lines).unwrap();
 
         assert_eq!(34, Enhancer::get_index_from_3x3_lit_pixels(&img, (2, 2)));
-        assert_eq!(false, enhancer.data[0]);
-        assert_eq!(true, enhancer.data[34]);
-        assert_eq!(true, enhancer.data[50]);
+        assert!(!enhancer.data[0]);
+        assert!(enhancer.data[34]);
+        assert!(enhancer.data[50]);
         assert_eq!((5, 5), img.dim());
         println!("original\n{}", &img);
 
@@ -229,5 +322,13 @@ mod tests {
         }
         println!("after 50 steps\n{}", &img);
         assert_eq!(3351, img.count_lit_pixels());
+
+        colored::control::set_override(false);
+        let trench_map = TrenchMap::parse(input).unwrap();
+        let frames: Vec<_> = trench_map.frames().unwrap().collect();
+        assert_eq!(51, frames.len());
+        assert!(frames[0].starts_with("step 0 : 10 lit pixels\n#..#.\n"));
+        assert!(frames[2].starts_with("step 2 : 35 lit pixels\n"));
+        assert!(frames[50].starts_with("step 50 : 3351 lit pixels\n"));
     }
 }
@@ -1,5 +1,10 @@
-use anyhow::{anyhow, Error};
-use itertools::Itertools;
+use crate::parse::parse_lines;
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver, Variant};
+use crate::trace::debug;
+use anyhow::{anyhow, Error, Result};
+use itertools::{iproduct, Itertools};
 use std::collections::HashMap;
 use std::str::FromStr;
 
@@ -81,7 +86,7 @@ struct Universe {
 
 fn compute_results_for_dirac_dices(players: Vec<Player>) -> Vec<usize> {
     let players_count = players.len();
-    let mut win_count = vec![0usize, players_count];
+    let mut win_count = vec![0usize; players_count];
 
     // We're storing each play, with its number of occurrences (different dice throws lead to same sum)
     // and the winner idx if present
@@ -91,7 +96,10 @@ fn compute_results_for_dirac_dices(players: Vec<Player>) -> Vec<usize> {
         winner_idx: None,
     }];
     loop {
-        // eprintln!("open plays : {}", multiverse.iter().map(|((count,_),_)|*count).sum::<usize>());
+        debug!(
+            "open plays : {}",
+            multiverse.iter().map(|u| u.count).sum::<usize>()
+        );
 
         let step_result: Vec<_> = multiverse
             .iter()
@@ -156,17 +164,8 @@ fn compute_results_for_dirac_dices(players: Vec<Player>) -> Vec<usize> {
 
         let mut count_by_players: HashMap<Vec<Player>, usize> = HashMap::new();
 
-        // TODO : #[cfg(debug_assertions)]
-        // let winners = step_result
-        //     .iter()
-        //     .filter_map(|((count, _), winner)| winner.map(|_| *count))
-        //     .sum::<usize>();
-        // eprintln!("{} new winners", winners);
-        //
-        // let kept_count = step_result
-        //     .iter()
-        //     .filter(|((_, _), winner)| winner.is_none())
-        //     .count();
+        let mut winners = 0;
+        let mut kept_count = 0;
         for Universe {
             count,
             players,
@@ -175,7 +174,9 @@ fn compute_results_for_dirac_dices(players: Vec<Player>) -> Vec<usize> {
         {
             if let Some(i) = winner_idx {
                 win_count[i] += count;
+                winners += count;
             } else {
+                kept_count += 1;
                 let new_count = count_by_players.get(&players).unwrap_or(&0) + count;
                 count_by_players.insert(players, new_count);
             }
@@ -189,7 +190,12 @@ fn compute_results_for_dirac_dices(players: Vec<Player>) -> Vec<usize> {
                 winner_idx: None,
             })
             .collect();
-        // eprintln!("condensed {} entries, kept {}", kept_count-multiverse.len(), multiverse.len());
+        debug!("{} new winners", winners);
+        debug!(
+            "condensed {} entries, kept {}",
+            kept_count - multiverse.len(),
+            multiverse.len()
+        );
         if multiverse.is_empty() {
             break;
         }
@@ -197,32 +203,105 @@ fn compute_results_for_dirac_dices(players: Vec<Player>) -> Vec<usize> {
     win_count
 }
 
-pub fn display_dirac_dice_play() {
-    let input = "Player 1 starting position: 3
-Player 2 starting position: 5
-";
-    let players: Vec<_> = input
-        .lines()
-        .filter_map(|l| l.parse::<Player>().ok())
-        .collect();
-    let loosing_score_by_rolls = play_deterministic_dice(players.clone());
-
-    println!(
-        "deterministic dice : loosing score by rolls {}",
-        loosing_score_by_rolls
-    );
-
-    let dirac_wins = compute_results_for_dirac_dices(players);
-    println!("max number of win : {}", dirac_wins.iter().max().unwrap());
+/// reference for compute_results_for_dirac_dices, each turn splitting the universe
+/// for every one of the 27 rolls, the wins from a given state being remembered
+fn count_wins_roll_by_roll(players: &[Player]) -> Vec<usize> {
+    fn wins_from(
+        players: &[Player],
+        turn: usize,
+        known: &mut HashMap<(Vec<Player>, usize), Vec<usize>>,
+    ) -> Vec<usize> {
+        if let Some(wins) = known.get(&(players.to_vec(), turn)) {
+            return wins.clone();
+        }
+        let mut wins = vec![0; players.len()];
+        for (a, b, c) in iproduct!(1..=3, 1..=3, 1..=3) {
+            let mut next = players.to_vec();
+            if next[turn].advance(a + b + c) >= 21 {
+                wins[turn] += 1;
+            } else {
+                let next_wins = wins_from(&next, (turn + 1) % players.len(), known);
+                wins.iter_mut().zip(next_wins).for_each(|(w, n)| *w += n);
+            }
+        }
+        known.insert((players.to_vec(), turn), wins.clone());
+        wins
+    }
+    wins_from(players, 0, &mut HashMap::new())
+}
+
+pub struct DiracDice {
+    players: Vec<Player>,
 }
+
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "starting positions",
+    input: "Player 1 starting position: 4
+Player 2 starting position: 8",
+    part1: Some("739785"),
+    part2: Some("444356092776315"),
+}];
+
+impl Solver for DiracDice {
+    fn parse(input: &str) -> Result<Self> {
+        let players = parse_lines(input, |l| l.parse::<Player>())?;
+        if players.len() != 2 {
+            return Err(anyhow!("2 players expected, found {}", players.len()));
+        }
+        Ok(Self { players })
+    }
+
+    fn part1(&self) -> Answer {
+        play_deterministic_dice(self.players.clone()).into()
+    }
+
+    fn part2(&self) -> Answer {
+        let dirac_wins = compute_results_for_dirac_dices(self.players.clone());
+        (*dirac_wins.iter().max().unwrap()).into()
+    }
+
+    // the size does not matter, the board having a fixed size
+    fn generate(rng: &mut Rng, _size: usize) -> String {
+        (1..=2)
+            .map(|player| {
+                format!(
+                    "Player {} starting position: {}\n",
+                    player,
+                    rng.between(1, 10)
+                )
+            })
+            .collect()
+    }
+
+    fn variants() -> Vec<Variant<Self>> {
+        vec![Variant {
+            name: "roll by roll",
+            part: Part::Two,
+            solve: |d| {
+                let wins = count_wins_roll_by_roll(&d.players);
+                (*wins.iter().max().unwrap()).into()
+            },
+        }]
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("deterministic dice : loosing score by rolls {}", answer),
+            Part::Two => format!("max number of win : {}", answer),
+        }
+    }
+}
+pub fn display_dirac_dice_play(input: &str, part: Part) {
+    print_answer::<DiracDice>(input, part)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn aoc_example_works() {
-        let input = "Player 1 starting position: 4
-Player 2 starting position: 8";
+        let input = EXAMPLES[0].input;
 
         let players: Vec<_> = input
             .lines()
@@ -238,8 +317,7 @@ Player 2 starting position: 8";
         println!("wins {:?}", wins);
         assert_eq!(444356092776315, wins[0]);
 
-        //assert_eq!(341960390180808,wins[1]);
-        // is this a bug ?
-        assert_eq!(341960390180810, wins[1]);
+        assert_eq!(341960390180808, wins[1]);
+        assert_eq!(wins, count_wins_roll_by_roll(&players));
     }
 }
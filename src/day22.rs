@@ -1,5 +1,9 @@
 use crate::day19::ThreeDPoint;
-use anyhow::{anyhow, Error};
+use crate::parse::parse_lines;
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver, Variant};
+use anyhow::{anyhow, Error, Result};
 use itertools::Itertools;
 use std::cmp::{max, min};
 use std::ops::Range;
@@ -74,14 +78,23 @@ fn get_actual_range(proposed: &(isize, isize), possible: &(isize, isize)) -> Ran
 }
 
 #[derive(Debug, Clone, Default)]
-struct Cuboid {
+pub struct Cuboid {
     xrange: (isize, isize),
     yrange: (isize, isize),
     zrange: (isize, isize),
 }
 
 impl Cuboid {
-    fn intersect(&self, other: &Self) -> Option<Self> {
+    /// each range is inclusive
+    pub fn new(xrange: (isize, isize), yrange: (isize, isize), zrange: (isize, isize)) -> Self {
+        Self {
+            xrange,
+            yrange,
+            zrange,
+        }
+    }
+
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
         if let Some((xrange, yrange, zrange)) = (0..3)
             .filter_map(|i| intersect_vrange(self.get_range(i), other.get_range(i)))
             .collect_tuple()
@@ -106,19 +119,24 @@ impl Cuboid {
         }
     }
 
-    fn size(&self) -> usize {
+    pub fn size(&self) -> usize {
         (self.xrange.1 - self.xrange.0 + 1) as usize
             * (self.yrange.1 - self.yrange.0 + 1) as usize
             * (self.zrange.1 - self.zrange.0 + 1) as usize
     }
 }
 
-struct Command {
+#[derive(Clone)]
+pub struct Command {
     status: bool,
     cubes: Cuboid,
 }
 
 impl Command {
+    pub fn new(status: bool, cubes: Cuboid) -> Self {
+        Self { status, cubes }
+    }
+
     // tbh, using &mut dyn Reactor (without size) should be more practicable
     fn apply<const SIZE: usize>(&self, reactor: &mut Reactor<SIZE>) {
         let possible = (-(reactor.offset as isize), reactor.offset as isize);
@@ -192,27 +210,23 @@ impl FromStr for Command {
     }
 }
 
+fn parse_commands(input: &str) -> Result<Vec<Command>> {
+    parse_lines(input, |l| l.parse::<Command>())
+}
+
 // Naïve approach : all cubes are represented by a bool !
-fn reboot_sized_reactor(input: &str) -> usize {
+fn reboot_sized_reactor(commands: &[Command]) -> usize {
     let mut reactor: Reactor<101> = Reactor::new();
-    let commands: Vec<Command> = input
-        .lines()
-        .filter_map(|l| l.parse::<Command>().ok())
-        .collect();
 
-    for command in &commands {
+    for command in commands {
         command.apply(&mut reactor);
     }
     reactor.count_lit_cubes()
 }
 
-fn reboot_unsized_reactor(input: &str) -> usize {
-    let commands: Vec<Command> = input
-        .lines()
-        .filter_map(|l| l.parse::<Command>().ok())
-        .collect();
+pub fn reboot_unsized_reactor(commands: &[Command]) -> usize {
     let mut commands_counting_overlap: Vec<Command> = Vec::with_capacity(commands.len());
-    for command in commands {
+    for command in commands.iter().cloned() {
         let mut extra_command: Vec<Command> = Vec::with_capacity(commands_counting_overlap.len());
 
         // all ON commands are added to the mix
@@ -249,55 +263,37 @@ fn reboot_unsized_reactor(input: &str) -> usize {
         .sum::<isize>() as usize
 }
 
-pub fn display_reactor_reboot() {
-    let input = include_str!("../resources/day22_reboot_sequence.txt");
-    println!(
-        "number of cubes lit after reboot sequence for 101*101*101 reactor : {}",
-        reboot_sized_reactor(input)
-    );
-    println!(
-        "number of cubes lit after reboot sequence for full reactor : {}",
-        reboot_unsized_reactor(input)
-    );
+// same as the sized reactor, through the cuboids algebra
+fn reboot_initialization_area(commands: &[Command]) -> usize {
+    let area = Cuboid::new((-50, 50), (-50, 50), (-50, 50));
+    let commands: Vec<Command> = commands
+        .iter()
+        .filter_map(|c| {
+            c.cubes
+                .intersect(&area)
+                .map(|cubes| Command::new(c.status, cubes))
+        })
+        .collect();
+    reboot_unsized_reactor(&commands)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn actual_ranges_can_always_be_extracted() {
-        assert_eq!(101, get_actual_range(&(-50, 50), &(-50, 50)).len());
-        assert_eq!(101, get_actual_range(&(-50, 50), &(-50, 50)).len());
-        assert_eq!(1, get_actual_range(&(0, 0), &(-50, 50)).len());
-        assert_eq!(101, get_actual_range(&(-100, 100), &(-50, 50)).len());
-        assert_eq!(0, get_actual_range(&(-100, -51), &(-50, 50)).len());
-        assert_eq!(1, get_actual_range(&(-100, -50), &(-50, 50)).len());
-        assert_eq!(0, get_actual_range(&(51, 1000), &(-50, 50)).len());
-        assert_eq!(1, get_actual_range(&(50, 1000), &(-50, 50)).len());
-
-        assert_eq!(0, get_actual_range(&(-100, -101), &(-100, 0)).len());
-    }
+pub struct RebootSequence {
+    commands: Vec<Command>,
+}
 
-    #[test]
-    fn simple_aoc_example_works() {
-        let input = "on x=10..12,y=10..12,z=10..12
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        name: "small",
+        input: "on x=10..12,y=10..12,z=10..12
 on x=11..13,y=11..13,z=11..13
 off x=9..11,y=9..11,z=9..11
-on x=10..10,y=10..10,z=10..10";
-        let commands: Vec<_> = input
-            .lines()
-            .filter_map(|l| l.parse::<Command>().ok())
-            .collect();
-        let mut reactor: Reactor<31> = Reactor::new();
-        for c in commands {
-            c.apply(&mut reactor);
-        }
-        assert_eq!(39, reactor.count_lit_cubes());
-    }
-    #[test]
-    fn aoc_sized_cube_example_works() {
-        let input = "on x=-20..26,y=-36..17,z=-47..7
+on x=10..10,y=10..10,z=10..10",
+        part1: Some("39"),
+        part2: None,
+    },
+    Example {
+        name: "larger",
+        input: "on x=-20..26,y=-36..17,z=-47..7
 on x=-20..33,y=-21..23,z=-26..28
 on x=-22..28,y=-29..23,z=-38..16
 on x=-46..7,y=-6..46,z=-50..-1
@@ -318,12 +314,13 @@ on x=-49..-5,y=-3..45,z=-29..18
 off x=18..30,y=-20..-8,z=-3..13
 on x=-41..9,y=-7..43,z=-33..15
 on x=-54112..-39298,y=-85059..-49293,z=-27449..7877
-on x=967..23432,y=45373..81175,z=27513..53682";
-        assert_eq!(590784, reboot_sized_reactor(input));
-    }
-    #[test]
-    fn aoc_unsized_cube_example_works() {
-        let input = "on x=-5..47,y=-31..22,z=-19..33
+on x=967..23432,y=45373..81175,z=27513..53682",
+        part1: Some("590784"),
+        part2: None,
+    },
+    Example {
+        name: "reboot steps",
+        input: "on x=-5..47,y=-31..22,z=-19..33
 on x=-44..5,y=-27..21,z=-14..35
 on x=-49..-1,y=-11..42,z=-10..38
 on x=-20..34,y=-40..6,z=-44..1
@@ -382,7 +379,128 @@ off x=-37810..49457,y=-71013..-7894,z=-105357..-13188
 off x=-27365..46395,y=31009..98017,z=15428..76570
 off x=-70369..-16548,y=22648..78696,z=-1892..86821
 on x=-53470..21291,y=-120233..-33476,z=-44150..38147
-off x=-93533..-4276,y=-16170..68771,z=-104985..-24507";
-        assert_eq!(2758514936282235, reboot_unsized_reactor(input));
+off x=-93533..-4276,y=-16170..68771,z=-104985..-24507",
+        part1: Some("474140"),
+        part2: Some("2758514936282235"),
+    },
+];
+
+impl Solver for RebootSequence {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            commands: parse_commands(input)?,
+        })
+    }
+
+    fn part1(&self) -> Answer {
+        reboot_sized_reactor(&self.commands).into()
+    }
+
+    fn part2(&self) -> Answer {
+        reboot_unsized_reactor(&self.commands).into()
+    }
+
+    // one step in three stays in the initialization area, the first one switching cubes on
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        (0..size.max(1))
+            .map(|step| {
+                let state = if step == 0 || rng.one_in(2) {
+                    "on"
+                } else {
+                    "off"
+                };
+                let (limit, extent) = if rng.one_in(3) {
+                    (50, 40)
+                } else {
+                    (100_000, 40_000)
+                };
+                let ranges: Vec<_> = ["x", "y", "z"]
+                    .iter()
+                    .map(|axis| {
+                        let low = rng.between(-limit, limit - extent);
+                        format!("{}={}..{}", axis, low, low + rng.between(0, extent))
+                    })
+                    .collect();
+                format!("{} {}\n", state, ranges.join(","))
+            })
+            .collect()
+    }
+
+    fn variants() -> Vec<Variant<Self>> {
+        vec![Variant {
+            name: "unsized reactor",
+            part: Part::One,
+            solve: |r| reboot_initialization_area(&r.commands).into(),
+        }]
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!(
+                "number of cubes lit after reboot sequence for 101*101*101 reactor : {}",
+                answer
+            ),
+            Part::Two => format!(
+                "number of cubes lit after reboot sequence for full reactor : {}",
+                answer
+            ),
+        }
+    }
+}
+
+pub fn display_reactor_reboot(input: &str, part: Part) {
+    print_answer::<RebootSequence>(input, part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actual_ranges_can_always_be_extracted() {
+        assert_eq!(101, get_actual_range(&(-50, 50), &(-50, 50)).len());
+        assert_eq!(101, get_actual_range(&(-50, 50), &(-50, 50)).len());
+        assert_eq!(1, get_actual_range(&(0, 0), &(-50, 50)).len());
+        assert_eq!(101, get_actual_range(&(-100, 100), &(-50, 50)).len());
+        assert_eq!(0, get_actual_range(&(-100, -51), &(-50, 50)).len());
+        assert_eq!(1, get_actual_range(&(-100, -50), &(-50, 50)).len());
+        assert_eq!(0, get_actual_range(&(51, 1000), &(-50, 50)).len());
+        assert_eq!(1, get_actual_range(&(50, 1000), &(-50, 50)).len());
+
+        assert_eq!(0, get_actual_range(&(-100, -101), &(-100, 0)).len());
+    }
+
+    #[test]
+    fn simple_aoc_example_works() {
+        let input = EXAMPLES[0].input;
+        let commands: Vec<_> = input
+            .lines()
+            .filter_map(|l| l.parse::<Command>().ok())
+            .collect();
+        let mut reactor: Reactor<31> = Reactor::new();
+        for c in commands {
+            c.apply(&mut reactor);
+        }
+        assert_eq!(39, reactor.count_lit_cubes());
+    }
+    #[test]
+    fn aoc_sized_cube_example_works() {
+        let input = EXAMPLES[1].input;
+        assert_eq!(
+            590784,
+            reboot_sized_reactor(&parse_commands(input).unwrap())
+        );
+        assert_eq!(
+            590784,
+            reboot_initialization_area(&parse_commands(input).unwrap())
+        );
+    }
+    #[test]
+    fn aoc_unsized_cube_example_works() {
+        let input = EXAMPLES[2].input;
+        assert_eq!(
+            2758514936282235,
+            reboot_unsized_reactor(&parse_commands(input).unwrap())
+        );
     }
 }
@@ -1,8 +1,13 @@
 use crate::day23::Amphipod::{Amber, Bronze, Copper, Desert};
-use anyhow::{anyhow, Error};
+use crate::parse::ParseError;
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::search::dijkstra;
+use crate::solver::{print_answer, Answer, Example, Solver};
+use crate::trace::{debug, trace};
+use anyhow::{anyhow, Error, Result};
 use itertools::Itertools;
 use std::cmp::{max, min};
-use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::hash::Hash;
 use std::str::FromStr;
@@ -51,7 +56,7 @@ impl Display for Amphipod {
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Hash)]
-struct Disposition<const ROOM_LENGTH: usize> {
+pub struct Disposition<const ROOM_LENGTH: usize> {
     hallway: [Option<Amphipod>; 11],
     rooms: [[Option<Amphipod>; ROOM_LENGTH]; 4],
 }
@@ -137,179 +142,168 @@ impl<const ROOM_LENGTH: usize> Display for Disposition<ROOM_LENGTH> {
     }
 }
 
-#[derive(Clone)]
-struct Situation<const ROOM_LENGTH: usize> {
-    score: usize,
-    disposition: Disposition<ROOM_LENGTH>,
+/// successive dispositions side by side, with the energy spent
+fn display_path<const ROOM_LENGTH: usize>(
+    path: &crate::search::SearchPath<Disposition<ROOM_LENGTH>>,
+) -> String {
+    let descriptions: Vec<String> = path.states.iter().map(|d| format!("{}", d)).collect();
 
-    #[cfg(debug_assertions)]
-    previous_disposition: Vec<Disposition<ROOM_LENGTH>>,
-}
-
-impl<const ROOM_LENGTH: usize> Display for Situation<ROOM_LENGTH> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        #[cfg(debug_assertions)]
-        {
-            let descriptions: Vec<String> = self
-                .previous_disposition
+    let flat_description = (0..descriptions[0].lines().count())
+        .map(|i| {
+            // FIXME respliting each time is higly inneffective, but we"re not supposed to display these
+            descriptions
                 .iter()
-                .map(|d| format!("{}", d))
-                .collect();
-
-            let flat_description = (0..descriptions[0].lines().count())
-                .map(|i| {
-                    // FIXME respliting each time is higly inneffective, but we"re not supposed to display these
-                    descriptions
-                        .iter()
-                        .map(|descr| descr.lines().nth(i).unwrap_or(""))
-                        .join("  ")
-                })
-                .join("\n");
-
-            f.write_str(&flat_description)?;
-        }
+                .map(|descr| descr.lines().nth(i).unwrap_or(""))
+                .join("  ")
+        })
+        .join("\n");
 
-        f.write_fmt(format_args!("\n{}\n", self.score))?;
-        self.disposition.fmt(f)
-    }
+    format!("{}\n{}", flat_description, path.cost)
 }
 
-impl<const ROOM_LENGTH: usize> Situation<ROOM_LENGTH> {
-    // source is used in debug mode to log all successive steps
-    fn new(score: usize, disposition: Disposition<ROOM_LENGTH>, source: &Self) -> Self {
-        #[cfg(debug_assertions)]
-        let mut previous_disposition = source.previous_disposition.clone();
-        #[cfg(debug_assertions)]
-        previous_disposition.push(source.disposition);
-
-        Self {
-            score,
-            disposition,
-            #[cfg(debug_assertions)]
-            previous_disposition,
-        }
-    }
+impl<const ROOM_LENGTH: usize> Disposition<ROOM_LENGTH> {
     fn can_move_out_of_room(&self, apod_loc: (usize, usize)) -> bool {
         // current room is finished
-        if self.disposition.is_room_finished(apod_loc.0) {
+        if self.is_room_finished(apod_loc.0) {
             return false;
         }
 
         // cannot pass through top Amphipods
-        if self.disposition.rooms[apod_loc.0][0..apod_loc.1]
+        if self.rooms[apod_loc.0][0..apod_loc.1]
             .iter()
             .any(Option::is_some)
         {
             return false;
         }
         // Amphipod is already in its room, which also contains only its siblings
-        if self.disposition.is_room_available(apod_loc.0) {
+        if self.is_room_available(apod_loc.0) {
             return false;
         }
         true
     }
 
-    /// provides a new Solution where an Amphipod have been moved from
+    /// provides a new Disposition, and the energy spent, where an Amphipod have been moved from
     /// its start room to its target room (as deep as possible)
     /// can be provided thanks to the 2 other moves, but this
     /// reduce the number of possibilities
-    fn room_to_room(&self, apod_loc: (usize, usize)) -> Option<Self> {
+    fn room_to_room(&self, apod_loc: (usize, usize)) -> Option<(Self, usize)> {
         if !self.can_move_out_of_room(apod_loc) {
             return None;
         }
 
-        if let Some(apod) = self.disposition.rooms[apod_loc.0][apod_loc.1] {
-            let room_idx = Disposition::<ROOM_LENGTH>::get_suitable_room(apod);
+        if let Some(apod) = self.rooms[apod_loc.0][apod_loc.1] {
+            let room_idx = Self::get_suitable_room(apod);
             let (hall_start, hall_end) = (
-                Disposition::<ROOM_LENGTH>::get_hallway_index_from_room(apod_loc.0),
-                Disposition::<ROOM_LENGTH>::get_hallway_index_from_room(room_idx),
+                Self::get_hallway_index_from_room(apod_loc.0),
+                Self::get_hallway_index_from_room(room_idx),
             );
             let hallway_travel = min(hall_start, hall_end)..max(hall_start, hall_end) + 1;
 
-            if self.disposition.hallway[hallway_travel.clone()]
+            if self.hallway[hallway_travel.clone()]
                 .iter()
                 .all(Option::is_none)
             {
-                if let Some(target_room_pos) = self.disposition.get_first_available_place(room_idx)
-                {
-                    let score = self.score
-                        + apod.get_single_move_cost()
-                            * (apod_loc.1 + hallway_travel.len() + 1 + target_room_pos);
-                    let hallway = self.disposition.hallway;
-                    let mut rooms = self.disposition.rooms;
+                if let Some(target_room_pos) = self.get_first_available_place(room_idx) {
+                    let energy = apod.get_single_move_cost()
+                        * (apod_loc.1 + hallway_travel.len() + 1 + target_room_pos);
+                    let hallway = self.hallway;
+                    let mut rooms = self.rooms;
                     rooms[apod_loc.0][apod_loc.1] = None;
                     rooms[room_idx][target_room_pos] = Some(apod);
 
-                    return Some(Self::new(score, Disposition { hallway, rooms }, self));
+                    return Some((Self { hallway, rooms }, energy));
                 }
             }
         }
         None
     }
 
-    fn hallway_to_room(&self, apod_loc: usize) -> Option<Self> {
-        if let Some(apod) = self.disposition.hallway[apod_loc] {
-            let room_idx = Disposition::<ROOM_LENGTH>::get_suitable_room(apod);
-            let (hall_start, hall_end) = (
-                apod_loc,
-                Disposition::<ROOM_LENGTH>::get_hallway_index_from_room(room_idx),
-            );
+    fn hallway_to_room(&self, apod_loc: usize) -> Option<(Self, usize)> {
+        if let Some(apod) = self.hallway[apod_loc] {
+            let room_idx = Self::get_suitable_room(apod);
+            let (hall_start, hall_end) = (apod_loc, Self::get_hallway_index_from_room(room_idx));
             let hallway_travel = min(hall_start, hall_end)..max(hall_start, hall_end) + 1;
 
             // we're already on the hallway => that makes 1 place occupied
-            if self.disposition.hallway[hallway_travel.clone()]
+            if self.hallway[hallway_travel.clone()]
                 .iter()
                 .filter(|occupant| !occupant.is_none())
                 .count()
                 == 1
             {
-                if let Some(target_room_pos) = self.disposition.get_first_available_place(room_idx)
-                {
-                    let score = self.score
-                        + apod.get_single_move_cost() * (hallway_travel.len() + target_room_pos);
-                    let mut hallway = self.disposition.hallway;
+                if let Some(target_room_pos) = self.get_first_available_place(room_idx) {
+                    let energy =
+                        apod.get_single_move_cost() * (hallway_travel.len() + target_room_pos);
+                    let mut hallway = self.hallway;
                     hallway[apod_loc] = None;
-                    let mut rooms = self.disposition.rooms;
+                    let mut rooms = self.rooms;
                     rooms[room_idx][target_room_pos] = Some(apod);
-                    return Some(Self::new(score, Disposition { hallway, rooms }, self));
+                    return Some((Self { hallway, rooms }, energy));
                 }
             }
         }
         None
     }
 
-    fn room_to_hallway(&self, apod_loc: (usize, usize), hall_target: usize) -> Option<Self> {
+    fn room_to_hallway(
+        &self,
+        apod_loc: (usize, usize),
+        hall_target: usize,
+    ) -> Option<(Self, usize)> {
         if !self.can_move_out_of_room(apod_loc) {
             return None;
         }
 
-        if !Disposition::<ROOM_LENGTH>::can_store_amphipod(hall_target) {
+        if !Self::can_store_amphipod(hall_target) {
             return None;
         }
 
-        if let Some(apod) = self.disposition.rooms[apod_loc.0][apod_loc.1] {
-            let (hall_start, hall_end) = (
-                Disposition::<ROOM_LENGTH>::get_hallway_index_from_room(apod_loc.0),
-                hall_target,
-            );
+        if let Some(apod) = self.rooms[apod_loc.0][apod_loc.1] {
+            let (hall_start, hall_end) =
+                (Self::get_hallway_index_from_room(apod_loc.0), hall_target);
             let hallway_travel = min(hall_start, hall_end)..max(hall_start, hall_end) + 1;
-            if self.disposition.hallway[hallway_travel.clone()]
+            if self.hallway[hallway_travel.clone()]
                 .iter()
                 .all(Option::is_none)
             {
-                let score =
-                    self.score + apod.get_single_move_cost() * (hallway_travel.len() + apod_loc.1);
-                let mut hallway = self.disposition.hallway;
+                let energy = apod.get_single_move_cost() * (hallway_travel.len() + apod_loc.1);
+                let mut hallway = self.hallway;
                 hallway[hall_target] = Some(apod);
-                let mut rooms = self.disposition.rooms;
+                let mut rooms = self.rooms;
                 rooms[apod_loc.0][apod_loc.1] = None;
 
-                return Some(Self::new(score, Disposition { hallway, rooms }, self));
+                return Some((Self { hallway, rooms }, energy));
             }
         }
 
         None
     }
+
+    /// moves straight into a room are always worth it, other moves are only
+    /// looked at when there is none
+    fn moves(&self) -> Vec<(Self, usize)> {
+        // ROOM to ROOM
+        let moves: Vec<_> = (0..self.rooms.len())
+            .flat_map(|i| (0..ROOM_LENGTH).filter_map(move |j| self.room_to_room((i, j))))
+            .collect();
+        if !moves.is_empty() {
+            return moves;
+        }
+
+        // HALLWAY TO ROOM
+        let moves: Vec<_> = (0..11).filter_map(|i| self.hallway_to_room(i)).collect();
+        if !moves.is_empty() {
+            return moves;
+        }
+
+        // ROOM_TO_HALLWAY
+        (0..self.rooms.len())
+            .flat_map(|i| {
+                (0..ROOM_LENGTH)
+                    .flat_map(move |j| (0..11).filter_map(move |z| self.room_to_hallway((i, j), z)))
+            })
+            .collect()
+    }
 }
 
 impl<const ROOM_LENGTH: usize> FromStr for Disposition<ROOM_LENGTH> {
@@ -318,16 +312,41 @@ impl<const ROOM_LENGTH: usize> FromStr for Disposition<ROOM_LENGTH> {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let amphipods: Vec<_> = s
             .lines()
-            .filter(|l| l.contains(&['A', 'B', 'C', 'D'][..]))
-            .map(|l| {
-                l.split('#')
+            .enumerate()
+            .filter(|(_, l)| l.contains(&['A', 'B', 'C', 'D'][..]))
+            .map(|(i, l)| {
+                let line = l
+                    .split('#')
                     .filter_map(|c| c.parse::<Amphipod>().ok())
-                    .collect_vec()
+                    .collect_vec();
+                if line.len() != 4 {
+                    return Err(ParseError::new(
+                        i + 1,
+                        format!("'{}' : {} amphipods instead of 4", l, line.len()),
+                    ));
+                }
+                Ok(line)
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
 
         if amphipods.len() != ROOM_LENGTH {
-            return Err(anyhow!("amphipods rooms seem malformed"));
+            return Err(anyhow!(
+                "{} rows of amphipods instead of {}",
+                amphipods.len(),
+                ROOM_LENGTH
+            ));
+        }
+        // each room must be filled by its own kind of amphipods
+        for kind in [Amber, Bronze, Copper, Desert] {
+            let count = amphipods.iter().flatten().filter(|a| **a == kind).count();
+            if count != ROOM_LENGTH {
+                return Err(anyhow!(
+                    "{} {} amphipods instead of {}",
+                    count,
+                    kind,
+                    ROOM_LENGTH
+                ));
+            }
         }
 
         let hallway = [None; 11];
@@ -342,132 +361,100 @@ impl<const ROOM_LENGTH: usize> FromStr for Disposition<ROOM_LENGTH> {
     }
 }
 
-// TODO : just use dyn disptach inside Situation
-fn get_least_energy_to_organize_amphipods<const ROOM_LENGTH: usize>(
+pub fn get_least_energy_to_organize_amphipods<const ROOM_LENGTH: usize>(
     start: &Disposition<ROOM_LENGTH>,
-) -> usize {
-    let mut best_finished: Option<Situation<ROOM_LENGTH>> = None;
-    let mut best_score: Option<usize> = None;
-
-    let mut best_situations: HashMap<Disposition<ROOM_LENGTH>, Situation<ROOM_LENGTH>> =
-        HashMap::new();
-
-    let mut current_situations = vec![Situation {
-        score: 0,
-        disposition: *start,
-        #[cfg(debug_assertions)]
-        previous_disposition: vec![],
-    }];
-    while !current_situations.is_empty() {
-        let mut next_situations: HashMap<Disposition<ROOM_LENGTH>, Situation<ROOM_LENGTH>> =
-            HashMap::with_capacity(current_situations.len());
-
-        for situation in &current_situations {
-            let mut local_next_situations: Vec<Situation<ROOM_LENGTH>> =
-                Vec::with_capacity(current_situations.len());
-
-            // ROOM to ROOM
-            local_next_situations.append(
-                &mut (0..situation.disposition.rooms.len())
-                    .flat_map(|i| {
-                        (0..ROOM_LENGTH).filter_map(move |j| situation.room_to_room((i, j)))
-                    })
-                    .collect(),
-            );
-
-            // HALLWAY TO ROOM
-            if local_next_situations.is_empty() {
-                local_next_situations.append(
-                    &mut (0..11)
-                        .filter_map(move |i| situation.hallway_to_room(i))
-                        .collect(),
-                );
-            }
-
-            // ROOM_TO_HALLWAY
-            if local_next_situations.is_empty() {
-                local_next_situations.append(
-                    &mut (0..situation.disposition.rooms.len())
-                        .flat_map(|i| {
-                            (0..ROOM_LENGTH).flat_map(move |j| {
-                                (0..11).filter_map(move |z| situation.room_to_hallway((i, j), z))
-                            })
-                        })
-                        .collect(),
-                );
-            }
-
-            //best
-            if let Some(local_best) = local_next_situations
-                .iter()
-                .filter(|s| s.disposition.is_finished())
-                .min_by_key(|s| s.score)
-            {
-                if best_score.unwrap_or(usize::MAX) > local_best.score {
-                    best_finished = Some(local_best.clone());
-                    best_score = Some(local_best.score);
-                }
-            }
+) -> Option<usize> {
+    let mut explored = 0;
+    let best = dijkstra(
+        *start,
+        |disposition| {
+            explored += 1;
+            let moves = disposition.moves();
+            trace!("{} candidate moves from\n{}", moves.len(), disposition);
+            moves
+        },
+        Disposition::is_finished,
+    );
 
-            // filter against known bests
-            for s in local_next_situations {
-                if !s.disposition.is_finished()
-                    && best_score
-                        .map(|best_score| best_score > s.score)
-                        .unwrap_or(true)
-                    && best_situations
-                        .get(&s.disposition)
-                        .map(|sit| sit.score > s.score)
-                        .unwrap_or(true)
-                {
-                    best_situations.insert(s.disposition, s.clone());
-                    // this way, next_situations are replaced on the fly if a better one is found
-                    next_situations.insert(s.disposition, s.clone());
-                }
-            }
-        }
-        current_situations = next_situations.into_values().collect();
-        // eprintln!("next evaluation : {} candidates", current_situations.len())
+    debug!("{} dispositions explored", explored);
+    if let Some(best) = &best {
+        debug!("cheapest organization :\n{}\n", display_path(best));
     }
 
-    #[cfg(debug_assertions)]
-    if let Some(best) = best_finished {
-        eprintln!("{}\n", best);
+    best.map(|best| best.cost)
+}
+
+// the folded part of the diagram, revealed for the second part
+pub fn unfold(input: &str) -> String {
+    let mut lines: Vec<&str> = input.lines().collect();
+    if let Some(first_room_line) = lines
+        .iter()
+        .position(|l| l.contains(&['A', 'B', 'C', 'D'][..]))
+    {
+        lines.insert(first_room_line + 1, "  #D#C#B#A#");
+        lines.insert(first_room_line + 2, "  #D#B#A#C#");
     }
+    lines.join("\n")
+}
 
-    best_score.unwrap_or(usize::MAX)
+pub struct Burrow {
+    folded: Disposition<2>,
+    unfolded: Disposition<4>,
 }
 
-pub fn organize_amphipods() {
-    let input = "\
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "burrow",
+    input: "\
 #############
 #...........#
-###D#A#C#A###
-  #D#C#B#B#
-  #########";
+###B#C#B#D###
+  #A#D#C#A#
+  #########",
+    part1: Some("12521"),
+    part2: Some("44169"),
+}];
+
+impl Solver for Burrow {
+    // burrows which cannot be organized used to be answered with usize::MAX
+    const VERSION: u32 = 2;
+
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            folded: input.parse()?,
+            unfolded: unfold(input).parse()?,
+        })
+    }
 
-    let start: Disposition<2> = input.parse().expect("failed to parse a Disposition");
+    fn part1(&self) -> Answer {
+        get_least_energy_to_organize_amphipods(&self.folded).map_or(Answer::None, Answer::from)
+    }
 
-    println!(
-        "Least energy to organize amphipods: {}",
-        get_least_energy_to_organize_amphipods(&start)
-    );
+    fn part2(&self) -> Answer {
+        get_least_energy_to_organize_amphipods(&self.unfolded).map_or(Answer::None, Answer::from)
+    }
 
-    let unfold_input = "\
-#############
-#...........#
-###D#A#C#A###
-  #D#C#B#A#
-  #D#B#A#C#
-  #D#C#B#B#
-  #########";
+    // the size does not matter, the burrow having a fixed size
+    fn generate(rng: &mut Rng, _size: usize) -> String {
+        let mut amphipods: Vec<char> = "AABBCCDD".chars().collect();
+        rng.shuffle(&mut amphipods);
+        let rooms = |row: &[char]| row.iter().join("#");
+        format!(
+            "#############\n#...........#\n###{}###\n  #{}#\n  #########\n",
+            rooms(&amphipods[..4]),
+            rooms(&amphipods[4..])
+        )
+    }
 
-    let new_start: Disposition<4> = unfold_input.parse().expect("failed to parse a Disposition");
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("Least energy to organize amphipods: {}", answer),
+            Part::Two => format!("Least energy to organize all amphipods: {}", answer),
+        }
+    }
+}
 
-    println!(
-        "Least energy to organize all amphipods: {}",
-        get_least_energy_to_organize_amphipods(&new_start)
-    );
+pub fn organize_amphipods(input: &str, part: Part) {
+    print_answer::<Burrow>(input, part)
 }
 
 #[cfg(test)]
@@ -493,24 +480,53 @@ mod tests {
 
     #[test]
     fn aoc_example_works() {
+        let input = EXAMPLES[0].input;
+        let start: Disposition<2> = input.parse().expect("could not parse input as Situation");
+        assert_eq!(Some(12521), get_least_energy_to_organize_amphipods(&start));
+
         let input = "\
 #############
 #...........#
 ###B#C#B#D###
+  #D#C#B#A#
+  #D#B#A#C#
   #A#D#C#A#
-  #########    ";
-        let start: Disposition<2> = input.parse().expect("could not parse input as Situation");
-        assert_eq!(12521, get_least_energy_to_organize_amphipods(&start));
+  #########  ";
+        let start: Disposition<4> = input.parse().expect("could not parse input as Situation");
+        assert_eq!(Some(44169), get_least_energy_to_organize_amphipods(&start));
+    }
+
+    #[test]
+    fn amphipods_must_fill_their_rooms() {
+        let err = "#############\n#...........#\n###A#A#A#A###\n  #B#C#D#B#\n  #########"
+            .parse::<Disposition<2>>()
+            .err()
+            .unwrap();
+        assert_eq!("4 A amphipods instead of 2", err.to_string());
+
+        let mut stuck: Disposition<2> = EXAMPLES[0].input.parse().unwrap();
+        stuck.hallway = [Some(Amber); 11];
+        assert_eq!(None, get_least_energy_to_organize_amphipods(&stuck));
+    }
 
+    #[test]
+    fn diagram_can_be_unfolded() {
         let input = "\
 #############
 #...........#
+###B#C#B#D###
+  #A#D#C#A#
+  #########";
+        assert_eq!(
+            "\
+#############
+#...........#
 ###B#C#B#D###
   #D#C#B#A#
   #D#B#A#C#
   #A#D#C#A#
-  #########  ";
-        let start: Disposition<4> = input.parse().expect("could not parse input as Situation");
-        assert_eq!(44169, get_least_energy_to_organize_amphipods(&start));
+  #########",
+            unfold(input)
+        );
     }
 }
@@ -1,8 +1,14 @@
 use crate::day24::Operation::{Add, Div, Equal, Input, Mod, Mul};
 use crate::day24::Value::{Reg, Val};
-use anyhow::{anyhow, Error};
+use crate::parse::parse_lines;
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver, Variant};
+use crate::trace::{info, trace};
+use anyhow::{anyhow, Error, Result};
 use itertools::Itertools;
 use rayon::prelude::*;
+use std::cell::OnceCell;
 use std::cmp::{max, min};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Display;
@@ -10,7 +16,7 @@ use std::fmt::Formatter;
 use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-enum Value {
+pub enum Value {
     Reg(u8),
     Val(isize),
 }
@@ -29,8 +35,11 @@ impl FromStr for Value {
     }
 }
 
+/// names of the registers, by index
+const REGISTERS: [char; 4] = ['w', 'x', 'y', 'z'];
+
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-enum Operation {
+pub enum Operation {
     Input(usize),
     Add((usize, Value)),
     Mul((usize, Value)),
@@ -39,6 +48,30 @@ enum Operation {
     Equal((usize, Value)),
 }
 
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reg(reg) => write!(f, "{}", REGISTERS[*reg as usize]),
+            Val(val) => write!(f, "{}", val),
+        }
+    }
+}
+
+/// same as the program source
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (name, (a, b)) = match self {
+            Input(a) => return write!(f, "inp {}", REGISTERS[*a]),
+            Add(params) => ("add", params),
+            Mul(params) => ("mul", params),
+            Div(params) => ("div", params),
+            Mod(params) => ("mod", params),
+            Equal(params) => ("eql", params),
+        };
+        write!(f, "{} {} {}", name, REGISTERS[*a], b)
+    }
+}
+
 impl Operation {
     fn decode_dual_params(vals: &str) -> Result<(usize, Value), Error> {
         let (a, b) = vals
@@ -57,7 +90,7 @@ impl FromStr for Operation {
     type Err = Error;
 
     fn from_str(instr: &str) -> Result<Self, Self::Err> {
-        match &instr[..4] {
+        match instr.get(..4).unwrap_or(instr) {
             "inp " => instr[4..]
                 .parse::<Value>()
                 .and_then(|v| {
@@ -74,13 +107,14 @@ impl FromStr for Operation {
             "mod " => Self::decode_dual_params(&instr[4..]).map(|(a, b)| Mod((a, b))),
             "eql " => Self::decode_dual_params(&instr[4..]).map(|(a, b)| Equal((a, b))),
 
-            _ => Err(anyhow!("could not translate {}", &instr[..4])),
+            op => Err(anyhow!("could not translate '{}'", op.trim())),
         }
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Copy, Hash, PartialEq, Eq)]
-struct ALU<'alu> {
+pub struct ALU<'alu> {
     registers: [isize; 4],
 
     instructions: &'alu [Operation],
@@ -103,7 +137,7 @@ impl<'alu> Display for ALU<'alu> {
 }
 
 impl<'alu> ALU<'alu> {
-    fn new(instructions: &'alu [Operation]) -> Self {
+    pub fn new(instructions: &'alu [Operation]) -> Self {
         Self {
             registers: [0; 4],
             instructions,
@@ -111,6 +145,11 @@ impl<'alu> ALU<'alu> {
             sp: 0,
         }
     }
+    /// w, x, y and z
+    pub fn registers(&self) -> &[isize; 4] {
+        &self.registers
+    }
+
     fn get(&self, v: &Value) -> isize {
         match v {
             Reg(u) => self.registers[*u as usize],
@@ -134,49 +173,44 @@ impl<'alu> ALU<'alu> {
     }
 
     fn input(&mut self, reg: usize) {
-        let val = self.next_val.take();
-        let reg = self.get_mut(reg);
-
-        *reg = val.expect("cannot process input instruction without data !") as isize;
-        // eprintln!("loading {} into {} ==>\n{}", &val, a, self);
+        let val = self
+            .next_val
+            .take()
+            .expect("cannot process input instruction without data !");
+        *self.get_mut(reg) = val as isize;
     }
 
     fn add(&mut self, a: usize, b: &Value) {
         let reg_b = self.get(b);
-        // eprintln!("add {} to {}({})", a, reg_b, b.trim());
         let reg_a = self.get_mut(a);
         *reg_a += reg_b;
     }
     fn mul(&mut self, a: usize, b: &Value) {
         let reg_b = self.get(b);
-        // eprintln!("mul {} by {}({})", a, reg_b, b.trim());
         let reg_a = self.get_mut(a);
         *reg_a *= reg_b;
     }
 
     fn div(&mut self, a: usize, b: &Value) {
         let reg_b = self.get(b);
-        // eprintln!("div {} by {}({})", a, reg_b, b.trim());
         let reg_a = self.get_mut(a);
         *reg_a /= reg_b;
     }
 
     fn modulo(&mut self, a: usize, b: &Value) {
         let reg_b = self.get(b);
-        // eprintln!("mod {} by {}({})", a, reg_b, b.trim());
         let reg_a = self.get_mut(a);
         *reg_a %= reg_b;
     }
 
     fn equal(&mut self, a: usize, b: &Value) {
         let reg_b = self.get(b);
-        // eprintln!("eq {} with {}({})", a, reg_b, b.trim());
-
         let reg_a = self.get_mut(a);
         *reg_a = if *reg_a == reg_b { 1 } else { 0 };
     }
 
-    fn resume(&mut self, next_val: Option<u8>) {
+    /// run until the end of the program, or until an input is needed while next_val is already consumed
+    pub fn resume(&mut self, next_val: Option<u8>) {
         self.next_val = next_val;
         let sp = self.sp;
         for instruction in &self.instructions[sp..] {
@@ -188,7 +222,7 @@ impl<'alu> ALU<'alu> {
             }
             self.process_instruction(instruction);
             self.sp += 1;
-            // eprintln!("{}\t{:?}",self.sp,self.registers);
+            trace!("{}\t{}\t{:?}", self.sp, instruction, self.registers);
         }
     }
 }
@@ -196,13 +230,10 @@ impl<'alu> ALU<'alu> {
 /// explore the solutions by step
 /// for each step, equivalent state is associated with its (min,max) data tuple
 /// conflating states at each step is not efficient enough, hence the one level Rayon only
-#[allow(dead_code)]
 fn get_largest_model_number_accepted_by_monad_breath_first(
-    monad: &str,
+    instructions: &[Operation],
     size: usize,
-) -> (usize, usize) {
-    let instructions = decode(monad);
-
+) -> Option<(usize, usize)> {
     // each intermediate value is identified by
     // * the registers values
     // * the SP
@@ -210,7 +241,7 @@ fn get_largest_model_number_accepted_by_monad_breath_first(
     let results: Vec<_> = (1..10)
         .into_par_iter()
         .flat_map(|i| {
-            let mut alu = ALU::new(&instructions);
+            let mut alu = ALU::new(instructions);
             alu.resume(Some(i));
             let mut intermediate_results = vec![(alu, (i as usize, i as usize))];
 
@@ -249,29 +280,17 @@ fn get_largest_model_number_accepted_by_monad_breath_first(
         })
         .collect();
 
-    let (fmin, fmax) = results
+    let accepted = results
         .into_iter()
-        .fold((usize::MAX, 0), |(fmin, fmax), (_, (lmin, lmax))| {
-            (min(fmin, lmin), max(fmax, lmax))
-        });
+        .map(|(_, checked)| checked)
+        .reduce(|(fmin, fmax), (lmin, lmax)| (min(fmin, lmin), max(fmax, lmax)));
 
-    eprintln!("max checked : {} ", fmax);
-    eprintln!("min checked : {} ", fmin);
-    (fmin, fmax)
+    info!("(min, max) checked : {:?}", accepted);
+    accepted
 }
 
-fn decode(program: &str) -> Vec<Operation> {
-    program
-        .lines()
-        .filter_map(|l| {
-            if let Ok(oper) = l.parse() {
-                Some(oper)
-            } else {
-                eprintln!("could not parse '{}'", l);
-                None
-            }
-        })
-        .collect()
+pub fn decode(program: &str) -> Result<Vec<Operation>> {
+    parse_lines(program, |l| l.parse())
 }
 
 /// explore the solution from max to min
@@ -281,11 +300,9 @@ fn decode(program: &str) -> Vec<Operation> {
 /// current version just split the job by first digit
 /// failed state are not shared this way (should spawn real task to do so, and then merge the fails at each return)
 fn get_largest_model_number_accepted_by_monad_depth_first(
-    monad: &str,
+    instructions: &[Operation],
     size: usize,
-) -> (usize, usize) {
-    let instructions = decode(monad);
-
+) -> Option<(usize, usize)> {
     let checked_max = vec![9u8; size];
     let checked_min = vec![1u8; size];
 
@@ -306,12 +323,12 @@ fn get_largest_model_number_accepted_by_monad_depth_first(
             let mut guaranteed_ko_partial_states: HashSet<(usize, [isize; 4])> =
                 HashSet::with_capacity(3usize.pow(size as u32));
 
-            intermediate_results.push_back(ALU::new(&instructions));
+            intermediate_results.push_back(ALU::new(instructions));
             loop {
                 while intermediate_results.len() <= size {
                     // since the first value is BEFORE the first data
                     let base = intermediate_results
-                        .get(intermediate_results.len() - 1)
+                        .back()
                         .expect("intermediate_result should not be empty");
 
                     let mut alu = *base;
@@ -319,7 +336,11 @@ fn get_largest_model_number_accepted_by_monad_depth_first(
 
                     if intermediate_results.len() < size {
                         if guaranteed_ko_partial_states.contains(&(alu.sp, alu.registers)) {
-                            // eprintln!("skipping {:?} {}, seen with {:?}",&data, intermediate_results.len()-1, garantied_ko_partial_states.get(&(alu.sp,alu.registers)));
+                            trace!(
+                                "skipping {:?} at digit {}, known to fail",
+                                data,
+                                intermediate_results.len() - 1
+                            );
                             data[intermediate_results.len()..]
                                 .copy_from_slice(&checked_min[intermediate_results.len()..]);
 
@@ -381,22 +402,144 @@ fn get_largest_model_number_accepted_by_monad_depth_first(
 
     results
         .into_iter()
-        .fold((usize::MAX, 0), |(fmin, fmax), (lmin, lmax)| {
-            (min(fmin, lmin), max(fmax, lmax))
+        .reduce(|(fmin, fmax), (lmin, lmax)| (min(fmin, lmin), max(fmax, lmax)))
+}
+
+/// serials have 14 digits, the search could not go through longer ones
+const MAX_DIGITS: usize = 14;
+
+pub struct Monad {
+    instructions: Vec<Operation>,
+    // the same search gives both the lowest and the largest serial, if any is accepted
+    accepted: OnceCell<Option<(usize, usize)>>,
+}
+
+impl Monad {
+    /// one digit of the serial is read by each inp, 14 for the real MONAD
+    fn digits(&self) -> usize {
+        self.instructions
+            .iter()
+            .filter(|op| matches!(op, Input(_)))
+            .count()
+    }
+
+    fn accepted(&self) -> Option<(usize, usize)> {
+        *self.accepted.get_or_init(|| {
+            get_largest_model_number_accepted_by_monad_depth_first(
+                &self.instructions,
+                self.digits(),
+            )
         })
+    }
 }
 
-pub fn print_larget_serial_accepted_by_monad() {
-    let program = include_str!("../resources/day24_monad.txt");
-    let (min_checked, max_checked) =
-        get_largest_model_number_accepted_by_monad_depth_first(program, 14);
-    println!("largest serial accepted {}", max_checked);
-    println!("lowest serial accepted {}", min_checked);
+// the puzzle text only shows small ALU programs, none of them checking a serial
+pub const EXAMPLES: &[Example] = &[];
+
+impl Solver for Monad {
+    // programs accepting no serial used to be answered with 0 and usize::MAX
+    const VERSION: u32 = 2;
+
+    fn parse(input: &str) -> Result<Self> {
+        let monad = Self {
+            instructions: decode(input)?,
+            accepted: OnceCell::new(),
+        };
+        match monad.digits() {
+            0 => Err(anyhow!("no inp instruction")),
+            digits if digits > MAX_DIGITS => Err(anyhow!(
+                "{} inp instructions, serials have at most {} digits",
+                digits,
+                MAX_DIGITS
+            )),
+            _ => Ok(monad),
+        }
+    }
+
+    fn part1(&self) -> Answer {
+        self.accepted()
+            .map_or(Answer::None, |(_, largest)| largest.into())
+    }
+
+    fn part2(&self) -> Answer {
+        self.accepted()
+            .map_or(Answer::None, |(lowest, _)| lowest.into())
+    }
+
+    // size blocks like the usual 14 ones, rounded up to an even number and at most 14,
+    // each one popping the digit pushed by its partner, whose offset keeps
+    // at least one pair of digits valid
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut blocks: Vec<(isize, isize, isize)> = vec![];
+        let mut pushed: Vec<isize> = vec![];
+        for remaining in (1..=size.clamp(1, MAX_DIGITS).next_multiple_of(2)).rev() {
+            let must_pop = pushed.len() + 1 >= remaining;
+            if !pushed.is_empty() && (must_pop || rng.one_in(2)) {
+                let offset = pushed.pop().unwrap();
+                blocks.push((26, rng.between(-8, 8) - offset, rng.between(1, 16)));
+            } else {
+                let offset = rng.between(1, 16);
+                pushed.push(offset);
+                blocks.push((1, rng.between(10, 16), offset));
+            }
+        }
+        blocks
+            .iter()
+            .map(|(div, a, b)| {
+                format!(
+                    "inp w\nmul x 0\nadd x z\nmod x 26\ndiv z {}\nadd x {}\neql x w\neql x 0\n\
+                     mul y 0\nadd y 25\nmul y x\nadd y 1\nmul z y\nmul y 0\nadd y w\nadd y {}\n\
+                     mul y x\nadd z y\n",
+                    div, a, b
+                )
+            })
+            .collect()
+    }
+
+    // uncached searches, the parts share a single depth first search
+    fn variants() -> Vec<Variant<Self>> {
+        vec![
+            Variant {
+                name: "depth first",
+                part: Part::One,
+                solve: |m| {
+                    get_largest_model_number_accepted_by_monad_depth_first(
+                        &m.instructions,
+                        m.digits(),
+                    )
+                    .map_or(Answer::None, |(_, largest)| largest.into())
+                },
+            },
+            Variant {
+                name: "breadth first",
+                part: Part::One,
+                solve: |m| {
+                    get_largest_model_number_accepted_by_monad_breath_first(
+                        &m.instructions,
+                        m.digits(),
+                    )
+                    .map_or(Answer::None, |(_, largest)| largest.into())
+                },
+            },
+        ]
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("largest serial accepted {}", answer),
+            Part::Two => format!("lowest serial accepted {}", answer),
+        }
+    }
+}
+
+pub fn print_larget_serial_accepted_by_monad(program: &str, part: Part) {
+    print_answer::<Monad>(program, part)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::solver::Solution;
 
     #[test]
     fn operationns_can_be_decoded() {
@@ -423,7 +566,8 @@ inp w
 mul x 0
 ";
         for instruction in program.lines() {
-            let _oper: Operation = instruction.parse().expect("parsing Operation");
+            let oper: Operation = instruction.parse().expect("parsing Operation");
+            assert_eq!(instruction, oper.to_string());
         }
     }
 
@@ -431,16 +575,48 @@ mul x 0
     fn monad_works_for_small_serial() {
         let program = include_str!("../resources/day24_monad.txt");
         println!(
-            "largest serial accepted {}",
-            get_largest_model_number_accepted_by_monad_depth_first(program, 7).0
+            "largest serial accepted {:?}",
+            get_largest_model_number_accepted_by_monad_depth_first(&decode(program).unwrap(), 7)
+                .map(|accepted| accepted.0)
+        );
+    }
+
+    #[test]
+    fn programs_need_serial_digits() {
+        assert_eq!(
+            "no inp instruction",
+            Monad::parse("add x 1\n").err().unwrap().to_string()
+        );
+        assert_eq!(
+            "15 inp instructions, serials have at most 14 digits",
+            Monad::parse(&"inp w\n".repeat(15))
+                .err()
+                .unwrap()
+                .to_string()
         );
     }
 
+    #[test]
+    fn programs_may_accept_no_serial() {
+        let monad = Monad::parse("inp w\nadd z 1\n").unwrap();
+        assert_eq!(Answer::None, monad.part1());
+        assert_eq!(Answer::None, monad.part2());
+        assert_eq!(Answer::None, monad.solve_variant("breadth first").unwrap());
+    }
+
+    #[test]
+    fn generated_serials_are_not_too_long() {
+        for (size, digits) in [(1, 2), (13, 14), (15, 14), (64, 14)] {
+            let monad = Monad::parse(&Monad::generate(&mut Rng::new(1), size)).unwrap();
+            assert_eq!(digits, monad.digits());
+        }
+    }
+
     #[test]
     fn monad_can_be_checked() {
         let program = include_str!("../resources/day24_monad.txt");
 
-        let instructions = decode(program);
+        let instructions = decode(program).unwrap();
 
         let data = vec![1, 1, 9, 9];
         let mut alu = ALU::new(&instructions);
@@ -455,8 +631,8 @@ mul x 0
     fn monad_can_be_checked_breath_first() {
         let program = include_str!("../resources/day24_monad.txt");
         assert_eq!(
-            (91811211611981, 92928914999991),
-            get_largest_model_number_accepted_by_monad_breath_first(program, 14)
+            Some((91811211611981, 92928914999991)),
+            get_largest_model_number_accepted_by_monad_breath_first(&decode(program).unwrap(), 14)
         );
     }
 
@@ -465,8 +641,8 @@ mul x 0
     fn monad_can_be_checked_depth_first() {
         let program = include_str!("../resources/day24_monad.txt");
         assert_eq!(
-            (91811211611981, 92928914999991),
-            get_largest_model_number_accepted_by_monad_depth_first(program, 14)
+            Some((91811211611981, 92928914999991)),
+            get_largest_model_number_accepted_by_monad_depth_first(&decode(program).unwrap(), 14)
         );
     }
 }
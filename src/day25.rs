@@ -1,117 +1,100 @@
 use crate::day25::Cucumber::{Down, Right};
+use crate::export::Color;
+use crate::grid::{Grid, Point};
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Frames, Solver};
+use anyhow::Result;
+use colored::Colorize;
+use std::collections::HashSet;
+use std::fmt::Write;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Cucumber {
     Right,
     Down,
 }
-type Floor = Vec<Vec<Option<Cucumber>>>;
-
-fn parse_input(input: &str) -> Floor {
-    input
-        .lines()
-        .map(|l| {
-            l.chars()
-                .map(|c| match c {
-                    '>' => Some(Right),
-                    'v' => Some(Down),
-                    _ => None,
-                })
-                .collect()
-        })
-        .collect()
-}
+type Floor = Grid<Option<Cucumber>>;
 
-fn move_right(floor: &mut Floor) -> usize {
-    let len = floor[0].len();
+fn parse_input(input: &str) -> Result<Floor> {
+    Grid::parse(input, |c| match c {
+        '>' => Some(Some(Right)),
+        'v' => Some(Some(Down)),
+        '.' => Some(None),
+        _ => None,
+    })
+}
 
+/// the whole herd looks ahead before any of its cucumbers moves,
+/// those leaving the floor by one side come back by the other ;
+/// gives where the cucumbers which moved are now
+fn move_herd(floor: &mut Floor, herd: Cucumber) -> Vec<Point> {
+    let direction = match herd {
+        Right => (0, 1),
+        Down => (1, 0),
+    };
     let moves: Vec<_> = floor
         .iter()
-        .enumerate()
-        .flat_map(|(i, l)| {
-            l.iter().enumerate().filter_map(move |(j, c)| {
-                let len = l.len();
-                match c {
-                    Some(Right) => {
-                        if l[(j + 1) % len].is_none() {
-                            Some((i, j))
-                        } else {
-                            None
-                        }
-                    }
-                    _ => None,
-                }
-            })
-        })
+        .filter(|(_, c)| **c == Some(herd))
+        .map(|(p, _)| (p, floor.wrapped(p, direction)))
+        .filter(|(_, next)| floor[*next].is_none())
         .collect();
-    let mut count = 0;
-    for (i, j) in moves {
-        count += 1;
-        floor[i][(j + 1) % len] = floor[i][j].take();
+    for (p, next) in &moves {
+        floor[*next] = floor[*p].take();
     }
-    count
+    moves.into_iter().map(|(_, next)| next).collect()
 }
-fn move_down(floor: &mut Floor) -> usize {
-    let depth = floor.len();
-
-    let the_floor: &Floor = floor;
 
-    let moves: Vec<_> = floor
-        .iter()
-        .enumerate()
-        .flat_map(|(i, l)| {
-            l.iter().enumerate().filter_map(move |(j, c)| match c {
-                Some(Down) => {
-                    if the_floor[(i + 1) % depth][j].is_none() {
-                        Some((i, j))
-                    } else {
-                        None
-                    }
-                }
-                _ => None,
-            })
-        })
-        .collect();
-    let mut count = 0;
-    for (i, j) in moves {
-        count += 1;
-        floor[(i + 1) % depth][j] = floor[i][j].take();
+/// cucumbers which moved during the step are highlighted
+fn draw_floor(floor: &Floor, step: usize, moved: &[Point]) -> String {
+    let mut frame = format!("step {} : {} cucumbers moved\n", step, moved.len());
+    let moved: HashSet<_> = moved.iter().collect();
+    for (p, cucumber) in floor.iter() {
+        let c = match cucumber {
+            Some(Right) => ">",
+            Some(Down) => "v",
+            None => ".",
+        };
+        if moved.contains(&p) {
+            write!(frame, "{}", c.bold().green()).unwrap();
+        } else {
+            write!(frame, "{}", c.dimmed()).unwrap();
+        }
+        if p.1 + 1 == floor.width() {
+            frame.push('\n');
+        }
     }
-    count
+    frame
 }
 
-fn count_steps_before_static(input: &str) -> usize {
-    let mut floor = parse_input(input);
+/// the floor once no cucumber can move anymore, and the number of steps to get there
+fn settle(floor: &Floor) -> (Floor, usize) {
+    let mut floor = floor.clone();
     let mut count = 0;
 
     loop {
         count += 1;
 
-        let mut moved = 0;
-        moved += move_right(&mut floor);
-        moved += move_down(&mut floor);
+        let moved = move_herd(&mut floor, Right).len() + move_herd(&mut floor, Down).len();
         if moved == 0 {
             break;
         }
     }
 
-    count
+    (floor, count)
 }
 
-pub fn find_spot_on_sea_floor() {
-    let input = include_str!("../resources/day25_sea_floor.txt");
-    println!(
-        "cucumbers stop moving after {} steps",
-        count_steps_before_static(input)
-    );
+fn count_steps_before_static(floor: &Floor) -> usize {
+    settle(floor).1
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub struct SeaFloor {
+    floor: Floor,
+}
 
-    #[test]
-    fn aoc_examples_work() {
-        let input = "v...>>.vv>
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "sea cucumbers",
+    input: "v...>>.vv>
 .vv>>.vv..
 >>.>v>...v
 >>v>>.>.v.
@@ -119,8 +102,99 @@ v>v.vv.v..
 >.>>..v...
 .vv..>.>v.
 v.v..>>v.v
-....v..v.>";
+....v..v.>",
+    part1: Some("58"),
+    part2: None,
+}];
+
+impl Solver for SeaFloor {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            floor: parse_input(input)?,
+        })
+    }
+
+    fn part1(&self) -> Answer {
+        count_steps_before_static(&self.floor).into()
+    }
+
+    // the last star is given once all others are collected
+    fn part2(&self) -> Answer {
+        Answer::None
+    }
+
+    // the last row and column are full, so that no cucumber can go round the floor forever
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let last = size.max(2) - 1;
+        Grid::from_fn(last + 1, last + 1, |(row, col)| match rng.below(3) {
+            _ if row == last => '>',
+            _ if col == last => 'v',
+            0 => '>',
+            1 => 'v',
+            _ => '.',
+        })
+        .to_string()
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("cucumbers stop moving after {} steps", answer),
+            Part::Two => "no second part on christmas day".to_string(),
+        }
+    }
+
+    // the floor once the herds stopped
+    fn picture(&self) -> Option<Grid<Color>> {
+        Some(settle(&self.floor).0.map(|cucumber| match cucumber {
+            Some(Right) => [240, 140, 40],
+            Some(Down) => [40, 200, 170],
+            None => [10, 30, 70],
+        }))
+    }
+
+    // until the first step during which no cucumber moves
+    fn frames(&self) -> Option<Frames<'_>> {
+        let mut floor = self.floor.clone();
+        let first = draw_floor(&floor, 0, &[]);
+        let mut stopped = false;
+        let steps = (1..).map_while(move |step| {
+            if stopped {
+                return None;
+            }
+            let mut moved = move_herd(&mut floor, Right);
+            moved.extend(move_herd(&mut floor, Down));
+            stopped = moved.is_empty();
+            Some(draw_floor(&floor, step, &moved))
+        });
+        Some(Box::new(std::iter::once(first).chain(steps)))
+    }
+}
+
+pub fn find_spot_on_sea_floor(input: &str, part: Part) {
+    print_answer::<SeaFloor>(input, part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::ParseError;
+
+    #[test]
+    fn aoc_examples_work() {
+        let input = EXAMPLES[0].input;
+
+        assert_eq!(58, count_steps_before_static(&parse_input(input).unwrap()));
+
+        colored::control::set_override(false);
+        let frames: Vec<_> = SeaFloor::parse(input).unwrap().frames().unwrap().collect();
+        assert_eq!(59, frames.len());
+        assert!(frames[1].starts_with("step 1 : "));
+        assert!(frames[58].starts_with("step 58 : 0 cucumbers moved\n"));
 
-        assert_eq!(58, count_steps_before_static(input));
+        let err = parse_input("v..\n.x>\n").unwrap_err();
+        assert_eq!(
+            &ParseError::at(2, 2, "unexpected 'x'"),
+            err.downcast_ref::<ParseError>().unwrap()
+        );
     }
 }
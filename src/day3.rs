@@ -1,123 +1,376 @@
-use itertools::Itertools;
+use crate::parse::ParseError;
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver, Variant};
+use crate::trace::debug;
+use anyhow::{anyhow, Result};
+use rayon::prelude::*;
+use std::cell::OnceCell;
+use std::collections::HashSet;
+use std::fmt::{self, Display};
 
-#[derive(Debug)]
-struct Diagnosis {
-    gamma: usize,
-    epsilon: usize,
-    oxygen: usize,
-    co2: usize,
+/// bit string of any width packed in words, its first bit being the most significant
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Bits {
+    words: Vec<u64>,
+    len: usize,
 }
 
-impl Diagnosis {
-    fn _epsilon(length: usize, gamma: usize) -> usize {
-        (!gamma) & ((1 << length) - 1)
+impl Bits {
+    pub fn zeros(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
     }
 
-    pub fn select_diag_from_rating(
-        diag_bits: &[Vec<u8>],
-        selector: impl Fn(u8, u8) -> bool,
-    ) -> usize {
-        let mut candidates: Vec<bool> = vec![true; diag_bits.len()];
-        let mut number_of_candidates = diag_bits.len();
+    pub fn from_bools(bits: impl IntoIterator<Item = bool>) -> Self {
+        let mut packed = Bits::default();
+        bits.into_iter().for_each(|bit| packed.push(bit));
+        packed
+    }
 
-        let diag_len = diag_bits[0].len();
-        for idx in 0..diag_len {
-            let bit_counts = diag_bits
-                .iter()
-                .enumerate()
-                .filter(|(idx, _)| candidates[*idx])
-                .map(|(_, bit)| bit[idx])
-                .fold((0, 0), |acc, val| match val {
-                    0 => (acc.0 + 1, acc.1),
-                    1 => (acc.0, acc.1 + 1),
-                    _ => acc,
-                });
-            let most_common_bit = if bit_counts.0 > bit_counts.1 { 0 } else { 1 };
-            for cand_idx in 0..diag_bits.len() {
-                if candidates[cand_idx] && !selector(diag_bits[cand_idx][idx], most_common_bit) {
-                    candidates[cand_idx] = false;
-                    number_of_candidates -= 1;
-                }
-                if number_of_candidates < 2 {
-                    break;
-                }
+    pub fn push(&mut self, bit: bool) {
+        if self.len.is_multiple_of(64) {
+            self.words.push(0);
+        }
+        self.words[self.len / 64] |= (bit as u64) << (self.len % 64);
+        self.len += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        assert!(index < self.len, "bit {} of {}", index, self.len);
+        self.words[index / 64] >> (index % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, index: usize, bit: bool) {
+        assert!(index < self.len, "bit {} of {}", index, self.len);
+        let mask = 1 << (index % 64);
+        if bit {
+            self.words[index / 64] |= mask;
+        } else {
+            self.words[index / 64] &= !mask;
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|i| self.get(i))
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// none when the value does not fit
+    pub fn to_usize(&self) -> Option<usize> {
+        self.iter().try_fold(0usize, |acc, bit| {
+            acc.checked_mul(2)?.checked_add(bit as usize)
+        })
+    }
+
+    // base 10^9 digits, the least significant first
+    fn limbs(&self) -> Vec<u64> {
+        let mut limbs = vec![0];
+        for bit in self.iter() {
+            let mut carry = bit as u64;
+            for limb in limbs.iter_mut() {
+                let doubled = *limb * 2 + carry;
+                *limb = doubled % LIMB;
+                carry = doubled / LIMB;
+            }
+            if carry > 0 {
+                limbs.push(carry);
             }
         }
+        limbs
+    }
 
-        let (_, result) = diag_bits
-            .iter()
-            .enumerate()
-            .find(|(idx, _)| candidates[*idx])
-            .unwrap();
-        // dbg!(idx, result);
-        Diagnosis::from_bits_to_val(result)
-    }
-
-    pub fn from_bits_to_val(bits: &[u8]) -> usize {
-        bits.iter().fold(0usize, |acc, bit| acc * 2 + *bit as usize)
-    }
-
-    pub fn new(report: &str) -> Diagnosis {
-        let inputs = report
-            .lines()
-            .filter(|l| !l.is_empty())
-            .map(|l| {
-                l.split("")
-                    .filter_map(|d| d.parse::<u8>().ok())
-                    .collect_vec()
-            })
-            .collect_vec();
-
-        let length = inputs.iter().map(|l| l.len()).max().unwrap();
-        let gamma_bits = (0..length)
-            .map(|i| {
-                let (zeroes_nb, ones_nb) = inputs
-                    .iter()
-                    .map(|input| input[i])
-                    .map(|val| match val {
-                        0 => (1, 0),
-                        1 => (0, 1),
-                        _ => (0, 0),
-                    })
-                    .fold((0usize, 0usize), |acc, val| (acc.0 + val.0, acc.1 + val.1));
-                if zeroes_nb > ones_nb {
-                    0u8
-                } else {
-                    1u8
-                } // This will ensure than 1 is considered if counts  are equal
-            })
-            .collect_vec();
-        let gamma: usize = Diagnosis::from_bits_to_val(&gamma_bits);
-        let epsilon = Diagnosis::_epsilon(length, gamma);
-
-        let oxygen = Diagnosis::select_diag_from_rating(&inputs, |val, most| val == most);
-        let co2 = Diagnosis::select_diag_from_rating(&inputs, |val, most| val != most);
-
-        Diagnosis {
-            gamma,
-            epsilon,
-            oxygen,
-            co2,
-        }
-    }
-}
-
-pub fn print_power() {
-    let report = include_str!("../resources/day3_diagnosis.txt");
-    let diag = Diagnosis::new(report);
-    println!("diagnosis : {:?}", diag);
-    println!("power : {:?}", diag.gamma * diag.epsilon);
-    println!("oxygen*co2 : {:?}", diag.oxygen * diag.co2);
+    /// the value written in base 10, whatever its width
+    pub fn to_decimal(&self) -> String {
+        format_limbs(&self.limbs())
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl Display for Bits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bits: String = self.iter().map(|b| if b { '1' } else { '0' }).collect();
+        f.pad(&bits)
+    }
+}
 
-    #[test]
-    fn aoc_example() {
-        let report = "
-00100
+const LIMB: u64 = 1_000_000_000;
+
+fn format_limbs(limbs: &[u64]) -> String {
+    let mut limbs = limbs.iter().rev().skip_while(|l| **l == 0).peekable();
+    let Some(first) = limbs.next() else {
+        return "0".to_string();
+    };
+    let mut decimal = first.to_string();
+    for limb in limbs {
+        decimal.push_str(&format!("{:09}", limb));
+    }
+    decimal
+}
+
+/// product of two values too wide for usize, written in base 10
+fn decimal_product(a: &Bits, b: &Bits) -> String {
+    let (a, b) = (a.limbs(), b.limbs());
+    let mut product = vec![0u64; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0;
+        for (j, y) in b.iter().enumerate() {
+            let current = product[i + j] + x * y + carry;
+            product[i + j] = current % LIMB;
+            carry = current / LIMB;
+        }
+        product[i + b.len()] += carry;
+    }
+    format_limbs(&product)
+}
+
+fn product(a: &Bits, b: &Bits) -> Answer {
+    match (a.to_usize(), b.to_usize()) {
+        (Some(a), Some(b)) if a.checked_mul(b).is_some() => (a * b).into(),
+        _ => Answer::Text(decimal_product(a, b)),
+    }
+}
+
+/// bit kept when a column has as many 0s as 1s
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TiePolicy {
+    FavourOne,
+    FavourZero,
+}
+
+impl TiePolicy {
+    fn bit(self) -> bool {
+        self == TiePolicy::FavourOne
+    }
+}
+
+/// tie policy of each rating, the puzzle favouring 1 for gamma and oxygen, and 0 for the others
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TiePolicies {
+    pub gamma: TiePolicy,
+    pub epsilon: TiePolicy,
+    pub oxygen: TiePolicy,
+    pub co2: TiePolicy,
+}
+
+impl Default for TiePolicies {
+    fn default() -> Self {
+        Self {
+            gamma: TiePolicy::FavourOne,
+            epsilon: TiePolicy::FavourZero,
+            oxygen: TiePolicy::FavourOne,
+            co2: TiePolicy::FavourZero,
+        }
+    }
+}
+
+/// bit counts of a column of the report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ColumnStats {
+    pub zeros: usize,
+    pub ones: usize,
+}
+
+impl ColumnStats {
+    // a popcount of the column words
+    fn count(column: &Bits) -> Self {
+        let ones = column.count_ones();
+        Self {
+            zeros: column.len() - ones,
+            ones,
+        }
+    }
+
+    pub fn most_common(&self, tie: TiePolicy) -> bool {
+        match self.ones.cmp(&self.zeros) {
+            std::cmp::Ordering::Equal => tie.bit(),
+            ordering => ordering.is_gt(),
+        }
+    }
+
+    pub fn least_common(&self, tie: TiePolicy) -> bool {
+        match self.ones.cmp(&self.zeros) {
+            std::cmp::Ordering::Equal => tie.bit(),
+            ordering => ordering.is_lt(),
+        }
+    }
+}
+
+/// the report is stored column by column, bit r of a column being the one of row r ;
+/// ratings are only computed when asked for, the column counts once
+#[derive(Debug)]
+pub struct Diagnosis {
+    report: Vec<Bits>,
+    ties: TiePolicies,
+    columns: OnceCell<Vec<ColumnStats>>,
+}
+
+// each non blank line is a row, its bits being pushed on their column
+fn parse_columns(report: &str) -> Result<Vec<Bits>> {
+    let mut columns: Vec<Bits> = vec![];
+    let mut rows = 0;
+    for (i, line) in report.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let width = line.chars().count();
+        if rows == 0 {
+            columns = vec![Bits::default(); width];
+        } else if width != columns.len() {
+            return Err(ParseError::new(
+                i + 1,
+                format!(
+                    "{} cells while previous lines have {}",
+                    width,
+                    columns.len()
+                ),
+            )
+            .into());
+        }
+        for (j, (c, column)) in line.chars().zip(&mut columns).enumerate() {
+            match c {
+                '0' => column.push(false),
+                '1' => column.push(true),
+                _ => return Err(ParseError::at(i + 1, j + 1, format!("unexpected '{}'", c)).into()),
+            }
+        }
+        rows += 1;
+    }
+    if rows == 0 {
+        return Err(anyhow!("no grid found"));
+    }
+    Ok(columns)
+}
+
+fn row(report: &[Bits], index: usize) -> Bits {
+    Bits::from_bools(report.iter().map(|column| column.get(index)))
+}
+
+// rows having a 0 moved before the rows having a 1, the number of the former being returned
+fn partition(rows: &mut [usize], column: &Bits) -> usize {
+    let mut zeros = 0;
+    for i in 0..rows.len() {
+        if !column.get(rows[i]) {
+            rows.swap(zeros, i);
+            zeros += 1;
+        }
+    }
+    zeros
+}
+
+/// keeps the rows having the wanted bit, column after column, by splitting the candidates
+/// in place between those having a 0 and those having a 1
+fn select_rating(report: &[Bits], selector: impl Fn(&ColumnStats) -> bool) -> Bits {
+    let mut rows: Vec<usize> = (0..report[0].len()).collect();
+    // the candidates are rows[start..end]
+    let (mut start, mut end) = (0, rows.len());
+    for column in report {
+        // the last row standing is the rating, whatever its next bits
+        if end - start < 2 {
+            break;
+        }
+        let split = start + partition(&mut rows[start..end], column);
+        let wanted = selector(&ColumnStats {
+            zeros: split - start,
+            ones: end - split,
+        });
+        // a column on which every candidate agrees cannot split them
+        match wanted {
+            true if split < end => start = split,
+            false if split > start => end = split,
+            _ => {}
+        }
+    }
+    let rating = row(report, rows[start]);
+    debug!("rating bits {}", rating);
+    rating
+}
+
+// same selection, rescanning the remaining candidates for each column
+fn filter_rating(report: &[Bits], selector: impl Fn(&ColumnStats) -> bool) -> Bits {
+    let mut candidates: Vec<usize> = (0..report[0].len()).collect();
+    for column in report {
+        if candidates.len() < 2 {
+            break;
+        }
+        let ones = candidates.iter().filter(|row| column.get(**row)).count();
+        let wanted = selector(&ColumnStats {
+            zeros: candidates.len() - ones,
+            ones,
+        });
+        if candidates.iter().any(|row| column.get(*row) == wanted) {
+            candidates.retain(|row| column.get(*row) == wanted);
+        }
+    }
+    row(report, candidates[0])
+}
+
+impl Diagnosis {
+    pub fn new(report: &str) -> Result<Diagnosis> {
+        Diagnosis::with_ties(report, TiePolicies::default())
+    }
+
+    pub fn with_ties(report: &str, ties: TiePolicies) -> Result<Diagnosis> {
+        Ok(Diagnosis {
+            report: parse_columns(report)?,
+            ties,
+            columns: OnceCell::new(),
+        })
+    }
+
+    fn filter_ratings(&self) -> Answer {
+        let oxygen = filter_rating(&self.report, |column| column.most_common(self.ties.oxygen));
+        let co2 = filter_rating(&self.report, |column| column.least_common(self.ties.co2));
+        product(&oxygen, &co2)
+    }
+
+    pub fn gamma(&self) -> Bits {
+        Bits::from_bools(
+            self.columns()
+                .iter()
+                .map(|c| c.most_common(self.ties.gamma)),
+        )
+    }
+
+    pub fn epsilon(&self) -> Bits {
+        Bits::from_bools(
+            self.columns()
+                .iter()
+                .map(|c| c.least_common(self.ties.epsilon)),
+        )
+    }
+
+    pub fn oxygen(&self) -> Bits {
+        select_rating(&self.report, |column| column.most_common(self.ties.oxygen))
+    }
+
+    pub fn co2(&self) -> Bits {
+        select_rating(&self.report, |column| column.least_common(self.ties.co2))
+    }
+
+    /// bit counts of each column, from the most significant one, counted in parallel
+    pub fn columns(&self) -> &[ColumnStats] {
+        self.columns
+            .get_or_init(|| self.report.par_iter().map(ColumnStats::count).collect())
+    }
+}
+
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "diagnostic report",
+    input: "00100
 11110
 10110
 10111
@@ -128,17 +381,186 @@ mod tests {
 10000
 11001
 00010
-01010
-";
-        // 10110
-        assert_eq!(1, Diagnosis::_epsilon(5, 0b11110));
-
-        let full_diag = Diagnosis::new(report);
-        assert_eq!(22, full_diag.gamma);
-        assert_eq!(9, full_diag.epsilon);
-        assert_eq!(198, full_diag.gamma * full_diag.epsilon);
-
-        assert_eq!(23, full_diag.oxygen);
-        assert_eq!(10, full_diag.co2);
+01010",
+    part1: Some("198"),
+    part2: Some("230"),
+}];
+
+impl Solver for Diagnosis {
+    // ratings no longer end on an arbitrary number when the candidates share a bit
+    const VERSION: u32 = 2;
+
+    fn parse(report: &str) -> Result<Self> {
+        Diagnosis::new(report)
+    }
+
+    fn part1(&self) -> Answer {
+        product(&self.gamma(), &self.epsilon())
+    }
+
+    fn part2(&self) -> Answer {
+        product(&self.oxygen(), &self.co2())
+    }
+
+    // at least 2 distinct values, wide enough for size of them, so that ratings always end on one
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let width = (usize::BITS - (size * 4).leading_zeros()).max(5) as usize;
+        let mut values: Vec<usize> = vec![];
+        let mut drawn = HashSet::new();
+        while values.len() < size.max(2) {
+            let value = rng.below(1 << width);
+            if drawn.insert(value) {
+                values.push(value);
+            }
+        }
+        values
+            .iter()
+            .map(|v| format!("{:0width$b}\n", v, width = width))
+            .collect()
+    }
+
+    fn variants() -> Vec<Variant<Self>> {
+        vec![Variant {
+            name: "row filtering",
+            part: Part::Two,
+            solve: Diagnosis::filter_ratings,
+        }]
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("power : {}", answer),
+            Part::Two => format!("oxygen*co2 : {}", answer),
+        }
+    }
+}
+
+pub fn print_power(report: &str, part: Part) {
+    print_answer::<Diagnosis>(report, part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(bits: &Bits) -> usize {
+        bits.to_usize().unwrap()
+    }
+
+    #[test]
+    fn aoc_example() {
+        let report = EXAMPLES[0].input;
+        let full_diag = Diagnosis::new(report).unwrap();
+        assert_eq!(22, value(&full_diag.gamma()));
+        assert_eq!(9, value(&full_diag.epsilon()));
+        assert_eq!(Answer::Number(198), full_diag.part1());
+
+        assert_eq!(23, value(&full_diag.oxygen()));
+        assert_eq!(10, value(&full_diag.co2()));
+        assert_eq!(ColumnStats { zeros: 5, ones: 7 }, full_diag.columns()[0]);
+        assert_eq!(Answer::Number(230), full_diag.filter_ratings());
+    }
+
+    #[test]
+    fn reports_are_packed_by_column() {
+        let report = parse_columns("001\n\n111\n011\n").unwrap();
+        assert_eq!(3, report.len());
+        assert_eq!("010", report[0].to_string());
+        assert_eq!("111", report[2].to_string());
+        assert_eq!(
+            ColumnStats { zeros: 1, ones: 2 },
+            ColumnStats::count(&report[1])
+        );
+        assert_eq!("011", row(&report, 2).to_string());
+
+        let mut rows = vec![0, 1, 2];
+        assert_eq!(1, partition(&mut rows, &report[1]));
+        assert_eq!(0, rows[0]);
+
+        for (report, message) in [
+            ("01\n0x\n", "line 2, column 2 : unexpected 'x'"),
+            ("01\n011\n", "line 2 : 3 cells while previous lines have 2"),
+            ("\n", "no grid found"),
+        ] {
+            assert_eq!(message, parse_columns(report).err().unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn ratings_stop_at_the_last_number() {
+        let diag = Diagnosis::new("01111\n10100\n").unwrap();
+        assert_eq!(0b10100, value(&diag.oxygen()));
+        assert_eq!(0b01111, value(&diag.co2()));
+    }
+
+    #[test]
+    fn shared_bits_do_not_split_ratings() {
+        let diag = Diagnosis::new("100\n101\n110\n").unwrap();
+        assert_eq!("101", diag.oxygen().to_string());
+        assert_eq!("110", diag.co2().to_string());
+    }
+
+    #[test]
+    fn ties_follow_the_policies() {
+        let report = "0110\n1001\n";
+        let diag = Diagnosis::new(report).unwrap();
+        assert_eq!("1111", diag.gamma().to_string());
+        assert_eq!("0000", diag.epsilon().to_string());
+        assert_eq!("1001", diag.oxygen().to_string());
+        assert_eq!("0110", diag.co2().to_string());
+
+        let ties = TiePolicies {
+            gamma: TiePolicy::FavourZero,
+            epsilon: TiePolicy::FavourOne,
+            oxygen: TiePolicy::FavourZero,
+            co2: TiePolicy::FavourOne,
+        };
+        let diag = Diagnosis::with_ties(report, ties).unwrap();
+        assert_eq!("0000", diag.gamma().to_string());
+        assert_eq!("1111", diag.epsilon().to_string());
+        assert_eq!("0110", diag.oxygen().to_string());
+        assert_eq!("1001", diag.co2().to_string());
+    }
+
+    #[test]
+    fn reports_can_be_wider_than_usize() {
+        let ones = "1".repeat(70);
+        let report = format!("{}\n0{}\n", ones, &ones[1..]);
+        let diag = Diagnosis::new(&report).unwrap();
+        assert_eq!(70, diag.gamma().len());
+        assert_eq!(70, diag.gamma().count_ones());
+        assert_eq!(None, diag.gamma().to_usize());
+        assert_eq!("1180591620717411303423", diag.gamma().to_decimal());
+        assert_eq!(ColumnStats { zeros: 1, ones: 1 }, diag.columns()[0]);
+        // (2^70 - 1) * (2^69 - 1)
+        assert_eq!(
+            Answer::Text("696898287454081973171220308589185180106753".to_string()),
+            diag.part2()
+        );
+    }
+
+    #[test]
+    fn bits_are_packed() {
+        let mut bits = Bits::zeros(130);
+        bits.set(0, true);
+        bits.set(129, true);
+        assert!(bits.get(0) && bits.get(129) && !bits.get(64));
+        assert_eq!(2, bits.count_ones());
+        assert_eq!(
+            Some(0b1011),
+            Bits::from_bools([true, false, true, true]).to_usize()
+        );
+        assert_eq!("0", Bits::zeros(3).to_decimal());
+        assert_eq!(
+            "1",
+            decimal_product(&Bits::from_bools([true]), &Bits::from_bools([true]))
+        );
+    }
+
+    #[test]
+    fn generated_reports_have_distinct_numbers() {
+        let report = Diagnosis::generate(&mut Rng::new(3), 20_000);
+        let numbers: HashSet<_> = report.lines().collect();
+        assert_eq!(20_000, numbers.len());
     }
 }
@@ -1,3 +1,9 @@
+use crate::parse::{parse_numbered_lines, parse_separated, ParseError};
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver};
+use crate::trace::{debug, trace};
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use std::fmt::{self, Display};
 
@@ -15,6 +21,7 @@ impl Display for BingoResult {
     }
 }
 
+#[derive(Clone)]
 pub(crate) struct BingoBoard {
     // Each time a number is found, replace it with None
     grid: Vec<Vec<Option<usize>>>,
@@ -48,33 +55,38 @@ impl fmt::Debug for BingoBoard {
 }
 
 impl BingoBoard {
-    pub(crate) fn new(lines: &[&str]) -> BingoBoard {
-        let grid = lines
-            .iter()
-            .map(|line| {
-                line.split_whitespace()
-                    .map(|val| val.parse::<usize>().ok())
-                    .collect_vec()
-            })
-            .filter(|v| !v.is_empty())
-            .collect_vec();
+    /// lines are given with their index in the whole input
+    pub(crate) fn new(lines: &[(usize, &str)]) -> Result<BingoBoard> {
+        let grid: Vec<Vec<Option<usize>>> = parse_numbered_lines(lines.iter().copied(), |line| {
+            line.split_whitespace()
+                .map(|val| Ok(Some(val.parse::<usize>()?)))
+                .collect()
+        })?;
         let lenght = grid[0].len();
-        if grid.iter().any(|l| l.len() != lenght) {
-            panic!("grid from {:?} is not regular : {:?}", lines, grid);
+        if let Some(irregular) = grid.iter().position(|l| l.len() != lenght) {
+            return Err(ParseError::new(
+                lines[irregular].0 + 1,
+                format!(
+                    "board line of {} numbers instead of {}",
+                    grid[irregular].len(),
+                    lenght
+                ),
+            )
+            .into());
         }
 
-        BingoBoard {
+        Ok(BingoBoard {
             grid,
             round_nb: 0,
             score: None,
-        }
+        })
     }
 
     pub(crate) fn compute_score(&mut self, last_played: usize) -> Option<usize> {
         let finished_line = self.grid.iter().find(|l| l.iter().all(|v| v.is_none()));
 
-        if finished_line.is_some() {
-            // dbg!(&finished_line);
+        if let Some(line) = finished_line {
+            trace!("line {:?} finished", line);
         }
 
         let finished_column = finished_line.is_some() || {
@@ -84,16 +96,14 @@ impl BingoBoard {
                 .collect_vec();
 
             let finished = columns.iter().find(|c| c.iter().all(|v| v.is_none()));
-            if finished.is_some() {
-                // dbg!(&finished);
-                // dbg!(&self);
+            if let Some(column) = finished {
+                trace!("column {:?} finished", column);
             }
 
             finished.is_some()
         };
 
         if finished_line.is_some() || finished_column {
-            // dbg!(finished_line, finished_column);
             let sum: usize = self
                 .grid
                 .iter()
@@ -123,47 +133,43 @@ impl BingoBoard {
     }
 }
 
-struct BingoGame {
+#[derive(Clone)]
+pub struct BingoGame {
     drawns: Vec<usize>,
     boards: Vec<BingoBoard>,
 }
 
 impl BingoGame {
-    pub fn new(bingo: &str) -> BingoGame {
-        let lines = bingo.lines().collect_vec();
-        // dbg!(&lines[0]);
-        let drawns = lines[0]
-            .split(',')
-            .filter_map(|val| val.parse::<usize>().ok())
-            .collect_vec();
+    pub fn new(bingo: &str) -> Result<BingoGame> {
+        let mut lines = bingo.lines().enumerate();
+        let (_, first_line) = lines.next().ok_or_else(|| anyhow!("empty bingo"))?;
+        let drawns = parse_separated(first_line.trim_end(), 0, ',')?;
 
         // boards are separated by empty lines
-        // ==> boards boundaries are non consecutive empty lines
-        let mut last_non_empty_line = None;
-        let mut last_empty_line = None;
         let mut boards: Vec<BingoBoard> = Vec::new();
-        for (idx, line) in lines[1..].iter().enumerate() {
-            // dbg!(idx, line);
-            if line.is_empty() {
-                last_empty_line = Some(idx);
-                if let Some(start_idx) = last_non_empty_line {
-                    boards.push(BingoBoard::new(&lines[start_idx..idx + 1])); // tricky :because of init offset
-                    last_non_empty_line = None;
+        let mut board_lines = vec![];
+        for (idx, line) in lines {
+            if line.trim().is_empty() {
+                if !board_lines.is_empty() {
+                    boards.push(BingoBoard::new(&board_lines)?);
+                    board_lines.clear();
                 }
-            } else if last_non_empty_line.is_none() {
-                last_non_empty_line = Some(idx + 1); // FIXME : tricky
+            } else {
+                board_lines.push((idx, line));
             }
         }
-        if last_non_empty_line.unwrap() > last_empty_line.unwrap() {
-            boards.push(BingoBoard::new(&lines[last_non_empty_line.unwrap()..]));
+        if !board_lines.is_empty() {
+            boards.push(BingoBoard::new(&board_lines)?);
         }
-        BingoGame { drawns, boards }
+        if boards.is_empty() {
+            return Err(anyhow!("no bingo board"));
+        }
+        Ok(BingoGame { drawns, boards })
     }
 }
 
-pub fn play_bingo(bingo: &str) -> (BingoResult, BingoResult) {
-    let mut bg = BingoGame::new(bingo);
-    // dbg!("drawns : {}, boards :{}", &bg.drawns ,bg.boards.len());
+pub fn play_bingo(mut bg: BingoGame) -> (BingoResult, BingoResult) {
+    debug!("drawns : {}, boards : {}", bg.drawns.len(), bg.boards.len());
     let mut first_winner = None;
     let mut last_winner = None;
 
@@ -171,9 +177,7 @@ pub fn play_bingo(bingo: &str) -> (BingoResult, BingoResult) {
         let ranked_scores = bg
             .boards
             .iter_mut()
-            .enumerate()
-            .map(|(_idx, board)| {
-                // println!("play( {}, {})", idx, drawn);
+            .map(|board| {
                 board.play(drawn);
                 (board.round_nb, board.score)
             })
@@ -184,7 +188,7 @@ pub fn play_bingo(bingo: &str) -> (BingoResult, BingoResult) {
             .filter_map(|(idx, (rank, score))| score.map(|s| (idx, *rank, s)))
             .max_by(|(_idx, rank1, _score), (_, rank2, _)| rank1.cmp(rank2));
         if let Some((winner_idx, _rank, winner_score)) = best {
-            // dbg!(drawn, winner_idx);
+            debug!("board {} wins with {}", winner_idx, drawn);
             last_winner = Some(BingoResult {
                 winner_idx,
                 winner_score,
@@ -201,21 +205,9 @@ pub fn play_bingo(bingo: &str) -> (BingoResult, BingoResult) {
     (first_winner.unwrap(), last_winner.unwrap())
 }
 
-pub fn display_bingo() {
-    let bingo = include_str!("../resources/day4_bingo.txt");
-    let result = play_bingo(bingo);
-    println!(
-        "***Bingo results : ***\n* first win :{}\n* last win : {}",
-        result.0, result.1
-    );
-}
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn aoc_example_works() {
-        let bingo = "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "bingo subsystem",
+    input: "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
 
 22 13 17 11  0
  8  2 23  4 24
@@ -233,12 +225,74 @@ mod tests {
 10 16 15  9 19
 18  8 23 26 20
 22 11 13  6  5
- 2  0 12  3  7
-        ";
-        let (first, last) = play_bingo(bingo);
+ 2  0 12  3  7",
+    part1: Some("4512"),
+    part2: Some("1924"),
+}];
+
+impl Solver for BingoGame {
+    fn parse(bingo: &str) -> Result<Self> {
+        BingoGame::new(bingo)
+    }
+
+    fn part1(&self) -> Answer {
+        play_bingo(self.clone()).0.winner_score.into()
+    }
+
+    fn part2(&self) -> Answer {
+        play_bingo(self.clone()).1.winner_score.into()
+    }
+
+    // size boards, every number is drawn so that all of them win
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut draws: Vec<usize> = (0..100).collect();
+        rng.shuffle(&mut draws);
+        let mut input = draws.iter().join(",") + "\n";
+        for _ in 0..size.max(1) {
+            let mut numbers: Vec<usize> = (0..100).collect();
+            rng.shuffle(&mut numbers);
+            input.push('\n');
+            for row in numbers[..25].chunks(5) {
+                input += &row.iter().map(|n| format!("{:2}", n)).join(" ");
+                input.push('\n');
+            }
+        }
+        input
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("first win score : {}", answer),
+            Part::Two => format!("last win score : {}", answer),
+        }
+    }
+}
+pub fn display_bingo(bingo: &str, part: Part) {
+    print_answer::<BingoGame>(bingo, part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aoc_example_works() {
+        let bingo = EXAMPLES[0].input;
+        let (first, last) = play_bingo(BingoGame::new(bingo).unwrap());
         assert_eq!(2, first.winner_idx, "bad first winner idx");
         assert_eq!(4512, first.winner_score, "bad firstscore");
         assert_eq!(1, last.winner_idx, "bad last winner idx");
         assert_eq!(1924, last.winner_score, "bad last score");
     }
+
+    #[test]
+    fn malformed_boards_are_reported() {
+        let err = BingoGame::new("7,4,9\n\n22 13\n 8  2 23\n").err().unwrap();
+        assert_eq!(
+            &ParseError::new(4, "board line of 3 numbers instead of 2"),
+            err.downcast_ref::<ParseError>().unwrap()
+        );
+        assert!(BingoGame::new("7,x,9\n\n22 13\n 8  2\n").is_err());
+        assert!(BingoGame::new("7,4,9\n").is_err());
+    }
 }
@@ -1,3 +1,11 @@
+use crate::export::{blend, Color, BLACK};
+use crate::grid::Grid;
+use crate::parse::parse_lines;
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver};
+use crate::trace::debug;
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use std::cmp::{max, min};
 
@@ -25,17 +33,17 @@ impl Vent {
         max(max(self.x1, self.x2), max(self.y1, self.y2))
     }
 
-    // Could do try_new(&str) -> Option<Vent>
-    pub fn new(line: &str) -> Self {
-        if let Some((x1, y1, x2, y2)) = line
+    pub fn try_new(line: &str) -> Result<Self> {
+        let coords = line
             .split("->")
             .flat_map(|coords| coords.split(','))
-            .map(|val| val.trim().parse::<usize>().unwrap())
-            .collect_tuple()
-        {
-            return Vent { x1, y1, x2, y2 };
+            .map(|val| val.trim().parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some((x1, y1, x2, y2)) = coords.into_iter().collect_tuple() {
+            Ok(Vent { x1, y1, x2, y2 })
+        } else {
+            Err(anyhow!("expecting 'x1,y1 -> x2,y2'"))
         }
-        panic!("could not parse a Vent from {}", line)
     }
     pub fn points(&self, dir: Directions) -> Vec<Point> {
         if self.x1 == self.x2 {
@@ -49,17 +57,8 @@ impl Vent {
                 .map(|v| Point { x: v, y: self.y1 })
                 .collect();
         } else if dir == Directions::HorzVertDiag && {
-            // abs for usize...
-            let dx = if self.x1 < self.x2 {
-                self.x2 - self.x1
-            } else {
-                self.x1 - self.x2
-            };
-            let dy = if self.y1 < self.y2 {
-                self.y2 - self.y1
-            } else {
-                self.y1 - self.y2
-            };
+            let dx = self.x2.abs_diff(self.x1);
+            let dy = self.y2.abs_diff(self.y1);
             dx == dy
         } {
             return (0..max(self.x1, self.x2) - min(self.x1, self.x2) + 1)
@@ -78,51 +77,42 @@ impl Vent {
                 })
                 .collect();
         }
-        // eprintln!("unknown direction for {:?}", self);
+        debug!("unknown direction for {:?}", self);
         vec![]
     }
 }
 
-fn parse_vents(vents: &str) -> Vec<Vent> {
-    vents.lines().map(Vent::new).collect_vec()
+fn parse_vents(vents: &str) -> Result<Vec<Vent>> {
+    parse_lines(vents, Vent::try_new)
 }
 
-fn count_overlapped_more_than_twice(vents: &str, dir: Directions) -> usize {
-    let vents = parse_vents(vents);
-    let map_size = vents.iter().map(|v| v.max()).max().unwrap() + 1;
+/// number of vents over each point, rows being y
+fn map_vents(vents: &[Vent], dir: Directions) -> Grid<usize> {
+    let map_size = vents.iter().map(|v| v.max()).max().unwrap_or(0) + 1;
 
-    let mut map = vec![vec![0usize; map_size]; map_size];
+    let mut map = Grid::new(map_size, map_size, 0usize);
 
     // lets mark map for each vent points
     for vent in vents {
         for Point { x, y } in vent.points(dir) {
-            map[x][y] += 1;
+            map[(y, x)] += 1;
         }
     }
+    map
+}
 
+fn count_overlapped_more_than_twice(vents: &[Vent], dir: Directions) -> usize {
     // filter values above 1
-    map.iter()
-        .flat_map(|l| l.iter())
-        .filter(|&&v| v > 1)
-        .count()
+    map_vents(vents, dir).values().filter(|&&v| v > 1).count()
 }
 
-pub fn print_hydrothermals() {
-    let vents = include_str!("../resources/day5_hydrothermal_vents.txt");
-    println!(
-        "considering horizontal and vertical lines only, {} places are overlapsed more than once",
-        count_overlapped_more_than_twice(vents, Directions::HorzVert)
-    );
-    println!("considering horizontal, vertical and diagonal lines, {} places are overlapsed more than once", count_overlapped_more_than_twice(vents, Directions::HorzVertDiag));
+pub struct HydrothermalVents {
+    vents: Vec<Vent>,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn aoc_example_works() {
-        let vents = "0,9 -> 5,9
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "vents",
+    input: "0,9 -> 5,9
 8,0 -> 0,8
 9,4 -> 3,4
 2,2 -> 2,1
@@ -131,15 +121,95 @@ mod tests {
 0,9 -> 2,9
 3,4 -> 1,4
 0,0 -> 8,8
-5,5 -> 8,2";
+5,5 -> 8,2",
+    part1: Some("5"),
+    part2: Some("12"),
+}];
+
+impl Solver for HydrothermalVents {
+    fn parse(vents: &str) -> Result<Self> {
+        Ok(Self {
+            vents: parse_vents(vents)?,
+        })
+    }
+
+    fn part1(&self) -> Answer {
+        count_overlapped_more_than_twice(&self.vents, Directions::HorzVert).into()
+    }
+
+    fn part2(&self) -> Answer {
+        count_overlapped_more_than_twice(&self.vents, Directions::HorzVertDiag).into()
+    }
+
+    // size vents on a 1000x1000 map, starting away from its borders
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let directions = [
+            (0, 1),
+            (1, 0),
+            (0, -1),
+            (-1, 0),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+            (-1, -1),
+        ];
+        let room = |v: isize, d: isize| match d {
+            1 => 999 - v,
+            -1 => v,
+            _ => isize::MAX,
+        };
+        (0..size)
+            .map(|_| {
+                let (x, y) = (rng.between(1, 998), rng.between(1, 998));
+                let (dx, dy) = *rng.pick(&directions);
+                let length = rng.between(1, 300).min(room(x, dx)).min(room(y, dy));
+                format!("{},{} -> {},{}\n", x, y, x + dx * length, y + dy * length)
+            })
+            .collect()
+    }
+
+    // the more vents overlap, the hotter
+    fn picture(&self) -> Option<Grid<Color>> {
+        let map = map_vents(&self.vents, Directions::HorzVertDiag);
+        let hottest = map.values().max().copied().unwrap_or(0);
+        Some(map.map(|count| match count {
+            0 => BLACK,
+            _ => blend(
+                [40, 0, 120],
+                [255, 220, 0],
+                (*count - 1) as f64 / (hottest - 1).max(1) as f64,
+            ),
+        }))
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("considering horizontal and vertical lines only, {} places are overlapsed more than once", answer),
+            Part::Two => format!("considering horizontal, vertical and diagonal lines, {} places are overlapsed more than once", answer),
+        }
+    }
+}
+
+pub fn print_hydrothermals(vents: &str, part: Part) {
+    print_answer::<HydrothermalVents>(vents, part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aoc_example_works() {
+        let vents = EXAMPLES[0].input;
+        let vents = parse_vents(vents).unwrap();
 
         assert_eq!(
             5,
-            count_overlapped_more_than_twice(vents, Directions::HorzVert)
+            count_overlapped_more_than_twice(&vents, Directions::HorzVert)
         );
         assert_eq!(
             12,
-            count_overlapped_more_than_twice(vents, Directions::HorzVertDiag)
+            count_overlapped_more_than_twice(&vents, Directions::HorzVertDiag)
         );
     }
 }
@@ -1,27 +1,34 @@
-use itertools::Itertools;
+use crate::parse::{parse_separated, ParseError};
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Frames, Solver, Variant};
+use anyhow::Result;
+use colored::Colorize;
+use std::fmt::Write;
 
-#[derive(Debug)]
-struct Lanternfishes {
+const BAR_WIDTH: usize = 50;
+
+#[derive(Debug, Clone)]
+pub struct Lanternfishes {
     count_by_ages: [usize; 9],
 }
 
 impl Lanternfishes {
     pub fn new(fishes: &[usize]) -> Lanternfishes {
-        println!("{:?} ... {:?}", &fishes[..3], &fishes[fishes.len() - 3..]);
         let mut count_by_ages = [0usize; 9];
         for fish in fishes {
             count_by_ages[*fish] += 1;
         }
         Lanternfishes { count_by_ages }
     }
-    pub fn parse(fishes: &str) -> Lanternfishes {
-        Self::new(
-            &fishes
-                .trim()
-                .split(',')
-                .filter_map(|v| v.parse::<usize>().ok())
-                .collect_vec(),
-        )
+    pub fn parse(fishes: &str) -> Result<Lanternfishes> {
+        let ages: Vec<usize> = parse_separated(fishes.trim(), 0, ',')?;
+        if let Some(idx) = ages.iter().position(|age| *age > 8) {
+            return Err(
+                ParseError::new(1, format!("fish #{} is older than 8 days", idx + 1)).into(),
+            );
+        }
+        Ok(Self::new(&ages))
     }
     pub fn count(&self) -> usize {
         self.count_by_ages.iter().sum()
@@ -37,33 +44,130 @@ impl Lanternfishes {
     }
 }
 
-fn count_lanternfishes_after(population: &str, duration: usize) -> usize {
-    let mut pop = Lanternfishes::parse(population);
+fn count_lanternfishes_after(population: &Lanternfishes, duration: usize) -> usize {
+    let mut pop = population.clone();
     for _ in 0..duration {
         pop.grow_1day();
     }
     pop.count()
 }
-pub fn print_lanternfishes_counts() {
-    let lanternfishes = include_str!("../resources/day6_lanternfishes.txt");
-    for d in [0, 80, 256] {
-        println!(
-            "after {} days, there are {} fishes",
-            d,
-            count_lanternfishes_after(lanternfishes, d)
-        );
+
+/// reference for count_lanternfishes_after, following every fish on its own
+fn count_each_lanternfish_after(population: &Lanternfishes, duration: usize) -> usize {
+    let mut fishes: Vec<usize> = (0..9)
+        .flat_map(|age| std::iter::repeat_n(age, population.count_by_ages[age]))
+        .collect();
+    for _ in 0..duration {
+        let newborns = fishes.iter().filter(|f| **f == 0).count();
+        for fish in &mut fishes {
+            *fish = if *fish == 0 { 6 } else { *fish - 1 };
+        }
+        fishes.extend(std::iter::repeat_n(8, newborns));
     }
+    fishes.len()
 }
 
-#[cfg(test)]
+/// one bar per age, scaled on the largest group, the fishes about to give birth highlighted
+fn draw_ages(population: &Lanternfishes, day: usize) -> String {
+    let mut frame = format!("day {} : {} fishes\n", day, population.count());
+    let largest = population.count_by_ages.iter().max().copied().unwrap_or(0);
+    for (age, count) in population.count_by_ages.iter().enumerate() {
+        let width = (*count as u128 * BAR_WIDTH as u128 / largest.max(1) as u128) as usize;
+        let bar = "#".repeat(width);
+        let bar = if age == 0 {
+            bar.bold().yellow()
+        } else {
+            bar.cyan()
+        };
+        writeln!(
+            frame,
+            "{} {}{} {}",
+            age,
+            bar,
+            " ".repeat(BAR_WIDTH - width),
+            count
+        )
+        .unwrap();
+    }
+    frame
+}
+
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "initial state",
+    input: "3,4,3,1,2",
+    part1: Some("5934"),
+    part2: Some("26984457539"),
+}];
+
+impl Solver for Lanternfishes {
+    fn parse(lanternfishes: &str) -> Result<Self> {
+        Lanternfishes::parse(lanternfishes)
+    }
+
+    fn part1(&self) -> Answer {
+        count_lanternfishes_after(self, 80).into()
+    }
+
+    fn part2(&self) -> Answer {
+        count_lanternfishes_after(self, 256).into()
+    }
 
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let ages: Vec<_> = (0..size).map(|_| rng.between(1, 5).to_string()).collect();
+        ages.join(",") + "\n"
+    }
+
+    fn variants() -> Vec<Variant<Self>> {
+        vec![Variant {
+            name: "each fish",
+            part: Part::One,
+            solve: |l| count_each_lanternfish_after(l, 80).into(),
+        }]
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("after 80 days, there are {} fishes", answer),
+            Part::Two => format!("after 256 days, there are {} fishes", answer),
+        }
+    }
+
+    // over the 256 days of the second part
+    fn frames(&self) -> Option<Frames<'_>> {
+        let mut population = self.clone();
+        Some(Box::new((0..=256).map(move |day| {
+            if day > 0 {
+                population.grow_1day();
+            }
+            draw_ages(&population, day)
+        })))
+    }
+}
+
+pub fn print_lanternfishes_counts(lanternfishes: &str, part: Part) {
+    print_answer::<Lanternfishes>(lanternfishes, part)
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
     #[test]
     fn aoc_example_works() {
-        let lanternfishes = "3,4,3,1,2";
+        let lanternfishes = Lanternfishes::parse(EXAMPLES[0].input).unwrap();
+
+        assert_eq!(5934, count_lanternfishes_after(&lanternfishes, 80));
+        assert_eq!(26984457539, count_lanternfishes_after(&lanternfishes, 256));
+        assert_eq!(5934, count_each_lanternfish_after(&lanternfishes, 80));
+    }
 
-        assert_eq!(5934, count_lanternfishes_after(lanternfishes, 80));
-        assert_eq!(26984457539, count_lanternfishes_after(lanternfishes, 256));
+    #[test]
+    fn frames_show_every_day() {
+        colored::control::set_override(false);
+        let lanternfishes = Lanternfishes::parse(EXAMPLES[0].input).unwrap();
+        let frames: Vec<_> = lanternfishes.frames().unwrap().collect();
+        assert_eq!(257, frames.len());
+        assert!(frames[0].starts_with("day 0 : 5 fishes\n0 "));
+        assert!(frames[0].contains(&format!("3 {} 2\n", "#".repeat(BAR_WIDTH))));
+        assert!(frames[256].starts_with("day 256 : 26984457539 fishes\n"));
     }
 }
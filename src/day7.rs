@@ -1,10 +1,14 @@
-fn compute_alignment_necessary_fuel(pos: &str, fuel_law: impl Fn(usize) -> usize) -> usize {
-    let pos: Vec<_> = pos
-        .trim()
-        .split(',')
-        .filter_map(|v| v.parse::<usize>().ok())
-        .collect();
+use crate::parse::parse_separated;
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver, Variant};
+use anyhow::Result;
 
+fn parse_positions(pos: &str) -> Result<Vec<usize>> {
+    parse_separated(pos.trim(), 0, ',')
+}
+
+fn compute_alignment_necessary_fuel(pos: &[usize], fuel_law: impl Fn(usize) -> usize) -> usize {
     // let be naive
     let min_pos = *pos.iter().min().unwrap();
     let max_pos = *pos.iter().max().unwrap();
@@ -18,16 +22,87 @@ fn compute_alignment_necessary_fuel(pos: &str, fuel_law: impl Fn(usize) -> usize
         .unwrap()
 }
 
-pub fn print_crab_alignment() {
-    let crabs_pos = include_str!("../resources/day7_crabs_pos.txt");
-    println!(
-        "fuel necessary to align  crabs (simple law) {}",
-        compute_alignment_necessary_fuel(crabs_pos, |d| d)
-    );
-    println!(
-        "fuel necessary to align  crabs (cumulative law){}",
-        compute_alignment_necessary_fuel(crabs_pos, |d| (d * (d + 1)) >> 1)
-    );
+/// reference for compute_alignment_necessary_fuel, moving the crabs one step at a time
+/// towards every target, the cumulative law making each step cost one more than the last
+fn move_crabs_step_by_step(pos: &[usize], cumulative: bool) -> usize {
+    let max_pos = *pos.iter().max().unwrap();
+    (0..=max_pos)
+        .map(|target| {
+            let mut fuel = 0;
+            for crab in pos {
+                let (mut p, mut cost) = (*crab, 1);
+                while p != target {
+                    p = if p < target { p + 1 } else { p - 1 };
+                    fuel += cost;
+                    if cumulative {
+                        cost += 1;
+                    }
+                }
+            }
+            fuel
+        })
+        .min()
+        .unwrap()
+}
+
+pub struct Crabs {
+    positions: Vec<usize>,
+}
+
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "horizontal positions",
+    input: "16,1,2,0,4,2,7,1,2,14",
+    part1: Some("37"),
+    part2: Some("168"),
+}];
+
+impl Solver for Crabs {
+    fn parse(crabs_pos: &str) -> Result<Self> {
+        Ok(Self {
+            positions: parse_positions(crabs_pos)?,
+        })
+    }
+
+    fn part1(&self) -> Answer {
+        compute_alignment_necessary_fuel(&self.positions, |d| d).into()
+    }
+
+    fn part2(&self) -> Answer {
+        compute_alignment_necessary_fuel(&self.positions, |d| (d * (d + 1)) >> 1).into()
+    }
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let positions: Vec<_> = (0..size)
+            .map(|_| rng.below(2 * size + 1).to_string())
+            .collect();
+        positions.join(",") + "\n"
+    }
+
+    fn variants() -> Vec<Variant<Self>> {
+        vec![
+            Variant {
+                name: "step by step",
+                part: Part::One,
+                solve: |c| move_crabs_step_by_step(&c.positions, false).into(),
+            },
+            Variant {
+                name: "growing steps",
+                part: Part::Two,
+                solve: |c| move_crabs_step_by_step(&c.positions, true).into(),
+            },
+        ]
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("fuel necessary to align  crabs (simple law) {}", answer),
+            Part::Two => format!("fuel necessary to align  crabs (cumulative law) {}", answer),
+        }
+    }
+}
+
+pub fn print_crab_alignment(crabs_pos: &str, part: Part) {
+    print_answer::<Crabs>(crabs_pos, part)
 }
 
 #[cfg(test)]
@@ -35,13 +110,15 @@ mod tests {
     use super::*;
     #[test]
     fn aoc_example_works() {
-        let crabs_pos = "16,1,2,0,4,2,7,1,2,14";
-        assert_eq!(37, compute_alignment_necessary_fuel(crabs_pos, |d| d));
+        let crabs_pos = parse_positions(EXAMPLES[0].input).unwrap();
+        assert_eq!(37, compute_alignment_necessary_fuel(&crabs_pos, |d| d));
 
         // 1 + 2 + ...+ n = n*(n+1)/2  (and /2 ==  >>1)
         assert_eq!(
             168,
-            compute_alignment_necessary_fuel(crabs_pos, |d| (d * (d + 1)) >> 1)
+            compute_alignment_necessary_fuel(&crabs_pos, |d| (d * (d + 1)) >> 1)
         );
+        assert_eq!(37, move_crabs_step_by_step(&crabs_pos, false));
+        assert_eq!(168, move_crabs_step_by_step(&crabs_pos, true));
     }
 }
@@ -1,3 +1,9 @@
+use crate::parse::parse_lines;
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver};
+use crate::trace::{debug, trace};
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
@@ -15,17 +21,16 @@ enum Wire {
 }
 use Wire::{A, B, C, D, E, F, G};
 impl Wire {
-    // Should get proper error
-    pub fn parse(v: &str) -> Result<Self, String> {
+    pub fn parse(v: char) -> Result<Self> {
         match v {
-            "a" => Ok(A),
-            "b" => Ok(B),
-            "c" => Ok(C),
-            "d" => Ok(D),
-            "e" => Ok(E),
-            "f" => Ok(F),
-            "g" => Ok(G),
-            unknown => Err(format!("'{}' cannot be parsed to Wire", unknown)),
+            'a' => Ok(A),
+            'b' => Ok(B),
+            'c' => Ok(C),
+            'd' => Ok(D),
+            'e' => Ok(E),
+            'f' => Ok(F),
+            'g' => Ok(G),
+            unknown => Err(anyhow!("'{}' cannot be parsed to Wire", unknown)),
         }
     }
 
@@ -103,28 +108,33 @@ struct DisplaysSource {
 }
 
 impl DisplaysSource {
-    fn parse_wires_group(wires: &str) -> Vec<Wire> {
-        wires
-            .split("")
-            .filter_map(|c| Wire::parse(c).ok())
-            .collect()
+    fn parse_wires_group(wires: &str) -> Result<Vec<Wire>> {
+        wires.chars().map(Wire::parse).collect()
     }
-    pub fn parse(input: &str) -> Option<Self> {
-        input.split('|').collect_tuple().map(|(digits, result)| {
-            let digits_segments: Vec<_> = digits
-                .split_whitespace()
-                .map(Self::parse_wires_group)
-                .collect();
-
-            let output_segments: Vec<_> = result
-                .split_whitespace()
-                .map(Self::parse_wires_group)
-                .collect();
-
-            Self {
-                digits_segments,
-                output_segments,
-            }
+    pub fn parse(input: &str) -> Result<Self> {
+        let (digits, result) = input
+            .split('|')
+            .collect_tuple()
+            .ok_or_else(|| anyhow!("expecting digits and output separated by '|'"))?;
+        let digits_segments: Vec<_> = digits
+            .split_whitespace()
+            .map(Self::parse_wires_group)
+            .collect::<Result<_>>()?;
+        let output_segments: Vec<_> = result
+            .split_whitespace()
+            .map(Self::parse_wires_group)
+            .collect::<Result<_>>()?;
+
+        if digits_segments.len() != 10 || output_segments.len() != 4 {
+            return Err(anyhow!(
+                "expecting 10 digits and 4 output digits, got {} and {}",
+                digits_segments.len(),
+                output_segments.len()
+            ));
+        }
+        Ok(Self {
+            digits_segments,
+            output_segments,
         })
     }
     pub fn decode(&self) -> usize {
@@ -173,7 +183,7 @@ impl DisplaysSource {
         for j in 0..10 {
             for (wire, candidates) in wires_to_wire.iter_mut() {
                 if !wire_to_digits.get(wire).unwrap().contains(&j) {
-                    // eprintln!("removing {} digits from {:?} wire", j, wire);
+                    trace!("removing {} digits from {:?} wire", j, wire);
                     for candidate in get_wires(j) {
                         candidates.take(&candidate);
                     }
@@ -181,7 +191,7 @@ impl DisplaysSource {
             }
         }
 
-        for _ in 0..10 {
+        for tr in 0..10 {
             // max 10 iterations since there are 10 unknown
             // let's work with digits whose wire set is fully known
 
@@ -197,11 +207,11 @@ impl DisplaysSource {
                     // (sources.contains && actual_wires.contains) or (!sources.contains && !actual_wires.contains)
                     candidates.retain(|c| !(sources.contains(wire) ^ actual_wires.contains(c)));
                     if len > candidates.len() {
-                        // eprintln!(
-                        //     "removed {} wires from {:?} candidates",
-                        //     len - candidates.len(),
-                        //     wire
-                        // );
+                        trace!(
+                            "removed {} wires from {:?} candidates",
+                            len - candidates.len(),
+                            wire
+                        );
                     }
                 }
             }
@@ -213,20 +223,20 @@ impl DisplaysSource {
                 .map(|(wire, translations)| (*wire, *translations.iter().next().unwrap()))
                 .collect();
             if resolved_wires.len() == wires_to_wire.len() {
-                // eprintln!("solved at {} try", tr);
+                debug!("solved at {} try", tr);
                 break;
             }
             for (displayed, actual) in resolved_wires {
                 // direct Mapping
                 for (wire, candidates) in wires_to_wire.iter_mut() {
                     if displayed != *wire && candidates.take(&actual).is_some() {
-                        // eprintln!("removing {:?} from {:?} candidates", &actual, wire);
+                        trace!("removing {:?} from {:?} candidates", &actual, wire);
                     }
                 }
                 // Digit mapping :  todo build from digit_to_sisplay_index ?
                 for (digit, sources) in digit_to_source_wires.iter_mut() {
                     if !get_wires(*digit).contains(&actual) && sources.take(&displayed).is_some() {
-                        // eprintln!("removing {:?} from {:?} sources", &displayed, digit);
+                        trace!("removing {:?} from {:?} sources", &displayed, digit);
                     }
                 }
                 for (digit, display_indexes) in digit_to_displayed_index.iter_mut() {
@@ -236,11 +246,11 @@ impl DisplaysSource {
                         display_indexes
                             .retain(|idx| !self.digits_segments[*idx].contains(&displayed));
                         if len > display_indexes.len() {
-                            // eprintln!(
-                            //     "{:?} possible index removed from {:?}",
-                            //     len - display_indexes.len(),
-                            //     digit
-                            // );
+                            trace!(
+                                "{:?} possible index removed from {:?}",
+                                len - display_indexes.len(),
+                                digit
+                            );
                         }
                     }
                 }
@@ -256,11 +266,11 @@ impl DisplaysSource {
                     let len = wires.len();
                     wires.retain(|f| all_possible_index.contains(&f));
                     if len > wires.len() {
-                        // eprintln!(
-                        //     "{:?} possible wires removed from {:?}",
-                        //     len - wires.len(),
-                        //     digit
-                        // );
+                        trace!(
+                            "{:?} possible wires removed from {:?}",
+                            len - wires.len(),
+                            digit
+                        );
                     }
 
                     let acceptable_wires = get_wires(*digit);
@@ -278,13 +288,16 @@ impl DisplaysSource {
                     }
                 }
 
-                // println!("correspondance possibles entre valeurs");
-                // for (digit, indexes) in &digit_to_displayed_index {
-                //     println!("  {}", digit);
-                //     for i in indexes {
-                //         println!("    {:?}", &self.digits_segments[*i]);
-                //     }
-                // }
+                for (digit, indexes) in &digit_to_displayed_index {
+                    trace!(
+                        "{} may be displayed as {:?}",
+                        digit,
+                        indexes
+                            .iter()
+                            .map(|i| &self.digits_segments[*i])
+                            .collect_vec()
+                    );
+                }
             }
         }
         if wires_to_wire
@@ -309,8 +322,8 @@ impl DisplaysSource {
     }
 }
 
-fn parse_displays(input: &str) -> Vec<DisplaysSource> {
-    input.lines().filter_map(DisplaysSource::parse).collect()
+fn parse_displays(input: &str) -> Result<Vec<DisplaysSource>> {
+    parse_lines(input, DisplaysSource::parse)
 }
 
 fn count_unique_numbers(sources: &[DisplaysSource]) -> usize {
@@ -333,15 +346,81 @@ fn sum_decoded(sources: &[DisplaysSource]) -> usize {
     sources.iter().map(|source| source.decode()).sum()
 }
 
-pub fn display_digits() {
-    let input = include_str!("../resources/day8_digits_displays.txt");
-    let sources = parse_displays(input);
+pub struct Displays {
+    sources: Vec<DisplaysSource>,
+}
+
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "notes",
+    input: "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb |fdgacbe cefdb cefbgd gcbe
+edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec |fcgedb cgb dgebacf gc
+fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef |cg cg fdcagb cbg
+fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega |efabcd cedba gadfec cb
+aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga |gecf egdcabf bgf bfgea
+fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf |gebdcfa ecba ca fadegcb
+dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf |cefg dcbef fcge gbcadfe
+bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd |ed bcgafe cdgba cbgef
+egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg |gbdfcae bgc cg cgb
+gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc |fgae cfgab fg bagce",
+    part1: Some("26"),
+    part2: Some("61229"),
+}];
+
+impl Solver for Displays {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            sources: parse_displays(input)?,
+        })
+    }
+
+    fn part1(&self) -> Answer {
+        count_unique_numbers(&self.sources).into()
+    }
+
+    fn part2(&self) -> Answer {
+        sum_decoded(&self.sources).into()
+    }
+
+    // each display has its own wiring, patterns and segments are shuffled
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        const DIGITS: [&str; 10] = [
+            "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+        ];
+        (0..size)
+            .map(|_| {
+                let mut wiring: Vec<char> = "abcdefg".chars().collect();
+                rng.shuffle(&mut wiring);
+                let pattern = |rng: &mut Rng, digit: usize| {
+                    let mut wires: Vec<char> = DIGITS[digit]
+                        .chars()
+                        .map(|s| wiring[s as usize - 'a' as usize])
+                        .collect();
+                    rng.shuffle(&mut wires);
+                    wires.into_iter().collect::<String>()
+                };
+                let mut patterns: Vec<_> = (0..10).map(|digit| pattern(rng, digit)).collect();
+                rng.shuffle(&mut patterns);
+                let outputs: Vec<_> = (0..4)
+                    .map(|_| {
+                        let digit = rng.below(10);
+                        pattern(rng, digit)
+                    })
+                    .collect();
+                format!("{} | {}\n", patterns.join(" "), outputs.join(" "))
+            })
+            .collect()
+    }
+
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("number of unique numbers : {}", answer),
+            Part::Two => format!("sum of decoded values : {}", answer),
+        }
+    }
+}
 
-    println!(
-        "number of unique numbers : {}",
-        count_unique_numbers(&sources)
-    );
-    println!("sum of decoded values : {}", sum_decoded(&sources));
+pub fn display_digits(input: &str, part: Part) {
+    print_answer::<Displays>(input, part)
 }
 
 #[cfg(test)]
@@ -355,23 +434,16 @@ mod tests {
 
         let source = DisplaysSource::parse(line).unwrap();
         source.decode();
+
+        assert!(DisplaysSource::parse("acedgfb cdfbe |cdfeb fcadb cdfeb cdbaf").is_err());
+        assert!(DisplaysSource::parse(&line.replace('g', "h")).is_err());
     }
 
     #[test]
     fn aoc_example_works() {
-        let input = "
-be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb |fdgacbe cefdb cefbgd gcbe
-edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec |fcgedb cgb dgebacf gc
-fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef |cg cg fdcagb cbg
-fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega |efabcd cedba gadfec cb
-aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga |gecf egdcabf bgf bfgea
-fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf |gebdcfa ecba ca fadegcb
-dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf |cefg dcbef fcge gbcadfe
-bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd |ed bcgafe cdgba cbgef
-egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg |gbdfcae bgc cg cgb
-gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc |fgae cfgab fg bagce";
+        let input = EXAMPLES[0].input;
 
-        let sources = parse_displays(input);
+        let sources = parse_displays(input).unwrap();
 
         assert_eq!(26, count_unique_numbers(&sources));
         assert_eq!(61229, sum_decoded(&sources));
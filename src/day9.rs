@@ -1,100 +1,51 @@
+use crate::export::{blend, distinct, Color, BLACK, WHITE};
+use crate::grid::{Grid, Point};
+use crate::registry::Part;
+use crate::rng::Rng;
+use crate::solver::{print_answer, Answer, Example, Solver};
+use anyhow::Result;
 use itertools::Itertools;
 
-fn parse_heights(input: &str) -> Vec<Vec<usize>> {
-    input
-        .lines()
-        .map(|line| {
-            line.split("")
-                .filter_map(|c| c.parse::<usize>().ok())
-                .collect()
-        })
-        .collect()
-}
-
-fn get_dim(map: &[Vec<usize>]) -> Point {
-    let max_x = map.len();
-    let max_y = map.iter().map(|line| line.len()).max().unwrap();
-    (max_x, max_y)
-}
-
-pub type Point = (usize, usize);
-
-fn get_neighbours_pos(
-    pos: &Point,
-    dim: &Point,
-    selector: impl Fn(Point, Point) -> bool,
-) -> Vec<Point> {
-    let (x, y) = pos;
-    let (max_x, max_y) = dim;
-
-    let x_range = match x {
-        0 => *x..x + 2,
-        max if *max == *max_x - 1 => x - 1..x + 1,
-        _ => x - 1..x + 2,
-    };
-    let y_range = match y {
-        0 => *y..y + 2,
-        max if *max == *max_y - 1 => y - 1..y + 1,
-        _ => y - 1..y + 2,
-    };
-    x_range
-        .flat_map(|nx| y_range.clone().map(move |ny| (nx, ny)))
-        .filter(|(nx, ny)| selector((*x, *y), (*nx, *ny)))
-        .collect()
-}
-
-// thats'not what we're asked :-p
-pub fn get_neighbours_pos_diag(pos: &Point, dim: &Point) -> Vec<Point> {
-    get_neighbours_pos(pos, dim, |(x, y), (nx, ny)| nx != x || ny != y)
-}
-
-pub fn get_neighbours_pos_horz_vert(pos: &Point, dim: &Point) -> Vec<Point> {
-    get_neighbours_pos(pos, dim, |(x, y), (nx, ny)| (nx != x) ^ (ny != y))
+fn parse_heights(input: &str) -> Result<Grid<usize>> {
+    Grid::parse_digits(input)
 }
 
-fn get_low_points(heights: &[Vec<usize>]) -> Vec<Point> {
-    let dim = get_dim(heights);
-
-    let height_points = heights
+fn get_low_points(heights: &Grid<usize>) -> Vec<Point> {
+    heights
         .iter()
-        .enumerate()
-        .flat_map(|(x, c)| c.iter().enumerate().map(move |(y, height)| (x, y, *height)));
-
-    height_points
-        .filter(|(x, y, h)| {
-            let neighbours = get_neighbours_pos_horz_vert(&(*x, *y), &dim);
-            *h < neighbours
-                .iter()
-                .map(|(x, y)| heights[*x][*y])
+        .filter(|(p, h)| {
+            heights
+                .neighbours(*p)
+                .map(|n| heights[n])
                 .min()
-                .unwrap()
+                .is_none_or(|lowest| **h < lowest)
         })
-        .map(|(x, y, _)| (x, y))
+        .map(|(p, _)| p)
         .collect()
 }
 
-fn sum_low_point_risks(heights: &[Vec<usize>]) -> usize {
+fn sum_low_point_risks(heights: &Grid<usize>) -> usize {
     let low_points = get_low_points(heights);
-    low_points.into_iter().map(|(x, y)| heights[x][y] + 1).sum()
+    low_points.into_iter().map(|p| heights[p] + 1).sum()
 }
 
-fn get_flowing_points(point: &Point, dim: &Point, heights: &[Vec<usize>]) -> Vec<Point> {
-    let height = heights[point.0][point.1];
-    get_neighbours_pos_horz_vert(point, dim)
-        .into_iter()
-        .filter(|(x, y)| height < heights[*x][*y] && heights[*x][*y] < 9)
+fn get_flowing_points(point: Point, heights: &Grid<usize>) -> Vec<Point> {
+    let height = heights[point];
+    heights
+        .neighbours(point)
+        .filter(|n| height < heights[*n] && heights[*n] < 9)
         .collect()
 }
 
-fn get_bassin(low_point: &Point, dim: &Point, heights: &[Vec<usize>]) -> Vec<Point> {
+fn get_bassin(low_point: &Point, heights: &Grid<usize>) -> Vec<Point> {
     let mut bassin: Vec<Point> = vec![];
 
     let mut new_points = vec![*low_point];
     while !new_points.is_empty() {
         let mut new_new_points: Vec<_> = new_points
             .iter()
-            .flat_map(|(x, y)| get_flowing_points(&(*x, *y), dim, heights).into_iter())
-            .filter(|(x, y)| !bassin.contains(&(*x, *y)))
+            .flat_map(|p| get_flowing_points(*p, heights).into_iter())
+            .filter(|p| !bassin.contains(p))
             .unique()
             .collect();
         bassin.append(&mut new_points);
@@ -103,12 +54,11 @@ fn get_bassin(low_point: &Point, dim: &Point, heights: &[Vec<usize>]) -> Vec<Poi
     bassin
 }
 
-fn multiply_bassins(heights: &[Vec<usize>]) -> usize {
-    let dim = get_dim(heights);
+fn multiply_bassins(heights: &Grid<usize>) -> usize {
     let low_points = get_low_points(heights);
     let bassins_lengths = low_points
         .iter()
-        .map(|p| get_bassin(p, &dim, heights))
+        .map(|p| get_bassin(p, heights))
         .map(|b| b.len())
         .sorted()
         .rev();
@@ -116,17 +66,66 @@ fn multiply_bassins(heights: &[Vec<usize>]) -> usize {
     bassins_lengths.take(3).product()
 }
 
-pub fn display_smoke_risks() {
-    let input = include_str!("../resources/day9_heights.txt");
-    let heights = parse_heights(input);
-    let risk = sum_low_point_risks(&heights);
+pub struct HeightMap {
+    heights: Grid<usize>,
+}
+
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "heightmap",
+    input: "2199943210
+3987894921
+9856789892
+8767896789
+9899965678",
+    part1: Some("15"),
+    part2: Some("1134"),
+}];
+
+impl Solver for HeightMap {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            heights: parse_heights(input)?,
+        })
+    }
+
+    fn part1(&self) -> Answer {
+        sum_low_point_risks(&self.heights).into()
+    }
+
+    fn part2(&self) -> Answer {
+        multiply_bassins(&self.heights).into()
+    }
+
+    // a size x size map
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        Grid::from_fn(size, size, |_| rng.below(10)).to_string()
+    }
+
+    // each bassin has its own color, darker as it gets higher, its low point being white
+    fn picture(&self) -> Option<Grid<Color>> {
+        let mut picture = self.heights.map(|h| match h {
+            9 => [40, 40, 40],
+            _ => [160, 160, 160],
+        });
+        for (i, low_point) in get_low_points(&self.heights).iter().enumerate() {
+            for p in get_bassin(low_point, &self.heights) {
+                picture[p] = blend(distinct(i), BLACK, self.heights[p] as f64 / 12.0);
+            }
+            picture[*low_point] = WHITE;
+        }
+        Some(picture)
+    }
 
-    println!("sum of risk of all low points {}", risk);
+    fn describe(part: Part, answer: &Answer) -> String {
+        match part {
+            Part::One => format!("sum of risk of all low points {}", answer),
+            Part::Two => format!("product of 3 largest bassins {}", answer),
+        }
+    }
+}
 
-    println!(
-        "product of 3 largest bassins {}",
-        multiply_bassins(&heights)
-    );
+pub fn display_smoke_risks(input: &str, part: Part) {
+    print_answer::<HeightMap>(input, part)
 }
 
 #[cfg(test)]
@@ -135,13 +134,8 @@ mod tests {
 
     #[test]
     fn aoc_example_works() {
-        let input = "2199943210
-3987894921
-9856789892
-8767896789
-9899965678
-";
-        let heights = parse_heights(input);
+        let input = EXAMPLES[0].input;
+        let heights = parse_heights(input).unwrap();
 
         assert_eq!(15, sum_low_point_risks(&heights));
         assert_eq!(1134, multiply_bassins(&heights));
@@ -0,0 +1,237 @@
+use crate::grid::Grid;
+use anyhow::{anyhow, Result};
+use std::fmt::Write;
+use std::path::Path;
+
+/// red, green and blue
+pub type Color = [u8; 3];
+
+pub const BLACK: Color = [0, 0, 0];
+pub const WHITE: Color = [255, 255, 255];
+
+/// linear blend, a ratio of 0 giving from and 1 giving to
+pub fn blend(from: Color, to: Color, ratio: f64) -> Color {
+    let ratio = ratio.clamp(0.0, 1.0);
+    let mut color = BLACK;
+    for i in 0..3 {
+        color[i] = (from[i] as f64 + (to[i] as f64 - from[i] as f64) * ratio).round() as u8;
+    }
+    color
+}
+
+/// colors far from each other for consecutive indexes, their hue turning by the golden angle
+pub fn distinct(index: usize) -> Color {
+    let hue = (index as f64 * 137.508) % 360.0 / 60.0;
+    let (high, low) = (230.0, 90.0);
+    let middle = low + (high - low) * (1.0 - (hue % 2.0 - 1.0).abs());
+    let [r, g, b] = match hue as usize {
+        0 => [high, middle, low],
+        1 => [middle, high, low],
+        2 => [low, high, middle],
+        3 => [low, middle, high],
+        4 => [middle, low, high],
+        _ => [high, low, middle],
+    };
+    [r as u8, g as u8, b as u8]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+    Svg,
+}
+
+impl ImageFormat {
+    /// guessed from the image file extension
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("ppm") => Ok(ImageFormat::Ppm),
+            Some("png") => Ok(ImageFormat::Png),
+            Some("svg") => Ok(ImageFormat::Svg),
+            _ => Err(anyhow!(
+                "cannot guess the image format of '{}' (.ppm, .png or .svg)",
+                path.display()
+            )),
+        }
+    }
+
+    /// each cell of the picture drawn as a scale x scale square
+    pub fn encode(self, picture: &Grid<Color>, scale: usize) -> Vec<u8> {
+        match self {
+            ImageFormat::Ppm => to_ppm(picture, scale),
+            ImageFormat::Png => to_png(picture, scale),
+            ImageFormat::Svg => to_svg(picture, scale).into_bytes(),
+        }
+    }
+}
+
+// rows of rgb bytes, each cell repeated scale times in both directions
+fn scaled_rows(picture: &Grid<Color>, scale: usize) -> impl Iterator<Item = Vec<u8>> + '_ {
+    picture.rows().flat_map(move |row| {
+        let pixels: Vec<u8> = row
+            .iter()
+            .flat_map(|color| std::iter::repeat_n(color, scale))
+            .flatten()
+            .copied()
+            .collect();
+        std::iter::repeat_n(pixels, scale)
+    })
+}
+
+/// binary netpbm
+pub fn to_ppm(picture: &Grid<Color>, scale: usize) -> Vec<u8> {
+    let mut ppm = format!(
+        "P6\n{} {}\n255\n",
+        picture.width() * scale,
+        picture.height() * scale
+    )
+    .into_bytes();
+    scaled_rows(picture, scale).for_each(|row| ppm.extend(row));
+    ppm
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u32, |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1u32, 0u32), |(a, b), byte| {
+        let a = (a + *byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
+
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+/// truecolor png, its pixels stored without compression so that no deflate encoder is needed
+pub fn to_png(picture: &Grid<Color>, scale: usize) -> Vec<u8> {
+    let mut raw = vec![];
+    for row in scaled_rows(picture, scale) {
+        // no filter on the row
+        raw.push(0);
+        raw.extend(row);
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let blocks = raw.chunks(u16::MAX as usize).collect::<Vec<_>>();
+    for (i, block) in blocks.iter().enumerate() {
+        zlib.push((i + 1 == blocks.len()) as u8);
+        zlib.extend((block.len() as u16).to_le_bytes());
+        zlib.extend((!(block.len() as u16)).to_le_bytes());
+        zlib.extend(*block);
+    }
+    if blocks.is_empty() {
+        zlib.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    zlib.extend(adler32(&raw).to_be_bytes());
+
+    let mut header = vec![];
+    header.extend(((picture.width() * scale) as u32).to_be_bytes());
+    header.extend(((picture.height() * scale) as u32).to_be_bytes());
+    // 8 bits per channel, rgb, default compression, filter and interlacing
+    header.extend([8, 2, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    png_chunk(&mut png, b"IHDR", &header);
+    png_chunk(&mut png, b"IDAT", &zlib);
+    png_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+/// one rectangle for each run of cells of the same color in a row
+pub fn to_svg(picture: &Grid<Color>, scale: usize) -> String {
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" shape-rendering=\"crispEdges\">\n",
+        picture.width() * scale,
+        picture.height() * scale
+    );
+    for (y, row) in picture.rows().enumerate() {
+        let mut x = 0;
+        for run in row.chunk_by(|a, b| a == b) {
+            let [r, g, b] = run[0];
+            writeln!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#{:02x}{:02x}{:02x}\"/>",
+                x * scale,
+                y * scale,
+                run.len() * scale,
+                scale,
+                r,
+                g,
+                b
+            )
+            .unwrap();
+            x += run.len();
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn picture() -> Grid<Color> {
+        Grid::from_rows(vec![vec![BLACK, BLACK, WHITE], vec![WHITE, WHITE, WHITE]]).unwrap()
+    }
+
+    #[test]
+    fn checksums_are_the_standard_ones() {
+        assert_eq!(0xcbf4_3926, crc32(b"123456789"));
+        assert_eq!(0xae42_6082, crc32(b"IEND"));
+        assert_eq!(0x11e6_0398, adler32(b"Wikipedia"));
+    }
+
+    #[test]
+    fn pictures_can_be_encoded() {
+        let ppm = to_ppm(&picture(), 2);
+        assert!(ppm.starts_with(b"P6\n6 4\n255\n"));
+        assert_eq!(11 + 6 * 4 * 3, ppm.len());
+        assert_eq!([0; 6], ppm[11..17]);
+
+        let png = to_png(&picture(), 2);
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x06\0\0\0\x04"));
+        assert!(png.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+
+        let svg = to_svg(&picture(), 10);
+        assert_eq!(3, svg.matches("<rect").count());
+        assert!(svg.contains("<rect x=\"0\" y=\"0\" width=\"20\" height=\"10\" fill=\"#000000\"/>"));
+        assert!(
+            svg.contains("<rect x=\"0\" y=\"10\" width=\"30\" height=\"10\" fill=\"#ffffff\"/>")
+        );
+    }
+
+    #[test]
+    fn format_is_guessed_from_extension() {
+        assert_eq!(
+            ImageFormat::Png,
+            ImageFormat::from_path(Path::new("out/basins.png")).unwrap()
+        );
+        assert!(ImageFormat::from_path(Path::new("basins.jpg")).is_err());
+    }
+
+    #[test]
+    fn colors_can_be_mixed() {
+        assert_eq!([128, 128, 128], blend(BLACK, WHITE, 0.5));
+        assert_eq!(WHITE, blend(BLACK, WHITE, 3.0));
+        assert_ne!(distinct(0), distinct(1));
+    }
+}
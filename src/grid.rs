@@ -0,0 +1,261 @@
+use crate::parse::{parse_digit_grid, parse_grid, parse_numbered_grid};
+use anyhow::{anyhow, Result};
+use std::fmt::{self, Display};
+use std::ops::{Index, IndexMut};
+
+/// (row, column) of a cell, starting from the top left corner
+pub type Point = (usize, usize);
+
+const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+const SURROUNDING: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// rectangular map, stored row by row
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    height: usize,
+    width: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn from_fn(height: usize, width: usize, mut cell: impl FnMut(Point) -> T) -> Self {
+        Self {
+            height,
+            width,
+            cells: (0..height)
+                .flat_map(|row| (0..width).map(move |col| (row, col)))
+                .map(&mut cell)
+                .collect(),
+        }
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self> {
+        let width = rows.first().map_or(0, Vec::len);
+        if let Some(row) = rows.iter().position(|r| r.len() != width) {
+            return Err(anyhow!(
+                "row {} has {} cells instead of {}",
+                row,
+                rows[row].len(),
+                width
+            ));
+        }
+        Ok(Self {
+            height: rows.len(),
+            width,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// each character of a non empty map converted by cell
+    pub fn parse(input: &str, cell: impl FnMut(char) -> Option<T>) -> Result<Self> {
+        Self::from_rows(parse_grid(input, cell)?)
+    }
+
+    /// same as parse, for (index, line) taken from the middle of an input
+    pub fn parse_numbered<'a>(
+        lines: impl IntoIterator<Item = (usize, &'a str)>,
+        cell: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self> {
+        Self::from_rows(parse_numbered_grid(lines, cell)?)
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// (height, width)
+    pub fn dim(&self) -> Point {
+        (self.height, self.width)
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.contains(p)
+            .then(|| &self.cells[p.0 * self.width + p.1])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        if self.contains(p) {
+            Some(&mut self.cells[p.0 * self.width + p.1])
+        } else {
+            None
+        }
+    }
+
+    pub fn contains(&self, (row, col): Point) -> bool {
+        row < self.height && col < self.width
+    }
+
+    /// the cell at signed coordinates, if they are inside the grid
+    pub fn checked(&self, (row, col): (isize, isize)) -> Option<Point> {
+        let p = (usize::try_from(row).ok()?, usize::try_from(col).ok()?);
+        self.contains(p).then_some(p)
+    }
+
+    pub fn offset(&self, (row, col): Point, (d_row, d_col): (isize, isize)) -> Option<Point> {
+        self.checked((row as isize + d_row, col as isize + d_col))
+    }
+
+    /// offset on a torus : leaving by one side comes back by the other
+    pub fn wrapped(&self, (row, col): Point, (d_row, d_col): (isize, isize)) -> Point {
+        (
+            (row as isize + d_row).rem_euclid(self.height as isize) as usize,
+            (col as isize + d_col).rem_euclid(self.width as isize) as usize,
+        )
+    }
+
+    /// up, left, right and down cells inside the grid
+    pub fn neighbours(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        ORTHOGONAL.iter().filter_map(move |d| self.offset(p, *d))
+    }
+
+    /// the 8 surrounding cells inside the grid, diagonals included
+    pub fn surrounding(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        SURROUNDING.iter().filter_map(move |d| self.offset(p, *d))
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |i| (i / width, i % width))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn map<U>(&self, cell: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            height: self.height,
+            width: self.width,
+            cells: self.cells.iter().map(cell).collect(),
+        }
+    }
+
+    /// factor x factor copies of the grid, each cell derived from the original
+    /// one and the (row, column) of its tile
+    pub fn tiled(&self, factor: usize, cell: impl Fn(&T, Point) -> T) -> Self {
+        Grid::from_fn(self.height * factor, self.width * factor, |(row, col)| {
+            cell(
+                &self[(row % self.height, col % self.width)],
+                (row / self.height, col / self.width),
+            )
+        })
+    }
+}
+
+impl Grid<usize> {
+    pub fn parse_digits(input: &str) -> Result<Self> {
+        Self::from_rows(parse_digit_grid(input)?)
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(height: usize, width: usize, value: T) -> Self {
+        Self {
+            height,
+            width,
+            cells: vec![value; height * width],
+        }
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        self.get(p)
+            .unwrap_or_else(|| panic!("{:?} is outside of a {:?} grid", p, self.dim()))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        let dim = self.dim();
+        self.get_mut(p)
+            .unwrap_or_else(|| panic!("{:?} is outside of a {:?} grid", p, dim))
+    }
+}
+
+/// one line per row, cells side by side
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                cell.fmt(f)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grids_can_be_parsed_and_displayed() {
+        let grid = Grid::parse_digits("123\n456\n").unwrap();
+        assert_eq!((2, 3), grid.dim());
+        assert_eq!(6, grid[(1, 2)]);
+        assert_eq!(None, grid.get((2, 0)));
+        assert_eq!("123\n456\n", grid.to_string());
+
+        let chars = Grid::parse(".#\n#.", |c| Some(c == '#')).unwrap();
+        assert_eq!(2, chars.values().filter(|c| **c).count());
+        assert!(Grid::from_rows(vec![vec![1, 2], vec![3]]).is_err());
+    }
+
+    #[test]
+    fn neighbours_stay_inside() {
+        let grid = Grid::new(3, 4, 0);
+        assert_eq!(
+            vec![(0, 1), (1, 0)],
+            grid.neighbours((0, 0)).collect::<Vec<_>>()
+        );
+        assert_eq!(4, grid.neighbours((1, 1)).count());
+        assert_eq!(3, grid.surrounding((2, 3)).count());
+        assert_eq!(8, grid.surrounding((1, 2)).count());
+    }
+
+    #[test]
+    fn offsets_can_wrap() {
+        let grid = Grid::new(3, 4, 0);
+        assert_eq!(None, grid.offset((0, 3), (0, 1)));
+        assert_eq!((0, 0), grid.wrapped((0, 3), (0, 1)));
+        assert_eq!((2, 3), grid.wrapped((0, 0), (-1, -1)));
+        assert_eq!(Some((1, 1)), grid.checked((1, 1)));
+        assert_eq!(None, grid.checked((-1, 1)));
+    }
+
+    #[test]
+    fn grids_can_be_tiled() {
+        let grid = Grid::parse_digits("8").unwrap();
+        let tiled = grid.tiled(3, |risk, (row, col)| (risk - 1 + row + col) % 9 + 1);
+        assert_eq!("891\n912\n123\n", tiled.to_string());
+    }
+}
@@ -0,0 +1,266 @@
+use crate::registry::Day;
+use crate::solver::Example;
+use anyhow::{anyhow, Context, Result};
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+
+/// resources of the source tree, the bundled copies standing in for the files it lacks
+pub fn default_resources_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("resources")
+}
+
+// built into the binary, for it to still run once moved away from its source tree
+const BUNDLED: &[(&str, &str)] = &[
+    (
+        "day1_sonar_depths.txt",
+        include_str!("../resources/day1_sonar_depths.txt"),
+    ),
+    (
+        "day2_movements.txt",
+        include_str!("../resources/day2_movements.txt"),
+    ),
+    (
+        "day3_diagnosis.txt",
+        include_str!("../resources/day3_diagnosis.txt"),
+    ),
+    (
+        "day4_bingo.txt",
+        include_str!("../resources/day4_bingo.txt"),
+    ),
+    (
+        "day5_hydrothermal_vents.txt",
+        include_str!("../resources/day5_hydrothermal_vents.txt"),
+    ),
+    (
+        "day6_lanternfishes.txt",
+        include_str!("../resources/day6_lanternfishes.txt"),
+    ),
+    (
+        "day7_crabs_pos.txt",
+        include_str!("../resources/day7_crabs_pos.txt"),
+    ),
+    (
+        "day8_digits_displays.txt",
+        include_str!("../resources/day8_digits_displays.txt"),
+    ),
+    (
+        "day9_heights.txt",
+        include_str!("../resources/day9_heights.txt"),
+    ),
+    (
+        "day10_navigation_syntax.txt",
+        include_str!("../resources/day10_navigation_syntax.txt"),
+    ),
+    (
+        "day11_octopuses_energy.txt",
+        include_str!("../resources/day11_octopuses_energy.txt"),
+    ),
+    (
+        "day12_connections.txt",
+        include_str!("../resources/day12_connections.txt"),
+    ),
+    (
+        "day13_transparent_paper.txt",
+        include_str!("../resources/day13_transparent_paper.txt"),
+    ),
+    (
+        "day14_chemistry.txt",
+        include_str!("../resources/day14_chemistry.txt"),
+    ),
+    (
+        "day15_risks.txt",
+        include_str!("../resources/day15_risks.txt"),
+    ),
+    (
+        "day16_bits.txt",
+        include_str!("../resources/day16_bits.txt"),
+    ),
+    (
+        "day17_targetarea.txt",
+        include_str!("../resources/day17_targetarea.txt"),
+    ),
+    (
+        "day18_fish_numbers.txt",
+        include_str!("../resources/day18_fish_numbers.txt"),
+    ),
+    (
+        "day19_scanners.txt",
+        include_str!("../resources/day19_scanners.txt"),
+    ),
+    (
+        "day20_enhancer.txt",
+        include_str!("../resources/day20_enhancer.txt"),
+    ),
+    (
+        "day21_dirac_dice.txt",
+        include_str!("../resources/day21_dirac_dice.txt"),
+    ),
+    (
+        "day22_reboot_sequence.txt",
+        include_str!("../resources/day22_reboot_sequence.txt"),
+    ),
+    (
+        "day23_amphipods.txt",
+        include_str!("../resources/day23_amphipods.txt"),
+    ),
+    (
+        "day24_monad.txt",
+        include_str!("../resources/day24_monad.txt"),
+    ),
+    (
+        "day25_sea_floor.txt",
+        include_str!("../resources/day25_sea_floor.txt"),
+    ),
+    ("answers.toml", include_str!("../resources/answers.toml")),
+];
+
+// only the default resources fall back to the bundled ones, a missing file of another
+// directory being a mistake to report
+fn bundled(path: &Path) -> Option<&'static str> {
+    if path.parent()? != default_resources_dir() {
+        return None;
+    }
+    let name = path.file_name()?.to_str()?;
+    BUNDLED.iter().find(|(n, _)| *n == name).map(|(_, c)| *c)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputSource {
+    /// each day reads its own file from this directory
+    Resources(PathBuf),
+    File(PathBuf),
+    Stdin,
+    /// n-th worked example of the day, starting from 1
+    Example(usize),
+}
+
+impl Default for InputSource {
+    fn default() -> Self {
+        InputSource::Resources(default_resources_dir())
+    }
+}
+
+impl InputSource {
+    /// '-' stands for stdin, anything else for a file
+    pub fn parse(arg: &str) -> Self {
+        match arg {
+            "-" => InputSource::Stdin,
+            path => InputSource::File(PathBuf::from(path)),
+        }
+    }
+
+    /// expected answers only make sense for the bundled inputs
+    pub fn answers_file(&self) -> Option<PathBuf> {
+        match self {
+            InputSource::Resources(dir) => Some(dir.join("answers.toml")),
+            _ => None,
+        }
+    }
+
+    pub fn example(&self, day: &Day) -> Option<&'static Example> {
+        match self {
+            InputSource::Example(n) => day.examples.get(n.checked_sub(1)?),
+            _ => None,
+        }
+    }
+
+    pub fn read(&self, day: &Day) -> Result<String> {
+        match self {
+            InputSource::Example(n) => {
+                let example = self.example(day).ok_or_else(|| {
+                    let names = day
+                        .examples
+                        .iter()
+                        .enumerate()
+                        .map(|(i, e)| format!("{} {}", i + 1, e.name))
+                        .collect::<Vec<_>>();
+                    if names.is_empty() {
+                        anyhow!("day {} has no example", day.number)
+                    } else {
+                        anyhow!(
+                            "day {} has no example {}, only {}",
+                            day.number,
+                            n,
+                            names.join(", ")
+                        )
+                    }
+                })?;
+                Ok(example.input.to_string())
+            }
+            InputSource::Resources(dir) => read_file(&dir.join(day.input)),
+            InputSource::File(path) => read_file(path),
+            InputSource::Stdin => {
+                let mut input = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut input)
+                    .context("could not read input from stdin")?;
+                Ok(input)
+            }
+        }
+    }
+}
+
+pub(crate) fn read_file(path: &Path) -> Result<String> {
+    match std::fs::read_to_string(path) {
+        Err(e) if e.kind() == ErrorKind::NotFound => bundled(path).map(str::to_string).ok_or(e),
+        read => read,
+    }
+    .with_context(|| format!("could not read {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::get_day;
+
+    #[test]
+    fn every_day_has_a_bundled_input() {
+        for day in crate::registry::all_days() {
+            assert!(
+                InputSource::default().read(day).is_ok(),
+                "missing input for day {}",
+                day.number
+            );
+        }
+    }
+
+    #[test]
+    fn only_default_resources_are_bundled() {
+        let dir = default_resources_dir();
+        for day in crate::registry::all_days() {
+            assert_eq!(
+                std::fs::read_to_string(dir.join(day.input)).ok().as_deref(),
+                bundled(&dir.join(day.input))
+            );
+        }
+        assert!(bundled(&dir.join("answers.toml")).is_some());
+        assert!(bundled(&dir.join("missing.txt")).is_none());
+        assert!(bundled(&PathBuf::from("inputs/day1_sonar_depths.txt")).is_none());
+        assert!(read_file(&PathBuf::from("inputs/day1_sonar_depths.txt")).is_err());
+    }
+
+    #[test]
+    fn examples_can_be_read() {
+        let day = get_day(12).unwrap();
+        let example = InputSource::Example(2).read(day).unwrap();
+        assert!(example.starts_with("dc-end"));
+        assert_eq!(
+            Some("19"),
+            InputSource::Example(2).example(day).unwrap().part1
+        );
+
+        let err = InputSource::Example(4).read(day).unwrap_err();
+        assert_eq!(
+            "day 12 has no example 4, only 1 small, 2 slightly larger, 3 even larger",
+            err.to_string()
+        );
+        assert!(InputSource::Example(1).read(get_day(24).unwrap()).is_err());
+    }
+
+    #[test]
+    fn missing_files_are_reported() {
+        let source = InputSource::parse("/nonexistent/day1.txt");
+        let err = source.read(get_day(1).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("/nonexistent/day1.txt"));
+    }
+}
@@ -0,0 +1,84 @@
+pub mod answers;
+pub mod cache;
+mod day1;
+mod day10;
+mod day11;
+mod day12;
+mod day13;
+mod day14;
+mod day15;
+mod day16;
+mod day17;
+mod day18;
+mod day19;
+mod day2;
+mod day20;
+mod day21;
+mod day22;
+mod day23;
+mod day24;
+mod day25;
+mod day3;
+mod day4;
+mod day5;
+mod day6;
+mod day7;
+mod day8;
+mod day9;
+pub mod export;
+pub mod grid;
+pub mod input;
+pub mod memory;
+pub mod output;
+pub mod parse;
+pub mod registry;
+pub mod rng;
+pub mod search;
+pub mod solver;
+pub mod timing;
+pub mod trace;
+
+/// streaming sonar sweep analysis over any window sizes (day 1)
+pub mod sonar {
+    pub use crate::day1::{SonarAnalyser, WindowStats};
+}
+
+/// submarine plans and the positions they go through (day 2)
+pub mod navigation {
+    pub use crate::day2::{
+        parse_plan, trajectory, Interpretation, Movement, Position, Statement, MAX_MOVEMENTS,
+        MAX_NESTING,
+    };
+}
+
+/// binary diagnostic ratings of any width, with their tie policies (day 3)
+pub mod diagnostic {
+    pub use crate::day3::{Bits, ColumnStats, Diagnosis, TiePolicies, TiePolicy};
+}
+
+/// snailfish numbers (day 18), reduced on each addition
+pub mod snailfish {
+    pub use crate::day18::FishNumber;
+}
+
+/// BITS transmission decoder (day 16)
+pub mod bits {
+    pub use crate::day16::Packet;
+}
+
+/// MONAD arithmetic logic unit interpreter (day 24)
+pub mod alu {
+    pub use crate::day24::{decode, Operation, Value, ALU};
+}
+
+/// inclusive cuboids and the on/off reboot steps combining them (day 22)
+pub mod cuboid {
+    pub use crate::day22::{reboot_unsized_reactor as count_lit_cubes, Command, Cuboid};
+}
+
+/// amphipods burrow organisation (day 23)
+pub mod amphipod {
+    pub use crate::day23::{
+        get_least_energy_to_organize_amphipods as least_energy, unfold, Disposition,
+    };
+}
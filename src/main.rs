@@ -1,157 +1,382 @@
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day2;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+mod cli;
+mod runner;
 
+use std::io::{IsTerminal, Write};
 use std::time::{Duration, Instant};
 
+use anyhow::{anyhow, Context, Result};
+use aoc2021::answers::{Answers, Check};
+use aoc2021::cache::AnswerCache;
+use aoc2021::input::InputSource;
+use aoc2021::memory::human_bytes;
+use aoc2021::output::{self, AnswerRecord, OutputFormat};
+use aoc2021::registry::Part;
+use aoc2021::rng::Rng;
+use aoc2021::solver::{Answer, Example};
+use aoc2021::timing::{DayTiming, TimingReport};
+use cli::{Animate, Bench, Export, Options, USAGE};
 use colored::*;
 use itertools::Itertools;
+use runner::{bench_day, run_days};
+
+#[cfg(feature = "count-allocations")]
+#[global_allocator]
+static ALLOCATOR: aoc2021::memory::CountingAllocator = aoc2021::memory::CountingAllocator;
 
 struct Timer {
     start: Instant,
-    last: Instant,
+    report: TimingReport,
 }
 impl Timer {
     fn new() -> Self {
-        let instant = Instant::now();
         Self {
-            last: instant,
-            start: instant,
+            start: Instant::now(),
+            report: TimingReport::default(),
         }
     }
 
-    fn click(&mut self) {
+    fn click(&mut self, timing: DayTiming) {
         static ONE_SECOND: Duration = Duration::from_secs(1);
-        let elapsed = self.last.elapsed();
+        let elapsed = timing.total();
+        let cached = match timing.cached.as_slice() {
+            [] => String::new(),
+            [part] => format!(" (part {} from cache)", part),
+            parts => format!(" (parts {} from cache)", parts.iter().join(" and ")),
+        };
+        let allocations = timing.alloc_total().map_or(String::new(), |a| {
+            format!(
+                ", {} allocations of {}, {} peak",
+                a.allocations,
+                human_bytes(a.bytes),
+                human_bytes(a.peak)
+            )
+        });
         if elapsed > ONE_SECOND {
             println!(
                 "{}\n",
-                format!("*** {} s ***", elapsed.as_secs()).truecolor(255, 128, 0)
+                format!(
+                    "*** {:.3} s{}{} ***",
+                    elapsed.as_secs_f64(),
+                    allocations,
+                    cached
+                )
+                .truecolor(255, 128, 0)
             );
         } else {
             println!(
                 "{}\n",
-                format!("*** {} ms ***", elapsed.as_millis()).truecolor(0, 255, 0)
+                format!(
+                    "*** {:.3} ms{}{} ***",
+                    elapsed.as_secs_f64() * 1000.0,
+                    allocations,
+                    cached
+                )
+                .truecolor(0, 255, 0)
             );
         }
-
-        self.last = Instant::now();
+        self.report.days.push(timing);
     }
 
     fn display_total(&self) {
         println!(
             "{}",
-            format!("*** TOTAL : {} s ***", self.start.elapsed().as_secs()).truecolor(0, 255, 0)
+            format!(
+                "*** TOTAL : {:.3} s ({:.3} s cumulated by days) ***",
+                self.start.elapsed().as_secs_f64(),
+                self.report.total().as_secs_f64()
+            )
+            .truecolor(0, 255, 0)
         );
     }
 }
 
-fn main() {
+fn main() -> Result<()> {
+    let options = Options::parse(std::env::args().skip(1))?;
+    if !std::io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
+    if options.help {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    if options.clear_cache {
+        let cache = AnswerCache::default();
+        let removed = cache.clear()?;
+        println!(
+            "{} cached answers removed from {}",
+            removed,
+            cache.dir().display()
+        );
+        return Ok(());
+    }
+    if let Some(filter) = &options.trace {
+        aoc2021::trace::init(filter.clone());
+    }
+    if options.check {
+        return check(&options);
+    }
+    if let Some(bench) = &options.bench {
+        return run_bench(&options, bench);
+    }
+    if let Some(animate) = &options.animate {
+        return run_animation(&options, animate);
+    }
+    if let Some(export) = &options.export {
+        return run_export(&options, export);
+    }
+    if let Some(generate) = &options.generate {
+        let day = options.days[0];
+        print!(
+            "{}",
+            (day.generate)(&mut Rng::new(generate.seed), generate.size)
+        );
+        return Ok(());
+    }
+    if options.format != OutputFormat::Text {
+        return print_records(&options);
+    }
+
     let mut timer = Timer::new();
     println!("{}", "********".truecolor(0, 255, 0));
-    day1::print_depth_incrs();
-    timer.click();
-
-    day2::print_position();
-    timer.click();
-
-    day3::print_power();
-    timer.click();
-
-    day4::display_bingo();
-    timer.click();
-
-    day5::print_hydrothermals();
-    timer.click();
-
-    day6::print_lanternfishes_counts();
-    timer.click();
-
-    day7::print_crab_alignment();
-    timer.click();
-
-    day8::display_digits();
-    timer.click();
-
-    day9::display_smoke_risks();
-    timer.click();
-
-    day10::print_syntax_check();
-    timer.click();
-
-    day11::display_octopuses_flash_count();
-    timer.click();
-
-    day12::display_pathes();
-    timer.click();
-
-    day13::print_origami_details();
-    timer.click();
-
-    day14::display_polymer();
-    timer.click();
+    run_days(&options, |run| {
+        let example = options.input.example(run.day);
+        let title = match (&options.input, example) {
+            (InputSource::Example(n), Some(example)) => {
+                format!("{} (example {} : {})", run.day.title, n, example.name)
+            }
+            _ => run.day.title.to_string(),
+        };
+        println!("{}", format!("Day {} : {}", run.day.number, title).bold());
+        for (part, answer, description) in &run.answers {
+            println!("{}", description);
+            if let Some(example) = example {
+                print_example_check(example, *part, answer);
+            }
+        }
+        timer.click(run.timing);
+        Ok(())
+    })?;
+    timer.display_total();
+    write_timings(&options, &timer.report)?;
+    println!(
+        "{}",
+        vec!["*"; 26]
+            .iter()
+            .enumerate()
+            .map(|(i, s)| s.truecolor(i as u8 * 10, 255 - i as u8 * 10, i as u8 * 10))
+            .join("")
+    );
+    Ok(())
+}
 
-    day15::display_safest_path();
-    timer.click();
+fn write_timings(options: &Options, report: &TimingReport) -> Result<()> {
+    if let Some((path, format)) = &options.timings {
+        std::fs::write(path, report.render(*format))
+            .with_context(|| format!("could not write timings to {}", path.display()))?;
+    }
+    Ok(())
+}
 
-    day16::print_bits();
-    timer.click();
+// tsv records are printed as soon as their day is solved, json ones once all are
+fn print_records(options: &Options) -> Result<()> {
+    let mut report = TimingReport::default();
+    let mut records = vec![];
+    if options.format == OutputFormat::Tsv {
+        println!("{}", AnswerRecord::TSV_HEADER);
+    }
+    run_days(options, |run| {
+        for (part, answer, _) in run.answers {
+            let record = AnswerRecord {
+                day: run.day.number,
+                part,
+                answer,
+                solve: run.timing.part(part).unwrap_or_default(),
+            };
+            if options.format == OutputFormat::Tsv {
+                println!("{}", record.to_tsv());
+            } else {
+                records.push(record);
+            }
+        }
+        report.days.push(run.timing);
+        Ok(())
+    })?;
+    if options.format == OutputFormat::Json {
+        print!("{}", output::to_json(&records));
+    }
+    write_timings(options, &report)
+}
 
-    day17::display_trajectory();
-    timer.click();
+// the answer is compared with the one the puzzle text gives, when it gives one
+fn print_example_check(example: &Example, part: Part, answer: &Answer) {
+    match Check::new(example.expected(part), answer) {
+        Check::Pass => println!("{}", "  as expected by the example".green()),
+        Check::Fail { expected } => println!(
+            "{}",
+            format!("  the example expects {}", one_line(&expected)).red()
+        ),
+        Check::Missing => {}
+    }
+}
 
-    day18::display_additions();
-    timer.click();
+// multi-line answers are shortened to their first line in the check table
+fn one_line(answer: &str) -> String {
+    let mut lines = answer.lines();
+    let first = lines.next().unwrap_or_default().to_string();
+    if lines.next().is_some() {
+        first + " …"
+    } else {
+        first
+    }
+}
 
-    day19::display_breacons_and_scanners();
-    timer.click();
+fn check(options: &Options) -> Result<()> {
+    let answers_file = options
+        .input
+        .answers_file()
+        .ok_or_else(|| anyhow!("no answers file for this input"))?;
+    let answers = Answers::load(&answers_file)?;
 
-    day20::display_enhanced_img();
-    timer.click();
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+    println!("{}", "day  part  status   answer".bold());
+    run_days(options, |run| {
+        for (part, answer, _) in &run.answers {
+            let (status, details) = match answers.check(run.day.number, *part, answer) {
+                Check::Pass => {
+                    passed += 1;
+                    ("ok".green(), one_line(&answer.to_string()))
+                }
+                Check::Fail { expected } => {
+                    failed += 1;
+                    (
+                        "FAIL".red().bold(),
+                        format!(
+                            "{} (expected {})",
+                            one_line(&answer.to_string()),
+                            one_line(&expected)
+                        ),
+                    )
+                }
+                Check::Missing => {
+                    missing += 1;
+                    let status = match answer {
+                        Answer::None => "-".normal(),
+                        _ => "missing".yellow(),
+                    };
+                    (status, one_line(&answer.to_string()))
+                }
+            };
+            println!(
+                "{:>3}  {:>4}  {:<7}  {}",
+                run.day.number, part, status, details
+            );
+        }
+        Ok(())
+    })?;
 
-    day21::display_dirac_dice_play();
-    timer.click();
+    println!(
+        "\n{} passed, {} failed, {} without expected answer",
+        passed, failed, missing
+    );
+    if failed > 0 {
+        return Err(anyhow!(
+            "{} answers do not match {}",
+            failed,
+            answers_file.display()
+        ));
+    }
+    Ok(())
+}
 
-    day22::display_reactor_reboot();
-    timer.click();
+// frames are either written to files, or drawn over each other when stdout is a terminal
+fn run_animation(options: &Options, animate: &Animate) -> Result<()> {
+    let day = options.days[0];
+    let solution = (day.parse)(&options.input.read(day)?)?;
+    let frames = solution
+        .frames()
+        .ok_or_else(|| anyhow!("day {} has no animation", day.number))?;
+    let (first, last) = animate.steps;
+    let steps = frames
+        .enumerate()
+        .skip(first)
+        .take((last - first).saturating_add(1));
 
-    day23::organize_amphipods();
-    timer.click();
+    if let Some(dir) = &animate.frames {
+        colored::control::set_override(false);
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("could not create {}", dir.display()))?;
+        let mut written = 0;
+        for (step, frame) in steps {
+            let path = dir.join(format!("day{}_step{:04}.txt", day.number, step));
+            std::fs::write(&path, frame)
+                .with_context(|| format!("could not write {}", path.display()))?;
+            written += 1;
+        }
+        println!("{} frames written to {}", written, dir.display());
+        return Ok(());
+    }
 
-    day24::print_larget_serial_accepted_by_monad();
-    timer.click();
+    let terminal = std::io::stdout().is_terminal();
+    for (_, frame) in steps {
+        if terminal {
+            // clear the screen and go back to its top left corner
+            print!("\x1b[2J\x1b[H{}", frame);
+            std::io::stdout().flush()?;
+            std::thread::sleep(animate.delay);
+        } else {
+            println!("{}", frame);
+        }
+    }
+    Ok(())
+}
 
-    day25::find_spot_on_sea_floor();
-    timer.click();
-    timer.display_total();
+fn run_export(options: &Options, export: &Export) -> Result<()> {
+    let day = options.days[0];
+    let solution = (day.parse)(&options.input.read(day)?)?;
+    let picture = solution
+        .picture()
+        .ok_or_else(|| anyhow!("day {} has no picture to export", day.number))?;
+    std::fs::write(&export.path, export.format.encode(&picture, export.scale))
+        .with_context(|| format!("could not write {}", export.path.display()))?;
     println!(
-        "{}",
-        vec!["*"; 26]
-            .iter()
-            .enumerate()
-            .map(|(i, s)| s.truecolor(i as u8 * 10, 255 - i as u8 * 10, i as u8 * 10))
-            .join("")
+        "day {} exported to {} ({}x{} cells)",
+        day.number,
+        export.path.display(),
+        picture.width(),
+        picture.height()
     );
+    Ok(())
+}
+
+fn run_bench(options: &Options, bench: &Bench) -> Result<()> {
+    let ms = |d: Duration| format!("{:.3}", d.as_secs_f64() * 1000.0);
+    for day in &options.days {
+        println!(
+            "{}",
+            format!(
+                "Day {} : {} ({} runs after {} warm-up)",
+                day.number, day.title, bench.runs, bench.warmup
+            )
+            .bold()
+        );
+        println!(
+            "{:<28} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            "ms", "min", "median", "mean", "p95", "stddev"
+        );
+        for (step, stats) in
+            bench_day(day, options, bench).with_context(|| format!("day {}", day.number))?
+        {
+            println!(
+                "{:<28} {:>12} {:>12} {:>12} {:>12} {:>12}",
+                step,
+                ms(stats.min),
+                ms(stats.median),
+                ms(stats.mean),
+                ms(stats.p95),
+                ms(stats.stddev)
+            );
+        }
+        println!();
+    }
+    Ok(())
 }
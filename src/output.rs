@@ -0,0 +1,166 @@
+use crate::registry::Part;
+use crate::solver::Answer;
+use crate::timing::ms;
+use anyhow::{anyhow, Result};
+use std::fmt::Write;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// human friendly descriptions
+    #[default]
+    Text,
+    Json,
+    Tsv,
+}
+
+impl OutputFormat {
+    pub fn parse(format: &str) -> Result<Self> {
+        match format.trim() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "tsv" => Ok(OutputFormat::Tsv),
+            unknown => Err(anyhow!(
+                "'{}' is not a valid format (text, json or tsv)",
+                unknown
+            )),
+        }
+    }
+}
+
+/// one answer with the time spent solving it, parsing excluded
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnswerRecord {
+    pub day: usize,
+    pub part: Part,
+    pub answer: Answer,
+    pub solve: Duration,
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+impl AnswerRecord {
+    pub const TSV_HEADER: &'static str = "day\tpart\tanswer\tms";
+
+    /// multi-line answers are escaped so that each record stays on its line
+    pub fn to_tsv(&self) -> String {
+        let answer = match &self.answer {
+            Answer::None => String::new(),
+            answer => answer
+                .to_string()
+                .replace('\\', "\\\\")
+                .replace('\n', "\\n")
+                .replace('\t', "\\t"),
+        };
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.day,
+            self.part,
+            answer,
+            ms(self.solve)
+        )
+    }
+
+    pub fn to_json(&self) -> String {
+        let answer = match &self.answer {
+            Answer::Number(n) => n.to_string(),
+            Answer::Text(t) => json_string(t),
+            Answer::None => "null".to_string(),
+        };
+        format!(
+            "{{\"day\": {}, \"part\": {}, \"answer\": {}, \"ms\": {}}}",
+            self.day,
+            self.part,
+            answer,
+            ms(self.solve)
+        )
+    }
+}
+
+pub fn to_json(records: &[AnswerRecord]) -> String {
+    if records.is_empty() {
+        return "[]\n".to_string();
+    }
+    let records = records
+        .iter()
+        .map(|r| format!("  {}", r.to_json()))
+        .collect::<Vec<_>>()
+        .join(",\n");
+    format!("[\n{}\n]\n", records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<AnswerRecord> {
+        vec![
+            AnswerRecord {
+                day: 13,
+                part: Part::Two,
+                answer: Answer::Text("Paper(2,1)\n#\t\"".to_string()),
+                solve: Duration::from_micros(1250),
+            },
+            AnswerRecord {
+                day: 25,
+                part: Part::One,
+                answer: Answer::Number(523),
+                solve: Duration::from_millis(40),
+            },
+            AnswerRecord {
+                day: 25,
+                part: Part::Two,
+                answer: Answer::None,
+                solve: Duration::ZERO,
+            },
+        ]
+    }
+
+    #[test]
+    fn records_can_be_exported_as_tsv() {
+        let lines: Vec<_> = records().iter().map(AnswerRecord::to_tsv).collect();
+        assert_eq!(
+            vec![
+                "13\t2\tPaper(2,1)\\n#\\t\"\t1.250",
+                "25\t1\t523\t40.000",
+                "25\t2\t\t0.000"
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn records_can_be_exported_as_json() {
+        assert_eq!(
+            r#"[
+  {"day": 13, "part": 2, "answer": "Paper(2,1)\n#\t\"", "ms": 1.250},
+  {"day": 25, "part": 1, "answer": 523, "ms": 40.000},
+  {"day": 25, "part": 2, "answer": null, "ms": 0.000}
+]
+"#,
+            to_json(&records())
+        );
+        assert_eq!("[]\n", to_json(&[]));
+    }
+
+    #[test]
+    fn formats_can_be_parsed() {
+        assert_eq!(OutputFormat::Json, OutputFormat::parse("json").unwrap());
+        assert_eq!(OutputFormat::Tsv, OutputFormat::parse("tsv").unwrap());
+        assert!(OutputFormat::parse("csv").is_err());
+    }
+}
@@ -0,0 +1,189 @@
+use anyhow::{anyhow, Result};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// malformed puzzle input, located by its line and column (both starting at 1)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn at(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column: Some(column),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.column {
+            Some(column) => write!(
+                f,
+                "line {}, column {} : {}",
+                self.line, column, self.message
+            ),
+            None => write!(f, "line {} : {}", self.line, self.message),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// parse each line which is not blank, the failing line being reported
+pub fn parse_lines<T>(input: &str, parse: impl FnMut(&str) -> Result<T>) -> Result<Vec<T>> {
+    parse_numbered_lines(input.lines().enumerate(), parse)
+}
+
+/// same as parse_lines, for (index, line) taken from the middle of an input
+pub fn parse_numbered_lines<'a, T>(
+    lines: impl IntoIterator<Item = (usize, &'a str)>,
+    mut parse: impl FnMut(&str) -> Result<T>,
+) -> Result<Vec<T>> {
+    lines
+        .into_iter()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            parse(line).map_err(|e| ParseError::new(i + 1, format!("'{}' : {:#}", line, e)).into())
+        })
+        .collect()
+}
+
+/// values on a single line (of index line_index) split by separator, the failing one being located
+pub fn parse_separated<T>(line: &str, line_index: usize, separator: char) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let mut column = 1;
+    let mut values = vec![];
+    for value in line.split(separator) {
+        let trimmed = value.trim_start();
+        let value_column = column + value.chars().count() - trimmed.chars().count();
+        values.push(trimmed.trim_end().parse::<T>().map_err(|e| {
+            ParseError::at(
+                line_index + 1,
+                value_column,
+                format!("'{}' : {}", trimmed.trim_end(), e),
+            )
+        })?);
+        column += value.chars().count() + 1;
+    }
+    Ok(values)
+}
+
+/// a non empty rectangular grid where each character is converted by cell
+pub fn parse_grid<T>(input: &str, cell: impl FnMut(char) -> Option<T>) -> Result<Vec<Vec<T>>> {
+    parse_numbered_grid(input.lines().enumerate(), cell)
+}
+
+/// same as parse_grid, for (index, line) taken from the middle of an input
+pub fn parse_numbered_grid<'a, T>(
+    lines: impl IntoIterator<Item = (usize, &'a str)>,
+    mut cell: impl FnMut(char) -> Option<T>,
+) -> Result<Vec<Vec<T>>> {
+    let mut grid: Vec<Vec<T>> = vec![];
+    for (i, line) in lines {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let row = line
+            .chars()
+            .enumerate()
+            .map(|(j, c)| {
+                cell(c).ok_or_else(|| ParseError::at(i + 1, j + 1, format!("unexpected '{}'", c)))
+            })
+            .collect::<Result<Vec<T>, _>>()?;
+        if let Some(first) = grid.first() {
+            if first.len() != row.len() {
+                return Err(ParseError::new(
+                    i + 1,
+                    format!(
+                        "{} cells while previous lines have {}",
+                        row.len(),
+                        first.len()
+                    ),
+                )
+                .into());
+            }
+        }
+        grid.push(row);
+    }
+    if grid.is_empty() {
+        return Err(anyhow!("no grid found"));
+    }
+    Ok(grid)
+}
+
+pub fn parse_digit_grid(input: &str) -> Result<Vec<Vec<usize>>> {
+    parse_grid(input, |c| c.to_digit(10).map(|d| d as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failing_lines_are_located() {
+        assert_eq!(
+            vec![1, 2],
+            parse_lines("1\n\n2\n", |l| Ok(l.parse::<usize>()?)).unwrap()
+        );
+
+        let err = parse_lines("1\n\n2x\n", |l| Ok(l.parse::<usize>()?)).unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!(3, err.line);
+        assert_eq!(None, err.column);
+        assert_eq!(
+            "line 3 : '2x' : invalid digit found in string",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn separated_values_are_located() {
+        assert_eq!(
+            vec![3, 4, 3],
+            parse_separated::<usize>("3,4,3\n".trim(), 0, ',').unwrap()
+        );
+
+        let err = parse_separated::<usize>("3, 4,x3", 6, ',').unwrap_err();
+        assert_eq!(
+            &ParseError::at(7, 6, "'x3' : invalid digit found in string"),
+            err.downcast_ref::<ParseError>().unwrap()
+        );
+    }
+
+    #[test]
+    fn grids_are_checked() {
+        assert_eq!(
+            vec![vec![1, 2], vec![3, 4]],
+            parse_digit_grid("12\n34\n").unwrap()
+        );
+
+        let err = parse_digit_grid("12\n3x\n").unwrap_err();
+        assert_eq!(
+            &ParseError::at(2, 2, "unexpected 'x'"),
+            err.downcast_ref::<ParseError>().unwrap()
+        );
+
+        let err = parse_digit_grid("12\n345\n").unwrap_err();
+        assert_eq!(2, err.downcast_ref::<ParseError>().unwrap().line);
+
+        assert!(parse_digit_grid("\n").is_err());
+    }
+}
@@ -0,0 +1,335 @@
+use crate::{
+    day1, day10, day11, day12, day13, day14, day15, day16, day17, day18, day19, day2, day20, day21,
+    day22, day23, day24, day25, day3, day4, day5, day6, day7, day8, day9,
+    rng::Rng,
+    solver::{self, Example, Solution, Solver},
+};
+use anyhow::{anyhow, Result};
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub fn all() -> &'static [Part; 2] {
+        static PARTS: [Part; 2] = [Part::One, Part::Two];
+        &PARTS
+    }
+
+    pub fn parse(part: &str) -> Result<Self> {
+        match part.trim() {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            unknown => Err(anyhow!("'{}' is not a valid part (1 or 2)", unknown)),
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Part::One => "1",
+            Part::Two => "2",
+        })
+    }
+}
+
+pub struct Day {
+    pub number: usize,
+    pub title: &'static str,
+    /// name of the puzzle input file in the resources directory
+    pub input: &'static str,
+    /// parses the input to print the described answer of a part
+    pub print: fn(&str, Part),
+    pub parse: fn(&str) -> Result<Box<dyn Solution>>,
+    pub generate: fn(&mut Rng, usize) -> String,
+    /// solver version, cached answers of another one are ignored
+    pub version: u32,
+    pub examples: &'static [Example],
+}
+
+static DAYS: [Day; 25] = [
+    Day {
+        number: 1,
+        title: "Sonar Sweep",
+        input: "day1_sonar_depths.txt",
+        print: day1::print_depth_incrs,
+        parse: solver::parse::<day1::SonarReport>,
+        generate: day1::SonarReport::generate,
+        version: day1::SonarReport::VERSION,
+        examples: day1::EXAMPLES,
+    },
+    Day {
+        number: 2,
+        title: "Dive!",
+        input: "day2_movements.txt",
+        print: day2::print_position,
+        parse: solver::parse::<day2::Course>,
+        generate: day2::Course::generate,
+        version: day2::Course::VERSION,
+        examples: day2::EXAMPLES,
+    },
+    Day {
+        number: 3,
+        title: "Binary Diagnostic",
+        input: "day3_diagnosis.txt",
+        print: day3::print_power,
+        parse: solver::parse::<day3::Diagnosis>,
+        generate: day3::Diagnosis::generate,
+        version: day3::Diagnosis::VERSION,
+        examples: day3::EXAMPLES,
+    },
+    Day {
+        number: 4,
+        title: "Giant Squid",
+        input: "day4_bingo.txt",
+        print: day4::display_bingo,
+        parse: solver::parse::<day4::BingoGame>,
+        generate: day4::BingoGame::generate,
+        version: day4::BingoGame::VERSION,
+        examples: day4::EXAMPLES,
+    },
+    Day {
+        number: 5,
+        title: "Hydrothermal Venture",
+        input: "day5_hydrothermal_vents.txt",
+        print: day5::print_hydrothermals,
+        parse: solver::parse::<day5::HydrothermalVents>,
+        generate: day5::HydrothermalVents::generate,
+        version: day5::HydrothermalVents::VERSION,
+        examples: day5::EXAMPLES,
+    },
+    Day {
+        number: 6,
+        title: "Lanternfish",
+        input: "day6_lanternfishes.txt",
+        print: day6::print_lanternfishes_counts,
+        parse: solver::parse::<day6::Lanternfishes>,
+        generate: day6::Lanternfishes::generate,
+        version: day6::Lanternfishes::VERSION,
+        examples: day6::EXAMPLES,
+    },
+    Day {
+        number: 7,
+        title: "The Treachery of Whales",
+        input: "day7_crabs_pos.txt",
+        print: day7::print_crab_alignment,
+        parse: solver::parse::<day7::Crabs>,
+        generate: day7::Crabs::generate,
+        version: day7::Crabs::VERSION,
+        examples: day7::EXAMPLES,
+    },
+    Day {
+        number: 8,
+        title: "Seven Segment Search",
+        input: "day8_digits_displays.txt",
+        print: day8::display_digits,
+        parse: solver::parse::<day8::Displays>,
+        generate: day8::Displays::generate,
+        version: day8::Displays::VERSION,
+        examples: day8::EXAMPLES,
+    },
+    Day {
+        number: 9,
+        title: "Smoke Basin",
+        input: "day9_heights.txt",
+        print: day9::display_smoke_risks,
+        parse: solver::parse::<day9::HeightMap>,
+        generate: day9::HeightMap::generate,
+        version: day9::HeightMap::VERSION,
+        examples: day9::EXAMPLES,
+    },
+    Day {
+        number: 10,
+        title: "Syntax Scoring",
+        input: "day10_navigation_syntax.txt",
+        print: day10::print_syntax_check,
+        parse: solver::parse::<day10::NavigationSubsystem>,
+        generate: day10::NavigationSubsystem::generate,
+        version: day10::NavigationSubsystem::VERSION,
+        examples: day10::EXAMPLES,
+    },
+    Day {
+        number: 11,
+        title: "Dumbo Octopus",
+        input: "day11_octopuses_energy.txt",
+        print: day11::display_octopuses_flash_count,
+        parse: solver::parse::<day11::Octopuses>,
+        generate: day11::Octopuses::generate,
+        version: day11::Octopuses::VERSION,
+        examples: day11::EXAMPLES,
+    },
+    Day {
+        number: 12,
+        title: "Passage Pathing",
+        input: "day12_connections.txt",
+        print: day12::display_pathes,
+        parse: solver::parse::<day12::CavesMap>,
+        generate: day12::CavesMap::generate,
+        version: day12::CavesMap::VERSION,
+        examples: day12::EXAMPLES,
+    },
+    Day {
+        number: 13,
+        title: "Transparent Origami",
+        input: "day13_transparent_paper.txt",
+        print: day13::print_origami_details,
+        parse: solver::parse::<day13::Paper>,
+        generate: day13::Paper::generate,
+        version: day13::Paper::VERSION,
+        examples: day13::EXAMPLES,
+    },
+    Day {
+        number: 14,
+        title: "Extended Polymerization",
+        input: "day14_chemistry.txt",
+        print: day14::display_polymer,
+        parse: solver::parse::<day14::Polymer>,
+        generate: day14::Polymer::generate,
+        version: day14::Polymer::VERSION,
+        examples: day14::EXAMPLES,
+    },
+    Day {
+        number: 15,
+        title: "Chiton",
+        input: "day15_risks.txt",
+        print: day15::display_safest_path,
+        parse: solver::parse::<day15::RiskMap>,
+        generate: day15::RiskMap::generate,
+        version: day15::RiskMap::VERSION,
+        examples: day15::EXAMPLES,
+    },
+    Day {
+        number: 16,
+        title: "Packet Decoder",
+        input: "day16_bits.txt",
+        print: day16::print_bits,
+        parse: solver::parse::<day16::Packet>,
+        generate: day16::Packet::generate,
+        version: day16::Packet::VERSION,
+        examples: day16::EXAMPLES,
+    },
+    Day {
+        number: 17,
+        title: "Trick Shot",
+        input: "day17_targetarea.txt",
+        print: day17::display_trajectory,
+        parse: solver::parse::<day17::TargetArea>,
+        generate: day17::TargetArea::generate,
+        version: day17::TargetArea::VERSION,
+        examples: day17::EXAMPLES,
+    },
+    Day {
+        number: 18,
+        title: "Snailfish",
+        input: "day18_fish_numbers.txt",
+        print: day18::display_additions,
+        parse: solver::parse::<day18::Homework>,
+        generate: day18::Homework::generate,
+        version: day18::Homework::VERSION,
+        examples: day18::EXAMPLES,
+    },
+    Day {
+        number: 19,
+        title: "Beacon Scanner",
+        input: "day19_scanners.txt",
+        print: day19::display_breacons_and_scanners,
+        parse: solver::parse::<day19::BeaconMap>,
+        generate: day19::BeaconMap::generate,
+        version: day19::BeaconMap::VERSION,
+        examples: day19::EXAMPLES,
+    },
+    Day {
+        number: 20,
+        title: "Trench Map",
+        input: "day20_enhancer.txt",
+        print: day20::display_enhanced_img,
+        parse: solver::parse::<day20::TrenchMap>,
+        generate: day20::TrenchMap::generate,
+        version: day20::TrenchMap::VERSION,
+        examples: day20::EXAMPLES,
+    },
+    Day {
+        number: 21,
+        title: "Dirac Dice",
+        input: "day21_dirac_dice.txt",
+        print: day21::display_dirac_dice_play,
+        parse: solver::parse::<day21::DiracDice>,
+        generate: day21::DiracDice::generate,
+        version: day21::DiracDice::VERSION,
+        examples: day21::EXAMPLES,
+    },
+    Day {
+        number: 22,
+        title: "Reactor Reboot",
+        input: "day22_reboot_sequence.txt",
+        print: day22::display_reactor_reboot,
+        parse: solver::parse::<day22::RebootSequence>,
+        generate: day22::RebootSequence::generate,
+        version: day22::RebootSequence::VERSION,
+        examples: day22::EXAMPLES,
+    },
+    Day {
+        number: 23,
+        title: "Amphipod",
+        input: "day23_amphipods.txt",
+        print: day23::organize_amphipods,
+        parse: solver::parse::<day23::Burrow>,
+        generate: day23::Burrow::generate,
+        version: day23::Burrow::VERSION,
+        examples: day23::EXAMPLES,
+    },
+    Day {
+        number: 24,
+        title: "Arithmetic Logic Unit",
+        input: "day24_monad.txt",
+        print: day24::print_larget_serial_accepted_by_monad,
+        parse: solver::parse::<day24::Monad>,
+        generate: day24::Monad::generate,
+        version: day24::Monad::VERSION,
+        examples: day24::EXAMPLES,
+    },
+    Day {
+        number: 25,
+        title: "Sea Cucumber",
+        input: "day25_sea_floor.txt",
+        print: day25::find_spot_on_sea_floor,
+        parse: solver::parse::<day25::SeaFloor>,
+        generate: day25::SeaFloor::generate,
+        version: day25::SeaFloor::VERSION,
+        examples: day25::EXAMPLES,
+    },
+];
+
+pub fn all_days() -> &'static [Day] {
+    &DAYS
+}
+
+pub fn get_day(number: usize) -> Result<&'static Day> {
+    DAYS.iter().find(|d| d.number == number).ok_or_else(|| {
+        anyhow!(
+            "unknown day {} (available days : {}..={})",
+            number,
+            DAYS[0].number,
+            DAYS[DAYS.len() - 1].number
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_are_registered_in_order() {
+        for (i, day) in all_days().iter().enumerate() {
+            assert_eq!(i + 1, day.number);
+        }
+        assert!(get_day(19).is_ok());
+        assert!(get_day(0).is_err());
+        assert!(get_day(26).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+/// small seeded generator (SplitMix64), so that a seed gives the same input on every platform
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// uniform in 0..n, n being positive
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "cannot draw below 0");
+        // rejecting the last incomplete block keeps the draw unbiased
+        let zone = u64::MAX - u64::MAX % n as u64;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return (value % n as u64) as usize;
+            }
+        }
+    }
+
+    /// uniform in low..=high
+    pub fn between(&mut self, low: isize, high: isize) -> isize {
+        assert!(low <= high, "empty range {}..={}", low, high);
+        low + self.below((high - low) as usize + 1) as isize
+    }
+
+    /// true once every n draws on average
+    pub fn one_in(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_depend_only_on_the_seed() {
+        let draws = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..5).map(|_| rng.below(1000)).collect::<Vec<_>>()
+        };
+        assert_eq!(draws(7), draws(7));
+        assert_ne!(draws(7), draws(8));
+    }
+
+    #[test]
+    fn draws_stay_in_range() {
+        let mut rng = Rng::new(42);
+        for _ in 0..1000 {
+            assert!(rng.below(3) < 3);
+            assert!((-2..=2).contains(&rng.between(-2, 2)));
+        }
+        let mut items: Vec<_> = (0..10).collect();
+        rng.shuffle(&mut items);
+        items.sort();
+        assert_eq!((0..10).collect::<Vec<_>>(), items);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::cli::{Bench, Options};
+use anyhow::{anyhow, Context, Result};
+use aoc2021::cache::CachedAnswer;
+use aoc2021::memory::{self, AllocMeter};
+use aoc2021::registry::{Day, Part};
+use aoc2021::solver::Answer;
+use aoc2021::solver::Solution;
+use aoc2021::timing::{DayTiming, Stats};
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+pub struct DayRun {
+    pub day: &'static Day,
+    /// answer, and its human friendly description, of each selected part
+    pub answers: Vec<(Part, Answer, String)>,
+    pub timing: DayTiming,
+}
+
+/// the input is only parsed when a selected part is not cached ; allocations are
+/// only measured when counted, and not across parallel days which would mix them
+fn run_day(day: &'static Day, options: &Options) -> Result<DayRun> {
+    let input = options.input.read(day)?;
+    let metered = memory::is_counting() && !options.parallel;
+    let mut timing = DayTiming::new(day.number);
+    let mut parsed: Option<Box<dyn Solution>> = None;
+
+    let mut answers = vec![];
+    for part in &options.parts {
+        let start = Instant::now();
+        if let Some(cached) = options
+            .cache
+            .as_ref()
+            .and_then(|c| c.get(day, *part, &input))
+        {
+            timing.parts.push((*part, start.elapsed()));
+            timing.cached.push(*part);
+            answers.push((*part, cached.answer, cached.description));
+            continue;
+        }
+
+        if parsed.is_none() {
+            let meter = AllocMeter::start();
+            let start = Instant::now();
+            parsed = Some((day.parse)(&input)?);
+            timing.parse = start.elapsed();
+            if metered {
+                timing.parse_alloc = Some(meter.stop());
+            }
+        }
+        let solution = parsed.as_deref().expect("parsed above");
+
+        let meter = AllocMeter::start();
+        let start = Instant::now();
+        let answer = solution.solve(*part)?;
+        timing.parts.push((*part, start.elapsed()));
+        if metered {
+            timing.parts_alloc.push((*part, meter.stop()));
+        }
+        let description = solution.describe(*part, &answer);
+        if let Some(cache) = &options.cache {
+            let cached = CachedAnswer {
+                answer: answer.clone(),
+                description: description.clone(),
+            };
+            cache.store(day, *part, &input, &cached)?;
+        }
+        answers.push((*part, answer, description));
+    }
+
+    Ok(DayRun {
+        day,
+        answers,
+        timing,
+    })
+}
+
+/// run the selected days, each run being handed over in calendar order,
+/// even when days are scheduled across the rayon thread pool
+pub fn run_days(options: &Options, mut on_day: impl FnMut(DayRun) -> Result<()>) -> Result<()> {
+    let run =
+        |day: &&'static Day| run_day(day, options).with_context(|| format!("day {}", day.number));
+
+    if !options.parallel {
+        for day in &options.days {
+            on_day(run(day)?)?;
+        }
+        return Ok(());
+    }
+
+    // set once a day failed, for the days not started yet to be skipped
+    let cancelled = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+    std::thread::scope(|scope| {
+        let cancelled = &cancelled;
+        scope.spawn(move || {
+            options
+                .days
+                .par_iter()
+                .enumerate()
+                .for_each_with(sender, |sender, (i, day)| {
+                    if !cancelled.load(Ordering::Relaxed) {
+                        // the receiver is only gone once an earlier day failed
+                        let _ = sender.send((i, run(day)));
+                    }
+                });
+        });
+
+        // runs completed ahead of their turn wait here
+        let mut pending = BTreeMap::new();
+        let mut next = 0;
+        for (i, day_run) in receiver {
+            pending.insert(i, day_run);
+            while let Some(day_run) = pending.remove(&next) {
+                if let Err(e) = day_run.and_then(&mut on_day) {
+                    cancelled.store(true, Ordering::Relaxed);
+                    return Err(e);
+                }
+                next += 1;
+            }
+        }
+        Ok(())
+    })
+}
+
+enum Step {
+    Parse,
+    Part(Part),
+    Variant(&'static str, Part),
+}
+
+impl Step {
+    fn name(&self) -> String {
+        match self {
+            Step::Parse => "parse".to_string(),
+            Step::Part(part) => format!("part {}", part),
+            Step::Variant(name, part) => format!("part {} ({})", part, name),
+        }
+    }
+}
+
+/// a fresh solution is parsed for each measure, so that nothing cached by a step is reused
+fn measure(input: &str, day: &Day, step: &Step) -> Result<(Duration, Option<Answer>)> {
+    let start = Instant::now();
+    let solution: Box<dyn Solution> = (day.parse)(input)?;
+    let parsed = start.elapsed();
+
+    let start = Instant::now();
+    let answer = match step {
+        Step::Parse => return Ok((parsed, None)),
+        Step::Part(part) => solution.solve(*part)?,
+        Step::Variant(name, _) => solution
+            .solve_variant(name)
+            .ok_or_else(|| anyhow!("unknown variant '{}'", name))?,
+    };
+    Ok((start.elapsed(), Some(answer)))
+}
+
+/// statistics of each step of a day : parsing, selected parts then their variants
+pub fn bench_day(
+    day: &'static Day,
+    options: &Options,
+    bench: &Bench,
+) -> Result<Vec<(String, Stats)>> {
+    let input = options.input.read(day)?;
+
+    let mut steps = vec![Step::Parse];
+    steps.extend(options.parts.iter().map(|p| Step::Part(*p)));
+    steps.extend(
+        (day.parse)(&input)?
+            .variants()
+            .into_iter()
+            .filter(|(_, part)| options.parts.contains(part))
+            .map(|(name, part)| Step::Variant(name, part)),
+    );
+
+    let mut answers: Vec<(Part, Answer)> = vec![];
+    let mut results = vec![];
+    for step in steps {
+        let mut samples = Vec::with_capacity(bench.runs);
+        let mut answer = None;
+        for run in 0..bench.warmup + bench.runs {
+            let (elapsed, step_answer) = measure(&input, day, &step)?;
+            if run >= bench.warmup {
+                samples.push(elapsed);
+            }
+            answer = step_answer;
+        }
+
+        match (&step, answer) {
+            (Step::Part(part), Some(answer)) => answers.push((*part, answer)),
+            (Step::Variant(name, part), Some(answer)) => {
+                if let Some((_, expected)) = answers.iter().find(|(p, _)| p == part) {
+                    if *expected != answer {
+                        return Err(anyhow!(
+                            "day {} variant '{}' answers {} instead of {}",
+                            day.number,
+                            name,
+                            answer,
+                            expected
+                        ));
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        let stats = Stats::from_samples(&samples).expect("bench runs at least once");
+        results.push((step.name(), stats));
+    }
+    Ok(results)
+}
@@ -0,0 +1,242 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+/// cheapest way found from the start to a goal, both included in states
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchPath<S> {
+    pub cost: usize,
+    pub states: Vec<S>,
+}
+
+/// every state met during a search is stored once, and linked to the one it was reached from
+struct Explored<S> {
+    states: Vec<S>,
+    indexes: HashMap<S, usize>,
+    parents: Vec<usize>,
+    costs: Vec<usize>,
+}
+
+impl<S: Clone + Eq + Hash> Explored<S> {
+    fn new(start: S) -> Self {
+        Self {
+            states: vec![start.clone()],
+            indexes: HashMap::from([(start, 0)]),
+            parents: vec![0],
+            costs: vec![0],
+        }
+    }
+
+    /// index of the state if it is new or reached more cheaply than before
+    fn improve(&mut self, state: S, parent: usize, cost: usize) -> Option<usize> {
+        match self.indexes.entry(state) {
+            Entry::Occupied(entry) => {
+                let index = *entry.get();
+                if cost >= self.costs[index] {
+                    return None;
+                }
+                self.parents[index] = parent;
+                self.costs[index] = cost;
+                Some(index)
+            }
+            Entry::Vacant(entry) => {
+                let index = self.states.len();
+                self.states.push(entry.key().clone());
+                entry.insert(index);
+                self.parents.push(parent);
+                self.costs.push(cost);
+                Some(index)
+            }
+        }
+    }
+
+    fn path_to(&self, mut index: usize) -> SearchPath<S> {
+        let cost = self.costs[index];
+        let mut states = vec![self.states[index].clone()];
+        while index != 0 {
+            index = self.parents[index];
+            states.push(self.states[index].clone());
+        }
+        states.reverse();
+        SearchPath { cost, states }
+    }
+}
+
+/// cheapest path to a goal, successors being given with the cost to reach them
+pub fn dijkstra<S, I>(
+    start: S,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<SearchPath<S>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, usize)>,
+{
+    astar(start, successors, |_| 0, is_goal)
+}
+
+/// same as dijkstra, the heuristic must never overestimate the remaining cost to a goal
+pub fn astar<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> usize,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<SearchPath<S>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, usize)>,
+{
+    let mut frontier = BinaryHeap::from([Reverse((heuristic(&start), 0, 0))]);
+    let mut explored = Explored::new(start);
+
+    while let Some(Reverse((_, cost, index))) = frontier.pop() {
+        // a cheaper way to this state was found after it was queued
+        if cost > explored.costs[index] {
+            continue;
+        }
+        let state = explored.states[index].clone();
+        if is_goal(&state) {
+            return Some(explored.path_to(index));
+        }
+        for (next, step_cost) in successors(&state) {
+            let next_cost = cost + step_cost;
+            let estimate = next_cost + heuristic(&next);
+            if let Some(next_index) = explored.improve(next, index, next_cost) {
+                frontier.push(Reverse((estimate, next_cost, next_index)));
+            }
+        }
+    }
+    None
+}
+
+/// path to a goal in the fewest steps, each step costing 1
+pub fn bfs<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<SearchPath<S>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut frontier = VecDeque::from([0]);
+    let mut explored = Explored::new(start);
+
+    while let Some(index) = frontier.pop_front() {
+        let state = explored.states[index].clone();
+        if is_goal(&state) {
+            return Some(explored.path_to(index));
+        }
+        let cost = explored.costs[index] + 1;
+        for next in successors(&state) {
+            if !explored.indexes.contains_key(&next) {
+                frontier.extend(explored.improve(next, index, cost));
+            }
+        }
+    }
+    None
+}
+
+/// number of distinct paths from the start to a goal, a goal ending its path ; None when
+/// a state leads back to itself, as there would be infinitely many paths
+pub fn count_paths<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<usize>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    // inside holds the states whose paths are being counted, down to the current one
+    fn count<S, I>(
+        state: S,
+        successors: &mut impl FnMut(&S) -> I,
+        is_goal: &mut impl FnMut(&S) -> bool,
+        counts: &mut HashMap<S, usize>,
+        inside: &mut HashSet<S>,
+    ) -> Option<usize>
+    where
+        S: Clone + Eq + Hash,
+        I: IntoIterator<Item = S>,
+    {
+        if is_goal(&state) {
+            return Some(1);
+        }
+        if let Some(known) = counts.get(&state) {
+            return Some(*known);
+        }
+        if !inside.insert(state.clone()) {
+            return None;
+        }
+        let mut total = 0;
+        for next in successors(&state).into_iter().collect::<Vec<_>>() {
+            total += count(next, successors, is_goal, counts, inside)?;
+        }
+        inside.remove(&state);
+        counts.insert(state, total);
+        Some(total)
+    }
+
+    count(
+        start,
+        &mut successors,
+        &mut is_goal,
+        &mut HashMap::new(),
+        &mut HashSet::new(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a -1-> b -1-> d, a -5-> c -1-> d, b -4-> c
+    fn edges(node: &char) -> Vec<(char, usize)> {
+        match node {
+            'a' => vec![('b', 1), ('c', 5)],
+            'b' => vec![('c', 4), ('d', 1)],
+            'c' => vec![('d', 1)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn cheapest_paths_are_found() {
+        let path = dijkstra('a', edges, |n| *n == 'd').unwrap();
+        assert_eq!(2, path.cost);
+        assert_eq!(vec!['a', 'b', 'd'], path.states);
+
+        let path = dijkstra('a', edges, |n| *n == 'c').unwrap();
+        assert_eq!(5, path.cost);
+        assert_eq!(None, dijkstra('c', edges, |n| *n == 'a'));
+
+        let path = astar('a', edges, |n| if *n == 'd' { 0 } else { 1 }, |n| *n == 'd');
+        assert_eq!(Some(2), path.map(|p| p.cost));
+    }
+
+    #[test]
+    fn shortest_paths_are_found() {
+        let successors = |n: &usize| vec![n + 1, n * 3];
+        let path = bfs(1, successors, |n| *n == 10).unwrap();
+        assert_eq!(3, path.cost);
+        assert_eq!(vec![1, 3, 9, 10], path.states);
+        assert_eq!(0, bfs(1, successors, |n| *n == 1).unwrap().cost);
+    }
+
+    #[test]
+    fn paths_are_counted() {
+        let successors = |n: &char| edges(n).into_iter().map(|(n, _)| n);
+        assert_eq!(Some(3), count_paths('a', successors, |n| *n == 'd'));
+        assert_eq!(Some(1), count_paths('c', successors, |n| *n == 'd'));
+        assert_eq!(Some(0), count_paths('d', successors, |n| *n == 'a'));
+
+        let around = |n: &usize| vec![(n + 1) % 3, 3];
+        assert_eq!(None, count_paths(0, around, |n| *n == 3));
+        assert_eq!(
+            Some(1),
+            count_paths(0, |n: &usize| vec![n + 1], |n| *n == 3)
+        );
+    }
+}
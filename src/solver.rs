@@ -0,0 +1,162 @@
+use crate::export::Color;
+use crate::grid::Grid;
+use crate::registry::Part;
+use crate::rng::Rng;
+use anyhow::Result;
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Number(usize),
+    Text(String),
+    /// for puzzles without a second part
+    None,
+}
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        Answer::Number(value)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => n.fmt(f),
+            Answer::Text(t) => f.write_str(t),
+            Answer::None => f.write_str("-"),
+        }
+    }
+}
+
+/// successive states of a puzzle drawn for the terminal, starting with the initial one
+pub type Frames<'a> = Box<dyn Iterator<Item = String> + 'a>;
+
+/// alternative strategy for one part, compared with the default one by benchmarks
+pub struct Variant<S> {
+    pub name: &'static str,
+    pub part: Part,
+    pub solve: fn(&S) -> Answer,
+}
+
+/// official worked example of a puzzle, with the answers given by its text
+pub struct Example {
+    pub name: &'static str,
+    pub input: &'static str,
+    pub part1: Option<&'static str>,
+    pub part2: Option<&'static str>,
+}
+
+impl Example {
+    pub fn expected(&self, part: Part) -> Option<&'static str> {
+        match part {
+            Part::One => self.part1,
+            Part::Two => self.part2,
+        }
+    }
+}
+
+/// Each day parses its puzzle input once, then answers both parts from it
+pub trait Solver: Sized {
+    /// to be bumped whenever a change could give other answers, so that cached ones are dropped
+    const VERSION: u32 = 1;
+
+    fn parse(input: &str) -> Result<Self>;
+
+    fn part1(&self) -> Answer;
+
+    fn part2(&self) -> Answer;
+
+    /// random puzzle input accepted by parse, size scaling what the day is made of
+    fn generate(rng: &mut Rng, size: usize) -> String;
+
+    /// answer of a part, for the days whose parts can fail on an input they parsed
+    fn try_solve(&self, part: Part) -> Result<Answer> {
+        Ok(match part {
+            Part::One => self.part1(),
+            Part::Two => self.part2(),
+        })
+    }
+
+    fn variants() -> Vec<Variant<Self>> {
+        vec![]
+    }
+
+    /// human friendly sentence for an answer
+    fn describe(part: Part, answer: &Answer) -> String {
+        format!("part {} : {}", part, answer)
+    }
+
+    /// only days evolving a state step by step are animated
+    fn frames(&self) -> Option<Frames<'_>> {
+        None
+    }
+
+    /// spatial result of the puzzle, for the days which have one
+    fn picture(&self) -> Option<Grid<Color>> {
+        None
+    }
+}
+
+/// object safe view of a parsed Solver, so that days can be registered together
+pub trait Solution {
+    fn solve(&self, part: Part) -> Result<Answer>;
+
+    fn describe(&self, part: Part, answer: &Answer) -> String;
+
+    /// name and part of each alternative strategy
+    fn variants(&self) -> Vec<(&'static str, Part)>;
+
+    fn solve_variant(&self, name: &str) -> Option<Answer>;
+
+    fn frames(&self) -> Option<Frames<'_>>;
+
+    fn picture(&self) -> Option<Grid<Color>>;
+}
+
+impl<S: Solver> Solution for S {
+    fn solve(&self, part: Part) -> Result<Answer> {
+        self.try_solve(part)
+    }
+
+    fn describe(&self, part: Part, answer: &Answer) -> String {
+        S::describe(part, answer)
+    }
+
+    fn variants(&self) -> Vec<(&'static str, Part)> {
+        S::variants().iter().map(|v| (v.name, v.part)).collect()
+    }
+
+    fn solve_variant(&self, name: &str) -> Option<Answer> {
+        S::variants()
+            .into_iter()
+            .find(|v| v.name == name)
+            .map(|v| (v.solve)(self))
+    }
+
+    fn frames(&self) -> Option<Frames<'_>> {
+        S::frames(self)
+    }
+
+    fn picture(&self) -> Option<Grid<Color>> {
+        S::picture(self)
+    }
+}
+
+/// prints the answer of a part as described by its day, or why it could not be given
+pub fn print_answer<S: Solver>(input: &str, part: Part) {
+    match S::parse(input).and_then(|solution| solution.try_solve(part)) {
+        Ok(answer) => println!("{}", S::describe(part, &answer)),
+        Err(e) => eprintln!("{:#}", e),
+    }
+}
+
+pub fn parse<S: Solver + 'static>(input: &str) -> Result<Box<dyn Solution>> {
+    Ok(Box::new(S::parse(input)?))
+}
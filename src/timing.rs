@@ -0,0 +1,394 @@
+use crate::memory::AllocStats;
+use crate::registry::Part;
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use std::fmt::Write;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayTiming {
+    pub day: usize,
+    pub parse: Duration,
+    pub parts: Vec<(Part, Duration)>,
+    /// parts answered from the cache, their duration being the lookup one
+    pub cached: Vec<Part>,
+    /// only measured when the allocations are counted
+    pub parse_alloc: Option<AllocStats>,
+    pub parts_alloc: Vec<(Part, AllocStats)>,
+}
+
+impl DayTiming {
+    pub fn new(day: usize) -> Self {
+        Self {
+            day,
+            parse: Duration::ZERO,
+            parts: vec![],
+            cached: vec![],
+            parse_alloc: None,
+            parts_alloc: vec![],
+        }
+    }
+
+    pub fn part(&self, part: Part) -> Option<Duration> {
+        self.parts.iter().find(|(p, _)| *p == part).map(|(_, d)| *d)
+    }
+
+    pub fn total(&self) -> Duration {
+        self.parse + self.parts.iter().map(|(_, d)| *d).sum::<Duration>()
+    }
+
+    pub fn part_alloc(&self, part: Part) -> Option<AllocStats> {
+        self.parts_alloc
+            .iter()
+            .find(|(p, _)| *p == part)
+            .map(|(_, a)| *a)
+    }
+
+    /// allocations of every step ; the parsed input staying alive while the parts run,
+    /// what the parse retained is added to the peak of the parts
+    pub fn alloc_total(&self) -> Option<AllocStats> {
+        let parse = self.parse_alloc.unwrap_or_default();
+        self.parts_alloc
+            .iter()
+            .map(|(_, a)| *a)
+            .reduce(|total, a| AllocStats {
+                allocations: total.allocations + a.allocations,
+                bytes: total.bytes + a.bytes,
+                peak: total.peak.max(a.peak),
+                retained: total.retained + a.retained,
+            })
+            .map(|parts| AllocStats {
+                allocations: parse.allocations + parts.allocations,
+                bytes: parse.bytes + parts.bytes,
+                peak: parse.peak.max(parse.retained + parts.peak),
+                retained: parse.retained + parts.retained,
+            })
+            .or(self.parse_alloc)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+}
+
+impl ReportFormat {
+    /// guessed from the report file extension
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Ok(ReportFormat::Json),
+            Some("csv") => Ok(ReportFormat::Csv),
+            _ => Err(anyhow!(
+                "cannot guess the report format of '{}' (.json or .csv)",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// durations are reported in milliseconds, with microsecond precision
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimingReport {
+    pub days: Vec<DayTiming>,
+}
+
+pub(crate) fn ms(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64() * 1000.0)
+}
+
+// extra fields of a json object, if the allocations were counted
+fn alloc_json(alloc: Option<AllocStats>) -> String {
+    alloc.map_or(String::new(), |a| {
+        format!(
+            ", \"allocations\": {}, \"alloc_bytes\": {}, \"peak_bytes\": {}",
+            a.allocations, a.bytes, a.peak
+        )
+    })
+}
+
+impl TimingReport {
+    pub fn total(&self) -> Duration {
+        self.days.iter().map(DayTiming::total).sum()
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Json => self.to_json(),
+            ReportFormat::Csv => self.to_csv(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let days = self
+            .days
+            .iter()
+            .map(|d| {
+                let parts = d
+                    .parts
+                    .iter()
+                    .map(|(p, t)| {
+                        let cached = if d.cached.contains(p) {
+                            ", \"cached\": true"
+                        } else {
+                            ""
+                        };
+                        format!(
+                            "{{\"part\": {}, \"solve_ms\": {}{}{}}}",
+                            p,
+                            ms(*t),
+                            cached,
+                            alloc_json(d.part_alloc(*p))
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(
+                    "    {{\"day\": {}, \"parse_ms\": {}{}, \"parts\": [{}], \"total_ms\": {}{}}}",
+                    d.day,
+                    ms(d.parse),
+                    alloc_json(d.parse_alloc),
+                    parts,
+                    ms(d.total()),
+                    alloc_json(d.alloc_total())
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",\n");
+        format!(
+            "{{\n  \"days\": [\n{}\n  ],\n  \"total_ms\": {}\n}}\n",
+            days,
+            ms(self.total())
+        )
+    }
+
+    /// one line per day, parts which were not run are left empty,
+    /// cached lists the parts answered from the cache ; the allocation
+    /// columns of the whole day are only there when they were counted
+    pub fn to_csv(&self) -> String {
+        let counted = self.days.iter().any(|d| d.alloc_total().is_some());
+        let mut csv = String::from("day,parse_ms,part1_ms,part2_ms,total_ms,cached");
+        if counted {
+            csv.push_str(",allocations,alloc_bytes,peak_bytes");
+        }
+        csv.push('\n');
+        for d in &self.days {
+            let part = |p| d.part(p).map(ms).unwrap_or_default();
+            write!(
+                csv,
+                "{},{},{},{},{},{}",
+                d.day,
+                ms(d.parse),
+                part(Part::One),
+                part(Part::Two),
+                ms(d.total()),
+                d.cached.iter().join(" ")
+            )
+            .unwrap();
+            if counted {
+                match d.alloc_total() {
+                    Some(a) => write!(csv, ",{},{},{}", a.allocations, a.bytes, a.peak).unwrap(),
+                    None => csv.push_str(",,,"),
+                }
+            }
+            csv.push('\n');
+        }
+        writeln!(
+            csv,
+            "total,,,,{},{}",
+            ms(self.total()),
+            if counted { ",,," } else { "" }
+        )
+        .unwrap();
+        csv
+    }
+}
+
+/// spread of repeated measures of the same step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    /// nearest rank 95th percentile
+    pub p95: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let n = sorted.len();
+
+        let median = if n.is_multiple_of(2) {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        } else {
+            sorted[n / 2]
+        };
+        let p95 = sorted[((n as f64 * 0.95).ceil() as usize).clamp(1, n) - 1];
+        let mean = sorted.iter().sum::<Duration>() / n as u32;
+        let variance = sorted
+            .iter()
+            .map(|s| (s.as_secs_f64() - mean.as_secs_f64()).powi(2))
+            .sum::<f64>()
+            / n as f64;
+
+        Some(Self {
+            min: sorted[0],
+            median,
+            mean,
+            p95,
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> TimingReport {
+        TimingReport {
+            days: vec![
+                DayTiming {
+                    day: 1,
+                    parse: Duration::from_micros(250),
+                    parts: vec![
+                        (Part::One, Duration::from_micros(1500)),
+                        (Part::Two, Duration::from_nanos(2_000_400)),
+                    ],
+                    cached: vec![],
+                    parse_alloc: None,
+                    parts_alloc: vec![],
+                },
+                DayTiming {
+                    day: 24,
+                    parse: Duration::ZERO,
+                    parts: vec![(Part::Two, Duration::from_secs(3))],
+                    cached: vec![],
+                    parse_alloc: None,
+                    parts_alloc: vec![],
+                },
+                DayTiming {
+                    day: 25,
+                    parse: Duration::ZERO,
+                    parts: vec![
+                        (Part::One, Duration::from_micros(20)),
+                        (Part::Two, Duration::from_micros(10)),
+                    ],
+                    cached: vec![Part::One, Part::Two],
+                    parse_alloc: None,
+                    parts_alloc: vec![],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn report_can_be_exported_as_csv() {
+        assert_eq!(
+            "\
+day,parse_ms,part1_ms,part2_ms,total_ms,cached
+1,0.250,1.500,2.000,3.750,
+24,0.000,,3000.000,3000.000,
+25,0.000,0.020,0.010,0.030,1 2
+total,,,,3003.780,
+",
+            report().to_csv()
+        );
+    }
+
+    #[test]
+    fn report_can_be_exported_as_json() {
+        assert_eq!(
+            r#"{
+  "days": [
+    {"day": 1, "parse_ms": 0.250, "parts": [{"part": 1, "solve_ms": 1.500}, {"part": 2, "solve_ms": 2.000}], "total_ms": 3.750},
+    {"day": 24, "parse_ms": 0.000, "parts": [{"part": 2, "solve_ms": 3000.000}], "total_ms": 3000.000},
+    {"day": 25, "parse_ms": 0.000, "parts": [{"part": 1, "solve_ms": 0.020, "cached": true}, {"part": 2, "solve_ms": 0.010, "cached": true}], "total_ms": 0.030}
+  ],
+  "total_ms": 3003.780
+}
+"#,
+            report().to_json()
+        );
+    }
+
+    #[test]
+    fn allocations_are_reported_when_counted() {
+        let mut report = report();
+        report.days[0].parse_alloc = Some(AllocStats {
+            allocations: 3,
+            bytes: 4000,
+            peak: 4000,
+            retained: 3000,
+        });
+        report.days[0].parts_alloc = vec![
+            (
+                Part::One,
+                AllocStats {
+                    allocations: 1,
+                    bytes: 1064,
+                    peak: 1064,
+                    retained: 0,
+                },
+            ),
+            (Part::Two, AllocStats::default()),
+        ];
+        report.days.truncate(2);
+
+        assert_eq!(
+            "\
+day,parse_ms,part1_ms,part2_ms,total_ms,cached,allocations,alloc_bytes,peak_bytes
+1,0.250,1.500,2.000,3.750,,4,5064,4064
+24,0.000,,3000.000,3000.000,,,,
+total,,,,3003.750,,,,
+",
+            report.to_csv()
+        );
+        assert!(report.to_json().contains(
+            r#"{"day": 1, "parse_ms": 0.250, "allocations": 3, "alloc_bytes": 4000, "peak_bytes": 4000, "parts": [{"part": 1, "solve_ms": 1.500, "allocations": 1, "alloc_bytes": 1064, "peak_bytes": 1064}, "#
+        ));
+        assert!(report.to_json().contains(
+            r#""total_ms": 3.750, "allocations": 4, "alloc_bytes": 5064, "peak_bytes": 4064}"#
+        ));
+    }
+
+    #[test]
+    fn stats_can_be_computed() {
+        assert_eq!(None, Stats::from_samples(&[]));
+
+        let samples: Vec<_> = [5, 1, 4, 2, 3, 100]
+            .into_iter()
+            .map(Duration::from_millis)
+            .collect();
+        let stats = Stats::from_samples(&samples).unwrap();
+        assert_eq!(Duration::from_millis(1), stats.min);
+        assert_eq!(Duration::from_micros(3500), stats.median);
+        assert_eq!(Duration::from_millis(115) / 6, stats.mean);
+        assert_eq!(Duration::from_millis(100), stats.p95);
+        assert_eq!(36, stats.stddev.as_millis());
+
+        let single = Stats::from_samples(&[Duration::from_millis(7)]).unwrap();
+        assert_eq!(Duration::from_millis(7), single.p95);
+        assert_eq!(Duration::ZERO, single.stddev);
+    }
+
+    #[test]
+    fn format_is_guessed_from_extension() {
+        assert_eq!(
+            ReportFormat::Json,
+            ReportFormat::from_path(Path::new("timings.json")).unwrap()
+        );
+        assert_eq!(
+            ReportFormat::Csv,
+            ReportFormat::from_path(Path::new("out/timings.csv")).unwrap()
+        );
+        assert!(ReportFormat::from_path(Path::new("timings.txt")).is_err());
+    }
+}
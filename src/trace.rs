@@ -0,0 +1,173 @@
+use anyhow::{anyhow, Result};
+use std::fmt::{self, Display};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::RwLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Info = 1,
+    Debug = 2,
+    /// step by step details, slowing the solvers down
+    Trace = 3,
+}
+
+impl Level {
+    pub fn parse(level: &str) -> Result<Self> {
+        match level.trim() {
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            unknown => Err(anyhow!(
+                "'{}' is not a valid trace level (info, debug or trace)",
+                unknown
+            )),
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        })
+    }
+}
+
+/// level of each target (a day module such as day24), and of the others if given
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TraceFilter {
+    others: Option<Level>,
+    targets: Vec<(String, Level)>,
+}
+
+impl TraceFilter {
+    /// 'day24=debug,day19=trace' or 'info,day24=trace', a bare level applying to every target
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut filter = TraceFilter::default();
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => filter
+                    .targets
+                    .push((target.trim().to_string(), Level::parse(level)?)),
+                None => filter.others = Some(Level::parse(directive)?),
+            }
+        }
+        if filter.others.is_none() && filter.targets.is_empty() {
+            return Err(anyhow!("no trace level given by '{}'", spec));
+        }
+        Ok(filter)
+    }
+
+    pub fn targets(&self) -> impl Iterator<Item = &str> {
+        self.targets.iter().map(|(target, _)| target.as_str())
+    }
+
+    pub fn level(&self, target: &str) -> Option<Level> {
+        self.targets
+            .iter()
+            .rev()
+            .find(|(t, _)| t == target)
+            .map(|(_, level)| *level)
+            .or(self.others)
+    }
+
+    fn max_level(&self) -> Option<Level> {
+        self.targets
+            .iter()
+            .map(|(_, l)| *l)
+            .chain(self.others)
+            .max()
+    }
+}
+
+// checked first, so that disabled messages cost a single load
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(0);
+static FILTER: RwLock<TraceFilter> = RwLock::new(TraceFilter {
+    others: None,
+    targets: Vec::new(),
+});
+
+pub fn init(filter: TraceFilter) {
+    MAX_LEVEL.store(filter.max_level().map_or(0, |l| l as u8), Ordering::Relaxed);
+    *FILTER.write().unwrap() = filter;
+}
+
+/// false for sure if no target is traced at this level
+#[inline]
+pub fn allows(level: Level) -> bool {
+    level as u8 <= MAX_LEVEL.load(Ordering::Relaxed)
+}
+
+pub fn enabled(target: &str, level: Level) -> bool {
+    allows(level)
+        && FILTER
+            .read()
+            .unwrap()
+            .level(target)
+            .is_some_and(|l| level <= l)
+}
+
+/// last part of a module path : aoc2021::day24 gives day24
+pub fn target(module_path: &str) -> &str {
+    module_path.rsplit("::").next().unwrap_or(module_path)
+}
+
+pub fn emit(target: &str, level: Level, message: fmt::Arguments) {
+    eprintln!("[{} {}] {}", target, level, message);
+}
+
+/// message of the given level, for the module it is written in
+macro_rules! trace_at {
+    ($level:expr, $($arg:tt)+) => {{
+        if $crate::trace::allows($level) {
+            let target = $crate::trace::target(module_path!());
+            if $crate::trace::enabled(target, $level) {
+                $crate::trace::emit(target, $level, format_args!($($arg)+));
+            }
+        }
+    }};
+}
+
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::trace::trace_at!($crate::trace::Level::Info, $($arg)+) };
+}
+
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::trace::trace_at!($crate::trace::Level::Debug, $($arg)+) };
+}
+
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::trace::trace_at!($crate::trace::Level::Trace, $($arg)+) };
+}
+
+pub(crate) use {debug, info, trace, trace_at};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_can_be_parsed() {
+        let filter = TraceFilter::parse("day24=debug, day19=trace").unwrap();
+        assert_eq!(Some(Level::Debug), filter.level("day24"));
+        assert_eq!(Some(Level::Trace), filter.level("day19"));
+        assert_eq!(None, filter.level("day1"));
+        assert_eq!(Some(Level::Trace), filter.max_level());
+
+        let filter = TraceFilter::parse("info,day24=trace").unwrap();
+        assert_eq!(Some(Level::Info), filter.level("day1"));
+        assert_eq!(Some(Level::Trace), filter.level("day24"));
+
+        assert!(TraceFilter::parse("day24=loud").is_err());
+        assert!(TraceFilter::parse("verbose").is_err());
+        assert!(TraceFilter::parse(",").is_err());
+    }
+
+    #[test]
+    fn targets_are_module_names() {
+        assert_eq!("day24", target("aoc2021::day24"));
+        assert_eq!("main", target("main"));
+    }
+}
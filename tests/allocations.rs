@@ -0,0 +1,35 @@
+use aoc2021::memory::{AllocMeter, CountingAllocator};
+use aoc2021::registry::{get_day, Part};
+use aoc2021::solver::Answer;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// the meters being shared by every thread, the steps are measured by a single test
+#[test]
+fn allocations_are_counted_by_step() {
+    assert!(aoc2021::memory::is_counting());
+
+    let meter = AllocMeter::start();
+    let held = vec![0u8; 1 << 20];
+    let freed = vec![0u8; 1 << 16];
+    drop(freed);
+    let stats = meter.stop();
+    assert_eq!(2, stats.allocations);
+    assert_eq!((1 << 20) + (1 << 16), stats.bytes);
+    assert_eq!((1 << 20) + (1 << 16), stats.peak);
+    assert_eq!(1 << 20, stats.retained);
+    drop(held);
+
+    let meter = AllocMeter::start();
+    let nothing = meter.stop();
+    assert_eq!(0, nothing.allocations);
+    assert_eq!(0, nothing.peak);
+
+    let day = get_day(1).unwrap();
+    let meter = AllocMeter::start();
+    let solution = (day.parse)("199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n").unwrap();
+    let parse = meter.stop();
+    assert!(parse.allocations > 0 && parse.peak > 0);
+    assert_eq!(Answer::Number(7), solution.solve(Part::One).unwrap());
+}
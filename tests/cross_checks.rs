@@ -0,0 +1,36 @@
+use aoc2021::registry::all_days;
+use aoc2021::rng::Rng;
+
+// seeds checked for every day, the slowest days being checked on fewer of them
+const SEEDS: u64 = 50;
+const SLOW_DAYS: [(usize, u64); 3] = [(19, 6), (21, 6), (24, 12)];
+
+// every alternative strategy must give the same answer as its default one,
+// on small random inputs where the slowest of them still finish quickly
+#[test]
+fn variants_agree_with_their_part() {
+    for day in all_days() {
+        let seeds = SLOW_DAYS
+            .iter()
+            .find(|(number, _)| *number == day.number)
+            .map_or(SEEDS, |(_, seeds)| *seeds);
+        for seed in 0..seeds {
+            let size = 2 + seed as usize % 4;
+            let input = (day.generate)(&mut Rng::new(seed), size);
+            let solution = (day.parse)(&input).unwrap();
+            for (name, part) in solution.variants() {
+                assert_eq!(
+                    solution.solve(part).unwrap(),
+                    solution.solve_variant(name).unwrap(),
+                    "day {} part {} '{}' disagrees, seed {} size {} on\n{}",
+                    day.number,
+                    part,
+                    name,
+                    seed,
+                    size,
+                    input
+                );
+            }
+        }
+    }
+}
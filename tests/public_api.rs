@@ -0,0 +1,176 @@
+use aoc2021::alu::{decode, ALU};
+use aoc2021::amphipod::{least_energy, unfold, Disposition};
+use aoc2021::bits::Packet;
+use aoc2021::cuboid::{count_lit_cubes, Command, Cuboid};
+use aoc2021::diagnostic::{Diagnosis, TiePolicies, TiePolicy};
+use aoc2021::export::ImageFormat;
+use aoc2021::navigation::{parse_plan, trajectory, Interpretation, Position};
+use aoc2021::registry::{all_days, get_day, Part};
+use aoc2021::rng::Rng;
+use aoc2021::snailfish::FishNumber;
+use aoc2021::solver::Answer;
+use aoc2021::sonar::SonarAnalyser;
+use itertools::Itertools;
+
+#[test]
+fn fish_numbers_can_be_added() {
+    let sum = FishNumber::try_read("[[[[4,3],4],4],[7,[[8,4],9]]]").unwrap()
+        + FishNumber::try_read("[1,1]").unwrap();
+    assert_eq!(
+        FishNumber::try_read("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]").unwrap(),
+        sum
+    );
+    assert_eq!(1384, sum.magnitude());
+}
+
+#[test]
+fn days_can_print_their_answers() {
+    for day in all_days() {
+        (day.print)("", Part::One);
+    }
+    (get_day(1).unwrap().print)("199\n200\n208\n", Part::Two);
+}
+
+#[test]
+fn sonar_logs_can_be_streamed() {
+    let log = std::io::Cursor::new("1\n2\n3\n3\n1\n5\n");
+    let stats = SonarAnalyser::analyse(log, &[2, 1]).unwrap();
+    assert_eq!(
+        (2, 3, 1),
+        (stats[0].window, stats[0].increases, stats[0].decreases)
+    );
+    assert_eq!(3, stats[1].longest_increasing_run);
+    assert_eq!(1, stats[1].plateaus);
+}
+
+#[test]
+fn trajectories_can_be_diffed() {
+    let plan = parse_plan("down 2\nforward 3\nup 5").unwrap();
+    let plain = trajectory(&Position::default(), &plan, Interpretation::Plain).unwrap();
+    let aimed = trajectory(&Position::default(), &plan, Interpretation::Aimed).unwrap();
+    assert_eq!(-3, plain[3].depth);
+    assert_eq!((6, -3), (aimed[3].depth, aimed[3].aim));
+}
+
+#[test]
+fn diagnostic_ties_can_be_chosen() {
+    let report = "10\n01\n11\n00\n";
+    let ties = TiePolicies {
+        oxygen: TiePolicy::FavourZero,
+        ..TiePolicies::default()
+    };
+    let diag = Diagnosis::with_ties(report, ties).unwrap();
+    assert_eq!("00", diag.oxygen().to_string());
+    assert_eq!(Some(3), diag.gamma().to_usize());
+    assert_eq!(2, diag.columns()[1].ones);
+    assert!(!diag.epsilon().is_empty());
+}
+
+#[test]
+fn packets_can_be_decoded() {
+    let packet = Packet::from_hexa("9C0141080250320F1802104A08").unwrap();
+    assert_eq!(20, packet.sum_version());
+    assert_eq!(1, packet.value());
+}
+
+#[test]
+fn alu_can_run_a_program() {
+    let instructions = decode("inp w\nadd z w\nmod z 2\ndiv w 2\nadd y w\nmod y 2").unwrap();
+    let mut alu = ALU::new(&instructions);
+    alu.resume(Some(7));
+    assert_eq!(&[3, 0, 1, 1], alu.registers());
+}
+
+#[test]
+fn cuboids_can_be_combined() {
+    let a = Cuboid::new((10, 12), (10, 12), (10, 12));
+    let b = Cuboid::new((11, 13), (11, 13), (11, 13));
+    assert_eq!(8, a.intersect(&b).unwrap().size());
+
+    let steps: Vec<Command> = [
+        Command::new(true, a),
+        Command::new(true, b),
+        "off x=9..11,y=9..11,z=9..11".parse().unwrap(),
+        "on x=10..10,y=10..10,z=10..10".parse().unwrap(),
+    ]
+    .into();
+    assert_eq!(39, count_lit_cubes(&steps));
+}
+
+#[test]
+fn amphipods_can_be_organized() {
+    let diagram = "\
+#############
+#...........#
+###B#C#B#D###
+  #A#D#C#A#
+  #########";
+    let start: Disposition<2> = diagram.parse().unwrap();
+    assert_eq!(Some(12521), least_energy(&start));
+    let unfolded: Disposition<4> = unfold(diagram).parse().unwrap();
+    assert_eq!(Some(44169), least_energy(&unfolded));
+}
+
+#[test]
+fn days_can_be_solved_from_the_registry() {
+    let day = get_day(1).unwrap();
+    let solution = (day.parse)("199\n200\n208\n210\n200\n207\n240\n269\n260\n263").unwrap();
+    assert_eq!(Answer::Number(7), solution.solve(Part::One).unwrap());
+    assert_eq!(Answer::Number(5), solution.solve(Part::Two).unwrap());
+}
+
+#[test]
+fn generated_inputs_can_be_parsed() {
+    for (day, size) in all_days().iter().cartesian_product([1, 4]) {
+        let input = (day.generate)(&mut Rng::new(3), size);
+        assert_eq!(input, (day.generate)(&mut Rng::new(3), size));
+        if let Err(e) = (day.parse)(&input) {
+            panic!("day {} cannot parse\n{}\n{:?}", day.number, input, e);
+        }
+    }
+}
+
+#[test]
+fn examples_give_the_puzzle_answers() {
+    for day in all_days() {
+        for example in day.examples {
+            let solution = (day.parse)(example.input).unwrap();
+            for part in Part::all() {
+                if let Some(expected) = example.expected(*part) {
+                    assert_eq!(
+                        expected,
+                        solution.solve(*part).unwrap().to_string().trim_end(),
+                        "day {} part {} on the {} example",
+                        day.number,
+                        part,
+                        example.name
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn spatial_days_can_be_exported() {
+    for day in all_days() {
+        let Some(example) = day.examples.first() else {
+            continue;
+        };
+        let picture = (day.parse)(example.input).unwrap().picture();
+        let spatial = [2, 5, 9, 13, 15, 20, 25].contains(&day.number);
+        assert_eq!(spatial, picture.is_some(), "day {}", day.number);
+        if let Some(picture) = picture {
+            let png = ImageFormat::Png.encode(&picture, 2);
+            assert!(png.starts_with(b"\x89PNG"), "day {}", day.number);
+        }
+    }
+
+    let paper = (get_day(13).unwrap().parse)(get_day(13).unwrap().examples[0].input).unwrap();
+    let picture = paper.picture().unwrap();
+    assert_eq!((7, 7), picture.dim());
+    assert_eq!(
+        16,
+        picture.values().filter(|c| **c != picture[(0, 0)]).count()
+    );
+}